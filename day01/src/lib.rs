@@ -8,9 +8,10 @@ use log::info;
 #[cfg(test)]
 use std::println as info;
 
-use mathlib::modulus;
+use mathlib::ModInt;
 
 type PuzzleInt = i32;
+type Dial = ModInt<100>;
 
 // 0 through 99 in order
 // Right = addition
@@ -32,7 +33,8 @@ fn convert_ints(string_list: &Vec<String>) -> Vec<PuzzleInt> {
 }
 
 fn move_int(value: PuzzleInt, rotation: PuzzleInt) -> PuzzleInt {
-    return modulus(value + rotation, 100);
+    let dial = Dial::new(value) + Dial::new(rotation);
+    return dial.value() as PuzzleInt;
 }
 
 /// Count how many times we arrive at 0.
@@ -56,22 +58,9 @@ pub fn puzzle_a(string_list: &Vec<String>) -> usize {
 }
 
 fn move_counting_0s(value: PuzzleInt, rotation: PuzzleInt) -> (PuzzleInt, usize) {
-    let mut count = usize::try_from(rotation.abs() / 100).unwrap();
-    let remainder_0 = rotation % 100;
-
-    info!(
-        "After dividing, have count {}, and remainder {}",
-        count, remainder_0
-    );
-
-    // next check if modulus is different from the addition
-    let addition = value + remainder_0;
-    let modded = modulus(addition, 100);
-    if value != 0 && modded != addition || addition == 0 {
-        count += 1;
-        info!("Detected past 0, count is {}", count);
-    }
-    return (modded, count);
+    let (dial, count) = Dial::new(value).step_counting_zeros(rotation as i64);
+    info!("Moved to {}, passing 0 {} times", dial, count);
+    return (dial.value() as PuzzleInt, count as usize);
 }
 
 /// Count how many times we pass by 0.
@@ -2,9 +2,11 @@ extern crate filelib;
 
 pub use filelib::load_no_blanks;
 use log::info;
+use mathlib::{digit_count, factorize, repeat_multiplier, sum_multiples, sum_multiples_of_any};
 
 type RangeType = usize;
-type ResultType = usize;
+// Sums over huge ranges don't fit in 64 bits.
+type ResultType = u128;
 
 fn get_ranges(input: &String) -> Vec<(RangeType, RangeType)> {
     let mut output = vec![];
@@ -17,40 +19,57 @@ fn get_ranges(input: &String) -> Vec<(RangeType, RangeType)> {
     return output;
 }
 
-fn find_invalid_ids(first: RangeType, last: RangeType) -> Vec<ResultType> {
-    // Naive way, iterate over, returning ever invalid id
+// Every digit length between first and last, with the part of the range that has that many digits.
+fn ranges_by_length(first: RangeType, last: RangeType) -> Vec<(u32, u64, u64)> {
+    let (first, last) = (first as u64, last as u64);
     let mut output = vec![];
-    for i in first..=last {
-        if is_repeated_twice(i) {
-            info!("Found repeat: {}", i);
-            output.push(i.try_into().expect("u32 should fit in u64"));
-        }
+    for length in digit_count(first, 10)..=digit_count(last, 10) {
+        let smallest = 10u64.pow(length - 1);
+        let largest = 10u64.checked_pow(length).map_or(u64::MAX, |v| v - 1);
+        output.push((length, first.max(smallest), last.min(largest)));
     }
     return output;
 }
 
-fn is_repeated_twice(number: RangeType) -> bool {
-    // Must have 2+ digits, must be even number of digits
-    if number < 10 {
-        return false;
+fn sum_repeated_twice(first: RangeType, last: RangeType) -> ResultType {
+    // Within one digit length, a seed repeated twice is the seed times 10..01, e.g. 1001.
+    // So these are just the multiples of that in the range, and those are an arithmetic series.
+    let mut total = 0;
+    for (length, low, high) in ranges_by_length(first, last) {
+        if !length.is_multiple_of(2) {
+            continue;
+        }
+        let multiplier = repeat_multiplier(length / 2, 2, 10).expect("Fits as the range does");
+        info!(
+            "Length {}: multiples of {} in {}-{}",
+            length, multiplier, low, high
+        );
+        total += sum_multiples(multiplier, low, high);
     }
+    return total;
+}
 
-    // log10 is the number of digits.
-    let digit_count = (number as f64).log10().floor() as RangeType + 1;
-    if digit_count % 2 != 0 {
-        return false;
+fn sum_repeated_any_number_of_times(first: RangeType, last: RangeType) -> ResultType {
+    // Same idea, but now any block length dividing the digit length, e.g. 1001001 for 3 of 3.
+    // Something like 111111 repeats in blocks of 1, 2 and 3, so inclusion-exclusion stops it
+    // being counted three times. Blocks of 1 are covered by blocks of 2 and 3 anyway, so
+    // only the longest blocks, length / prime, are needed.
+    let mut total = 0;
+    for (length, low, high) in ranges_by_length(first, last) {
+        let multipliers: Vec<u64> = factorize(length as u64)
+            .iter()
+            .map(|&(p, _)| {
+                let block = length / p as u32;
+                repeat_multiplier(block, length / block, 10).expect("Fits as the range does")
+            })
+            .collect();
+        info!(
+            "Length {}: multiples of {:?} in {}-{}",
+            length, multipliers, low, high
+        );
+        total += sum_multiples_of_any(&multipliers, low, high);
     }
-
-    // Midpoint divisor to check first vs second half
-    let base: RangeType = 10;
-    let divisor = base.pow(
-        (digit_count / 2)
-            .try_into()
-            .expect("Anything larger than a u32 here naturally cannot be used as an exponent"),
-    );
-    let first_half = number / divisor;
-    let second_half = number % divisor;
-    return first_half == second_half;
+    return total;
 }
 
 /// Find all strings repeated twice, add them together
@@ -64,36 +83,10 @@ pub fn puzzle_a(string_list: &Vec<String>) -> ResultType {
     let ranges = get_ranges(string_list.first().expect("At least one line of input"));
     return ranges
         .iter()
-        .map(|(first, last)| {
-            find_invalid_ids(*first, *last)
-                .into_iter()
-                .sum::<ResultType>()
-        })
+        .map(|(first, last)| sum_repeated_twice(*first, *last))
         .sum::<ResultType>();
 }
 
-fn find_invalid_ids_b(first: RangeType, last: RangeType) -> Vec<ResultType> {
-    // Naive way, iterate over, returning every invalid id
-    let mut output = vec![];
-    for i in first..=last {
-        if is_repeated_any_number_of_times(i) {
-            info!("Found repeat: {}", i);
-            output.push(i.try_into().expect("u32 should fit in u64"));
-        }
-    }
-    return output;
-}
-
-fn is_repeated_any_number_of_times(number: RangeType) -> bool {
-    // Maybe accidentally solved problem 2 ahead of time, lol.
-    let s = number.to_string();
-    let doubled = format!("{}{}", s, s);
-    let search_area = &doubled[1..doubled.len() - 1];
-    // If the original sring exists in the shaved double, its periodic.
-    // This is called a rotation trick.
-    return search_area.contains(&s);
-}
-
 /// Find all strings repeated any number of times, return them together.
 /// ```
 /// let vec1: Vec<String> = vec![
@@ -105,17 +98,52 @@ pub fn puzzle_b(string_list: &Vec<String>) -> ResultType {
     let ranges = get_ranges(string_list.first().expect("At least one line of input"));
     return ranges
         .iter()
-        .map(|(first, last)| {
-            find_invalid_ids_b(*first, *last)
-                .into_iter()
-                .sum::<ResultType>()
-        })
+        .map(|(first, last)| sum_repeated_any_number_of_times(*first, *last))
         .sum::<ResultType>();
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use mathlib::{digit_halves, is_digit_periodic};
+
+    // The brute force versions, checking every id one at a time.
+    fn find_invalid_ids(first: RangeType, last: RangeType) -> Vec<ResultType> {
+        // Naive way, iterate over, returning ever invalid id
+        let mut output = vec![];
+        for i in first..=last {
+            if is_repeated_twice(i) {
+                info!("Found repeat: {}", i);
+                output.push(i.try_into().expect("u32 should fit in u64"));
+            }
+        }
+        return output;
+    }
+
+    fn is_repeated_twice(number: RangeType) -> bool {
+        // Must have an even number of digits, then the halves have to match.
+        return match digit_halves(number as u64, 10) {
+            Some((first_half, second_half)) => first_half == second_half,
+            None => false,
+        };
+    }
+
+    fn find_invalid_ids_b(first: RangeType, last: RangeType) -> Vec<ResultType> {
+        // Naive way, iterate over, returning every invalid id
+        let mut output = vec![];
+        for i in first..=last {
+            if is_repeated_any_number_of_times(i) {
+                info!("Found repeat: {}", i);
+                output.push(i.try_into().expect("u32 should fit in u64"));
+            }
+        }
+        return output;
+    }
+
+    fn is_repeated_any_number_of_times(number: RangeType) -> bool {
+        // Maybe accidentally solved problem 2 ahead of time, lol.
+        return is_digit_periodic(number as u64, 10);
+    }
 
     #[test]
     fn test_get_ranges() {
@@ -180,4 +208,49 @@ mod tests {
         let expected = vec![11111];
         assert_eq!(result, expected);
     }
+
+    #[test]
+    fn test_closed_form_matches_brute_force() {
+        let ranges = vec![
+            (1, 100_000),
+            (95, 115),
+            (998, 1012),
+            (1188511880, 1188511890),
+            (565653, 565659),
+            (123_456, 1_234_567),
+        ];
+        for (first, last) in ranges {
+            let twice: ResultType = find_invalid_ids(first, last).into_iter().sum();
+            assert_eq!(sum_repeated_twice(first, last), twice, "{}-{}", first, last);
+            let any: ResultType = find_invalid_ids_b(first, last).into_iter().sum();
+            assert_eq!(
+                sum_repeated_any_number_of_times(first, last),
+                any,
+                "{}-{}",
+                first,
+                last
+            );
+        }
+    }
+
+    #[test]
+    fn test_closed_form_huge_range() {
+        // Far too many ids to check one at a time, but every 16 digit length has to be counted
+        let first = 1_000_000_000_000_000;
+        let last = 9_999_999_999_999_999;
+        // 10000000 through 99999999, each times 100000001
+        let seeds: u128 = (10_000_000 + 99_999_999) * 90_000_000 / 2;
+        assert_eq!(sum_repeated_twice(first, last), seeds * 100_000_001);
+        // Any block length dividing 16 also divides 8, so nothing new turns up in part b
+        assert_eq!(
+            sum_repeated_any_number_of_times(first, last),
+            sum_repeated_twice(first, last)
+        );
+        // 15 digits can't be two halves, but can be blocks of 3 or 5
+        assert_eq!(sum_repeated_twice(first / 10, first - 1), 0);
+        assert!(sum_repeated_any_number_of_times(first / 10, first - 1) > 0);
+        // Single digits never repeat
+        assert_eq!(sum_repeated_twice(1, 9), 0);
+        assert_eq!(sum_repeated_any_number_of_times(11, 11), 11);
+    }
 }
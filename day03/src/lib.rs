@@ -1,6 +1,7 @@
 extern crate filelib;
 
 pub use filelib::load_no_blanks;
+use mathlib::from_digits;
 
 #[cfg(not(test))]
 use log::info;
@@ -39,7 +40,8 @@ fn get_joltage(bank: &Vec<Battery>) -> Battery {
         .max()
         .expect("At least one value should exist so should max");
     info!("Returning {} and {}", tens, ones);
-    return tens * 10 + ones;
+    let joltage = from_digits(&[tens as u64, ones as u64], 10).expect("Two digits fit");
+    return joltage as Battery;
 }
 
 /// On each line, find two highest numbers, create a number from them, and add them up.
@@ -55,48 +57,32 @@ pub fn puzzle_a(string_list: &Vec<String>) -> Battery {
 }
 
 fn get_large_joltage(bank: &Vec<Battery>, steps: u32) -> LargeBattery {
-    // Recursive is easier to write, and since steps is 1->12, we will never stack overflow.
-    if steps == 0 {
-        // Should never happen, this is here just in case.
-        return 0;
-    }
-    if steps == 1 {
-        // just return the max of the remaining digits
-        return (*bank.iter().max().expect("must exist"))
-            .try_into()
-            .unwrap();
-    }
-
     let steps_usize: usize = steps.try_into().unwrap();
-
-    // What do we need to search? Consider 12 and
-    // 818181911112111
-    // We know it needs 12 digits, so we can remove the last 11
-    // 8181
-    // Because its 11 not 12, we need to remember to add 1.
-    let max_search = bank.len() - steps_usize + 1;
-    let search_bank = &bank[0..max_search];
-    info!("Searching {:?}", search_bank);
-
-    // Now that we have what to search, find the max.
-    let max = *search_bank.iter().max().unwrap();
-    // And find the index again.
-    let index = bank
-        .iter()
-        .position(|&r| r == max)
-        .expect("It should exist");
-
-    // Now we need to start creating the result. Its larger than a u32, so convert the type
-    // The digit we are at it based on steps. Remember that if this was the 2nd last digit (steps 2) we would it to be 10 (10^1).
-    let max_large: LargeBattery = max.try_into().unwrap();
-    let base: LargeBattery = 10;
-    let v: LargeBattery = base.pow(steps - 1) * max_large;
-
-    // Now what do we want our sub check to look at?
-    // Well it needs to ignore us, and then all the rest of the numbers are available.
-    let sub_bank = bank[index + 1..].to_vec();
-    info!("Found {} getting subdigits", v);
-    return v + get_large_joltage(&sub_bank, steps - 1);
+    let mut chosen: Vec<u64> = vec![];
+    let mut start = 0;
+    for remaining in (1..=steps_usize).rev() {
+        // What do we need to search? Consider 12 and
+        // 818181911112111
+        // We know it needs 12 digits, so we can remove the last 11
+        // 8181
+        // Because its 11 not 12, we need to remember to add 1.
+        let max_search = bank.len() - remaining + 1;
+        let search_bank = &bank[start..max_search];
+        info!("Searching {:?}", search_bank);
+
+        // Now that we have what to search, find the max, and the first place it shows up.
+        let max = *search_bank.iter().max().expect("must exist");
+        let index = search_bank
+            .iter()
+            .position(|&r| r == max)
+            .expect("It should exist");
+        chosen.push(max.into());
+
+        // The next digit has to come after this one.
+        start += index + 1;
+    }
+    info!("Found digits {:?}", chosen);
+    return from_digits(&chosen, 10).expect("Joltage fits in a u64");
 }
 
 /// Same thing but now its twelve numbers instead of 2.
@@ -5,12 +5,6 @@ pub use filelib::load_no_blanks;
 use gridlib::GridTraversable;
 use log::info;
 
-use std::collections::HashSet;
-use std::collections::{HashMap, VecDeque};
-
-type QueueType = VecDeque<gridlib::GridCoordinate>;
-type NeighborMap = HashMap<gridlib::GridCoordinate, usize>;
-
 #[derive(Copy, Clone, PartialEq, Eq)]
 enum GridType {
     Paper,
@@ -24,6 +18,13 @@ impl gridlib::GridPrintable for GridType {
             GridType::Empty => '.',
         };
     }
+
+    fn get_foreground(&self) -> Option<gridlib::Color> {
+        return match self {
+            GridType::Paper => Some(gridlib::Color::WHITE),
+            GridType::Empty => None,
+        };
+    }
 }
 
 type ParsedGrid = gridlib::Grid<GridType>;
@@ -56,6 +57,10 @@ impl gridlib::GridPrintable for Overlay {
     fn get_character(&self) -> char {
         return 'X';
     }
+
+    fn get_foreground(&self) -> Option<gridlib::Color> {
+        return Some(gridlib::Color::new(255, 64, 64));
+    }
 }
 
 impl gridlib::GridOverlay for Overlay {
@@ -92,7 +97,11 @@ fn max_neighbors(grid: &ParsedGrid, max_neighbors: usize) -> Vec<Overlay> {
 
 fn print_solution(grid: &ParsedGrid, overlay: &Vec<Overlay>) {
     info!("Grid solution {}:\n", overlay.len());
-    for line in grid.grid_strings_with_overlay(overlay.clone()) {
+    let mut renderer = gridlib::TerminalRenderer::new();
+    // Colour the removed rolls, but keep test output plain
+    renderer.set_color(!cfg!(test));
+    renderer.add_layer(0, overlay.iter().copied());
+    for line in renderer.render(grid).lines {
         info!("{}", line);
     }
 }
@@ -112,77 +121,128 @@ pub fn puzzle_a(string_list: &Vec<String>) -> usize {
     return solution.len();
 }
 
-fn create_search_space(grid: &ParsedGrid) -> (NeighborMap, QueueType) {
-    let mut neighbor_counts: HashMap<gridlib::GridCoordinate, usize> = HashMap::new();
-    let mut queue: VecDeque<gridlib::GridCoordinate> = VecDeque::new();
-
-    for coord in grid.coord_iter() {
-        if grid.get_value(coord).unwrap() == GridType::Paper {
-            // Count neighbors manually once
-            let count = grid
-                .get_all_adjacent_coordinates(coord)
-                .iter()
-                .filter(|&&adj| grid.get_value(adj).unwrap() == GridType::Paper)
-                .count();
-
-            neighbor_counts.insert(coord, count);
-
-            if count < 4 {
-                queue.push_back(coord);
-            }
-        }
+// Paper with too few neighbors gets removed, which can expose more paper.
+fn removal_rule(cell: GridType, neighbors: &[GridType]) -> GridType {
+    if cell == GridType::Paper && neighbors.iter().filter(|&&n| n == GridType::Paper).count() < 4 {
+        return GridType::Empty;
     }
-    return (neighbor_counts, queue);
+    return cell;
 }
 
-// Optimized solution, use muts to prevent cloning that would make this operation much longer.
-fn queue_removal(
-    grid: &mut ParsedGrid,
-    neighbor_counts: &mut NeighborMap,
-    queue: &mut QueueType,
-) -> usize {
-    let mut total_removed = 0;
+/// Iterate over the grid, removing paper until none can be removed.
+/// ```
+/// let vec1: Vec<String> = vec![
+///     "..@@.@@@@.", "@@@.@.@.@@", "@@@@@.@.@@", "@.@@@@..@.", "@@.@@@@.@@",
+///     ".@@@@@@@.@", ".@.@.@.@@@", "@.@@@.@@@@", ".@@@@@@@@.", "@.@.@@@.@."
+/// ].iter().map(|s| s.to_string()).collect();
+/// assert_eq!(day04::puzzle_b(&vec1), 43);
+/// ```
+pub fn puzzle_b(string_list: &Vec<String>) -> usize {
+    let parsed = parse_grid(string_list);
+    // Worklist mode only revisits cells next to a removal, rather than the whole grid.
+    let mut automaton = gridlib::Automaton::new(parsed, gridlib::Adjacency::All);
+    let run = automaton.run(gridlib::UpdateMode::Worklist, None, removal_rule);
+    info!("Removed paper in {} waves", run.changes.len());
+    return run.total_changes();
+}
 
-    // To prevent processing the same node twice if it gets added to the queue multiple times
-    let mut processed = HashSet::new();
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gridlib::{Automaton, StopReason, UpdateMode};
+    use std::collections::{HashMap, HashSet, VecDeque};
+
+    fn example() -> ParsedGrid {
+        let vec1: Vec<String> = [
+            "..@@.@@@@.",
+            "@@@.@.@.@@",
+            "@@@@@.@.@@",
+            "@.@@@@..@.",
+            "@@.@@@@.@@",
+            ".@@@@@@@.@",
+            ".@.@.@.@@@",
+            "@.@@@.@@@@",
+            ".@@@@@@@@.",
+            "@.@.@@@.@.",
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+        return parse_grid(&vec1);
+    }
 
-    while let Some(curr_coord) = queue.pop_front() {
-        if processed.contains(&curr_coord) {
-            continue;
+    // The original queue of removals, kept to check the automaton against.
+    fn queue_removal(grid: &mut ParsedGrid) -> usize {
+        let mut neighbor_counts: HashMap<gridlib::GridCoordinate, usize> = HashMap::new();
+        let mut queue: VecDeque<gridlib::GridCoordinate> = VecDeque::new();
+        for coord in grid.coord_iter() {
+            if grid.get_value(coord).unwrap() == GridType::Paper {
+                let count = grid
+                    .get_all_adjacent_coordinates(coord)
+                    .iter()
+                    .filter(|&&adj| grid.get_value(adj).unwrap() == GridType::Paper)
+                    .count();
+                neighbor_counts.insert(coord, count);
+                if count < 4 {
+                    queue.push_back(coord);
+                }
+            }
         }
-        grid.set_value(curr_coord, GridType::Empty);
-        processed.insert(curr_coord);
-        total_removed += 1;
 
-        // We only care about neighbors that are currently Paper
-        let neighbors = grid.get_all_adjacent_coordinates(curr_coord);
-
-        for neighbor in neighbors {
-            if let Some(count) = neighbor_counts.get_mut(&neighbor) {
-                if *count > 0 {
-                    *count -= 1;
-
-                    if *count < 4 {
-                        queue.push_back(neighbor);
+        let mut total_removed = 0;
+        let mut processed = HashSet::new();
+        while let Some(curr_coord) = queue.pop_front() {
+            if !processed.insert(curr_coord) {
+                continue;
+            }
+            grid.set_value(curr_coord, GridType::Empty);
+            total_removed += 1;
+            for neighbor in grid.get_all_adjacent_coordinates(curr_coord) {
+                if let Some(count) = neighbor_counts.get_mut(&neighbor) {
+                    if *count > 0 {
+                        *count -= 1;
+                        if *count < 4 {
+                            queue.push_back(neighbor);
+                        }
                     }
                 }
             }
         }
+        return total_removed;
     }
 
-    return total_removed;
-}
+    #[test]
+    fn test_removal_rule() {
+        let paper = GridType::Paper;
+        let empty = GridType::Empty;
+        assert!(removal_rule(paper, &[paper, paper, paper, empty]) == empty);
+        assert!(removal_rule(paper, &[paper, paper, paper, paper]) == paper);
+        assert!(removal_rule(paper, &[paper; 8]) == paper);
+        // Only paper counts, and nothing becomes paper
+        assert!(removal_rule(paper, &[empty; 8]) == empty);
+        assert!(removal_rule(empty, &[paper; 8]) == empty);
+    }
 
-/// Iterate over the grid, removing paper until none can be removed.
-/// ```
-/// let vec1: Vec<String> = vec![
-///     "..@@.@@@@.", "@@@.@.@.@@", "@@@@@.@.@@", "@.@@@@..@.", "@@.@@@@.@@",
-///     ".@@@@@@@.@", ".@.@.@.@@@", "@.@@@.@@@@", ".@@@@@@@@.", "@.@.@@@.@."
-/// ].iter().map(|s| s.to_string()).collect();
-/// assert_eq!(day04::puzzle_b(&vec1), 43);
-/// ```
-pub fn puzzle_b(string_list: &Vec<String>) -> usize {
-    let mut parsed = parse_grid(string_list);
-    let (mut neighbor_counts, mut queue) = create_search_space(&parsed);
-    return queue_removal(&mut parsed, &mut neighbor_counts, &mut queue);
+    #[test]
+    fn test_waves_match_queue() {
+        let mut expected = example();
+        let removed = queue_removal(&mut expected);
+
+        // In lockstep each generation is one wave, the first is everything part a finds
+        let mut sync = Automaton::new(example(), gridlib::Adjacency::All);
+        let sync_run = sync.run(UpdateMode::Synchronous, None, removal_rule);
+        assert_eq!(sync_run.stop, StopReason::FixedPoint);
+        assert_eq!(sync_run.changes, vec![13, 12, 7, 5, 2, 1, 1, 1, 1]);
+        assert_eq!(sync_run.changes[0], max_neighbors(&example(), 4).len());
+        assert_eq!(sync_run.total_changes(), removed);
+        assert!(*sync.get_grid() == expected);
+
+        // Updating in place lets removals cascade within a pass, so there are fewer generations
+        let mut worklist = Automaton::new(example(), gridlib::Adjacency::All);
+        let worklist_run = worklist.run(UpdateMode::Worklist, None, removal_rule);
+        assert_eq!(worklist_run.stop, StopReason::FixedPoint);
+        assert_eq!(worklist_run.changes, vec![30, 6, 4, 1, 2]);
+        assert_eq!(worklist_run.total_changes(), removed);
+        assert!(*worklist.get_grid() == expected);
+    }
 }
@@ -4,6 +4,7 @@ pub use filelib::load;
 pub use filelib::split_lines_by_blanks;
 #[cfg(not(test))]
 use log::info;
+use mathlib::RangeSet;
 
 #[cfg(test)]
 use std::println as info;
@@ -28,14 +29,12 @@ fn parse_available_ids(string_list: &Vec<String>) -> Vec<ID> {
     return string_list.iter().map(|s| s.parse().unwrap()).collect();
 }
 
-fn is_fresh(ranges: &Vec<(ID, ID)>, ingreident: ID) -> bool {
-    for (start, end) in ranges {
-        if *start <= ingreident && *end >= ingreident {
-            info!("Is fresh {}", ingreident);
-            return true;
-        }
+fn is_fresh(fresh: &RangeSet<ID>, ingreident: ID) -> bool {
+    let result = fresh.contains(ingreident);
+    if result {
+        info!("Is fresh {}", ingreident);
     }
-    return false;
+    return result;
 }
 
 /// Count number of fresh ingredients.
@@ -47,56 +46,23 @@ fn is_fresh(ranges: &Vec<(ID, ID)>, ingreident: ID) -> bool {
 /// assert_eq!(day05::puzzle_a(&vec1), 3);
 /// ```
 pub fn puzzle_a(string_list: &Vec<Vec<String>>) -> usize {
-    let mut ranges = parse_fresh_id_ranges(&string_list[0]);
-    ranges.sort_by(|&a, &b| a.0.cmp(&b.0));
+    let fresh = merge_ranges(&parse_fresh_id_ranges(&string_list[0]));
     let available = parse_available_ids(&string_list[1]);
     return available
         .iter()
-        .filter(|&&ing| is_fresh(&ranges, ing))
+        .filter(|&&ing| is_fresh(&fresh, ing))
         .count();
 }
 
-fn merge_ranges(ranges: &Vec<(ID, ID)>) -> Vec<(ID, ID)> {
-    let mut merged = vec![];
-    let mut current_merged_range = ranges[0];
-    let mut first = true;
-    for r in ranges {
-        if first {
-            info!("Expanding {} {}", r.0, r.1);
-            first = false;
-            current_merged_range = r.clone();
-            continue;
-        }
-        // if start of this range is less than the end of the previous, merge them
-        // This is valid because of the earlier sort.
-        if r.0 <= current_merged_range.1 {
-            if current_merged_range.1 < r.1 {
-                current_merged_range.1 = r.1;
-            }
-        } else {
-            // new range
-            info!(
-                "Merged range: {} {}",
-                current_merged_range.0, current_merged_range.1
-            );
-            merged.push(current_merged_range);
-
-            info!("Expanding {} {}", r.0, r.1);
-            current_merged_range = r.clone();
-        }
+fn merge_ranges(ranges: &Vec<(ID, ID)>) -> RangeSet<ID> {
+    // The set joins anything overlapping or touching as it goes in.
+    let merged: RangeSet<ID> = ranges.iter().copied().collect();
+    for (start, end) in merged.iter() {
+        info!("Merged range: {} {}", start, end);
     }
-    info!(
-        "Merged range: {} {}",
-        current_merged_range.0, current_merged_range.1
-    );
-    merged.push(current_merged_range);
     return merged;
 }
 
-fn count_range(start: ID, end: ID) -> ID {
-    return end - start + 1;
-}
-
 /// Ignore available, expand ranges to a set and count it.
 /// ```
 /// let vec1: Vec<Vec<String>> = vec![
@@ -106,10 +72,11 @@ fn count_range(start: ID, end: ID) -> ID {
 /// assert_eq!(day05::puzzle_b(&vec1), 14);
 /// ```
 pub fn puzzle_b(string_list: &Vec<Vec<String>>) -> ID {
-    let mut ranges = parse_fresh_id_ranges(&string_list[0]);
-    ranges.sort_by(|&a, &b| a.0.cmp(&b.0));
-    let merged = merge_ranges(&ranges);
-    return merged.iter().map(|(s, e)| count_range(*s, *e)).sum();
+    let fresh = merge_ranges(&parse_fresh_id_ranges(&string_list[0]));
+    return fresh
+        .covered_length()
+        .try_into()
+        .expect("Fewer fresh ids than fit in an ID");
 }
 
 #[cfg(test)]
@@ -127,6 +94,13 @@ mod tests {
     fn test_merge_ranges() {
         let vec1 = vec![(3, 5), (10, 14), (12, 18), (16, 20)];
         let expected = vec![(3, 5), (10, 20)];
-        assert_eq!(merge_ranges(&vec1), expected);
+        assert_eq!(merge_ranges(&vec1).iter().collect::<Vec<_>>(), expected);
+    }
+
+    #[test]
+    fn test_merge_ranges_unsorted_and_touching() {
+        let vec1 = vec![(16, 20), (3, 5), (6, 8), (12, 18)];
+        let expected = vec![(3, 8), (12, 20)];
+        assert_eq!(merge_ranges(&vec1).iter().collect::<Vec<_>>(), expected);
     }
 }
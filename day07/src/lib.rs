@@ -1,7 +1,10 @@
 extern crate filelib;
 
 pub use filelib::load_no_blanks;
-use gridlib::{Grid, GridCoordinate, GridPrintable, GridTraversable};
+use gridlib::{
+    Beam, BeamAction, BeamSimulator, Direction, Grid, GridCoordinate, GridParsable, GridPrintable,
+    GridTraversable, Heading,
+};
 
 #[cfg(not(test))]
 use log::info;
@@ -9,8 +12,6 @@ use log::info;
 #[cfg(test)]
 use std::println as info;
 
-use std::collections::{HashMap, HashSet, VecDeque};
-
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 enum Terrain {
     Blank,
@@ -26,95 +27,38 @@ impl GridPrintable for Terrain {
     }
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
-struct GridLine {
-    start: gridlib::GridCoordinate,
-    end: gridlib::GridCoordinate,
-}
-
-fn parse_grid_and_start(string_list: &Vec<String>) -> (Grid<Terrain>, GridCoordinate) {
-    let height = string_list.len();
-    let width = string_list[0].len();
-    let mut values = vec![];
-    let mut start = GridCoordinate::new(0, 0);
-
-    for (y, line) in string_list.iter().enumerate() {
-        for (x, c) in line.chars().enumerate() {
-            match c {
-                '.' => values.push(Terrain::Blank),
-                '^' => values.push(Terrain::Splitter),
-                'S' => {
-                    values.push(Terrain::Blank);
-                    start = GridCoordinate::new(x, y);
-                }
-                _ => panic!("Unknown character {}", c),
-            }
-        }
+impl GridParsable for Terrain {
+    fn from_character(c: char) -> Option<Self> {
+        return match c {
+            '.' => Some(Terrain::Blank),
+            '^' => Some(Terrain::Splitter),
+            _ => None,
+        };
     }
-
-    return (Grid::new(width, height, values), start);
 }
 
-fn find_end(start: GridCoordinate, grid: &Grid<Terrain>) -> (GridLine, Vec<GridCoordinate>) {
-    let mut next_starts = vec![];
-    let mut end = start.clone();
-    while let Some(next_coord) = grid.get_coordinate_by_direction(end, gridlib::Direction::SOUTH) {
-        let v = grid.get_value(next_coord).expect("Should have a value");
-
-        if v == Terrain::Splitter {
-            // This is the end, don't update end.
-            // Next starts: Get SOUTHEAST and Get SOUTHWEST from end of line and add to next_starts if possible
-            // We could also do WEST and EAST of next_coord, its equivalent.
-            if let Some(split_left) =
-                grid.get_coordinate_by_direction(end, gridlib::Direction::SOUTHWEST)
-            {
-                next_starts.push(split_left);
-            }
-            if let Some(split_right) =
-                grid.get_coordinate_by_direction(end, gridlib::Direction::SOUTHEAST)
-            {
-                next_starts.push(split_right);
-            }
-            break;
-        }
-
-        // End just follows next_coord consistently.
-        end = next_coord;
-    }
-
-    return (
-        GridLine {
-            start: start,
-            end: end,
-        },
-        next_starts,
-    );
+fn parse_grid_and_start(string_list: &Vec<String>) -> (Grid<Terrain>, GridCoordinate) {
+    // The start is a blank cell too, it just needs remembering first
+    let start = string_list
+        .iter()
+        .enumerate()
+        .find_map(|(y, line)| line.chars().position(|c| c == 'S').map(|x| (x, y)))
+        .map(|(x, y)| GridCoordinate::new(x, y))
+        .expect("Should have a start");
+    let lines: Vec<String> = string_list.iter().map(|l| l.replace('S', ".")).collect();
+    let grid = Grid::from_strings(&lines).expect("Should be a valid grid");
+    return (grid, start);
 }
 
-fn queue_lines_split_count(grid: &Grid<Terrain>, start: GridCoordinate) -> usize {
-    let mut queue = VecDeque::new();
-    queue.push_front(start);
-    let mut seen_starts: HashSet<GridCoordinate> = HashSet::new();
-    let mut seen_splits: HashSet<GridCoordinate> = HashSet::new();
-
-    while let Some(cur) = queue.pop_back() {
-        if seen_starts.contains(&cur) {
-            continue;
-        }
-        seen_starts.insert(cur);
-
-        let (z, next) = find_end(cur, &grid);
-        if z.end.y != grid.get_height() - 1 {
-            let splitter_loc = GridCoordinate::new(z.end.x, z.end.y + 1);
-            seen_splits.insert(splitter_loc);
-            info!("Split detected at {:?}", splitter_loc);
-        }
-
-        for n in next {
-            queue.push_front(n);
-        }
-    }
-    return seen_splits.len();
+// Beams go down, and a splitter sends one down on either side of it.
+fn splitter_rule(_: Beam, terrain: Terrain) -> BeamAction {
+    return match terrain {
+        Terrain::Blank => BeamAction::Pass,
+        Terrain::Splitter => BeamAction::Spawn(vec![
+            (Direction::WEST, Direction::SOUTH),
+            (Direction::EAST, Direction::SOUTH),
+        ]),
+    };
 }
 
 /// Count how many times this line was split.
@@ -131,37 +75,10 @@ fn queue_lines_split_count(grid: &Grid<Terrain>, start: GridCoordinate) -> usize
 /// ```
 pub fn puzzle_a(string_list: &Vec<String>) -> usize {
     let (grid, start) = parse_grid_and_start(string_list);
-    return queue_lines_split_count(&grid, start);
-}
-
-fn find_all_possible_paths(grid: &Grid<Terrain>, start: GridCoordinate) -> usize {
-    let mut memo = HashMap::new();
-    return dfs_recursive(grid, start, &mut memo);
-}
-
-fn dfs_recursive(
-    grid: &Grid<Terrain>,
-    current: GridCoordinate,
-    memo: &mut HashMap<GridCoordinate, usize>,
-) -> usize {
-    // Memoization immediate return
-    if let Some(&count) = memo.get(&current) {
-        return count;
-    }
-
-    let (_, next_starts) = find_end(current, grid);
-    if next_starts.is_empty() {
-        // The line ended from here
-        return 1;
-    }
-
-    let mut total_paths = 0;
-    for next in next_starts {
-        total_paths += dfs_recursive(grid, next, memo)
-    }
-
-    memo.insert(current, total_paths);
-    return total_paths;
+    let mut simulator = BeamSimulator::new(&grid, splitter_rule);
+    let trace = simulator.trace([Heading::new(start, Direction::SOUTH)]);
+    info!("{} splitters reached", trace.splits.len());
+    return trace.splits.len();
 }
 
 /// Count how many possible paths there are through this grid.
@@ -178,12 +95,119 @@ fn dfs_recursive(
 /// ```
 pub fn puzzle_b(string_list: &Vec<String>) -> usize {
     let (grid, start) = parse_grid_and_start(string_list);
-    return find_all_possible_paths(&grid, start);
+    // A split off the side of the manifold is dropped rather than counted as a path of its own
+    let on_grid_rule = |beam: Beam, terrain: Terrain| match splitter_rule(beam, terrain) {
+        BeamAction::Spawn(moves) => BeamAction::Spawn(
+            moves
+                .into_iter()
+                .filter(|&(step, _)| {
+                    grid.get_coordinate_by_direction(beam.position, step)
+                        .is_some()
+                })
+                .collect(),
+        ),
+        action => action,
+    };
+    let mut simulator = BeamSimulator::new(&grid, on_grid_rule);
+    let count = simulator
+        .count_histories(Heading::new(start, Direction::SOUTH))
+        .expect("Beams only go down, so can't loop");
+    return usize::try_from(count).expect("Path count fits in usize");
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use gridlib::GridRays;
+    use std::collections::{HashMap, HashSet, VecDeque};
+
+    // The original walk, kept to check the simulator against.
+    #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+    struct GridLine {
+        start: gridlib::GridCoordinate,
+        end: gridlib::GridCoordinate,
+    }
+
+    fn find_end(start: GridCoordinate, grid: &Grid<Terrain>) -> (GridLine, Vec<GridCoordinate>) {
+        let mut next_starts = vec![];
+        let cast = grid.cast_until(start, gridlib::Direction::SOUTH, |_, v| {
+            v == Terrain::Splitter
+        });
+        let end = cast.last;
+        if cast.hit.is_some() {
+            // Next starts: Get SOUTHEAST and Get SOUTHWEST from end of line and add to next_starts if possible
+            // We could also do WEST and EAST of the splitter, its equivalent.
+            for direction in [gridlib::Direction::SOUTHWEST, gridlib::Direction::SOUTHEAST] {
+                if let Some(split) = grid.get_coordinate_by_direction(end, direction) {
+                    next_starts.push(split);
+                }
+            }
+        }
+
+        return (
+            GridLine {
+                start: start,
+                end: end,
+            },
+            next_starts,
+        );
+    }
+
+    fn queue_lines_split_count(grid: &Grid<Terrain>, start: GridCoordinate) -> usize {
+        let mut queue = VecDeque::new();
+        queue.push_front(start);
+        let mut seen_starts: HashSet<GridCoordinate> = HashSet::new();
+        let mut seen_splits: HashSet<GridCoordinate> = HashSet::new();
+
+        while let Some(cur) = queue.pop_back() {
+            if seen_starts.contains(&cur) {
+                continue;
+            }
+            seen_starts.insert(cur);
+
+            let (z, next) = find_end(cur, &grid);
+            if z.end.y != grid.get_height() - 1 {
+                let splitter_loc = GridCoordinate::new(z.end.x, z.end.y + 1);
+                seen_splits.insert(splitter_loc);
+                info!("Split detected at {:?}", splitter_loc);
+            }
+
+            for n in next {
+                queue.push_front(n);
+            }
+        }
+        return seen_splits.len();
+    }
+
+    fn find_all_possible_paths(grid: &Grid<Terrain>, start: GridCoordinate) -> usize {
+        let mut memo = HashMap::new();
+        return dfs_recursive(grid, start, &mut memo);
+    }
+
+    fn dfs_recursive(
+        grid: &Grid<Terrain>,
+        current: GridCoordinate,
+        memo: &mut HashMap<GridCoordinate, usize>,
+    ) -> usize {
+        // Memoization immediate return
+        if let Some(&count) = memo.get(&current) {
+            return count;
+        }
+
+        let (_, next_starts) = find_end(current, grid);
+        if next_starts.is_empty() {
+            // The line ended from here
+            return 1;
+        }
+
+        let mut total_paths = 0;
+        for next in next_starts {
+            total_paths += dfs_recursive(grid, next, memo)
+        }
+
+        memo.insert(current, total_paths);
+        return total_paths;
+    }
 
     #[test]
     fn test_parse_grid_and_start() {
@@ -227,6 +251,37 @@ mod tests {
         assert_eq!(result_next, expected_next);
     }
 
+    #[test]
+    fn test_simulator_matches_walk() {
+        let (grid, start) = get_expected_start_and_grid();
+        let mut lines = grid.grid_strings();
+        lines[start.y].replace_range(start.x..start.x + 1, "S");
+        assert_eq!(puzzle_a(&lines), queue_lines_split_count(&grid, start));
+        assert_eq!(puzzle_b(&lines), find_all_possible_paths(&grid, start));
+    }
+
+    #[test]
+    fn test_edge_splitters() {
+        // The left splitter sends one beam off the side, only the other one goes on to split
+        let vec1: Vec<String> = ["S...", "....", "^...", "....", ".^..", "...."]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        assert_eq!(puzzle_a(&vec1), 2);
+        assert_eq!(puzzle_b(&vec1), 2);
+        let (grid, start) = parse_grid_and_start(&vec1);
+        assert_eq!(queue_lines_split_count(&grid, start), 2);
+        assert_eq!(find_all_possible_paths(&grid, start), 2);
+
+        // Both splits leave the grid in a single column, so the line just ends there
+        let vec2: Vec<String> = ["S", "^"].iter().map(|s| s.to_string()).collect();
+        assert_eq!(puzzle_a(&vec2), 1);
+        assert_eq!(puzzle_b(&vec2), 1);
+        let (grid, start) = parse_grid_and_start(&vec2);
+        assert_eq!(queue_lines_split_count(&grid, start), 1);
+        assert_eq!(find_all_possible_paths(&grid, start), 1);
+    }
+
     #[test]
     fn test_a_simple() {
         let vec1: Vec<String> = vec![
@@ -280,248 +335,25 @@ mod tests {
 
     fn get_expected_start_and_grid() -> (Grid<Terrain>, GridCoordinate) {
         let expected_start = GridCoordinate::new(7, 0);
-        let expected_grid_values = vec![
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Splitter,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Splitter,
-            Terrain::Blank,
-            Terrain::Splitter,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Splitter,
-            Terrain::Blank,
-            Terrain::Splitter,
-            Terrain::Blank,
-            Terrain::Splitter,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Splitter,
-            Terrain::Blank,
-            Terrain::Splitter,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Splitter,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Splitter,
-            Terrain::Blank,
-            Terrain::Splitter,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Splitter,
-            Terrain::Blank,
-            Terrain::Splitter,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Splitter,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Splitter,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Splitter,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Splitter,
-            Terrain::Blank,
-            Terrain::Splitter,
-            Terrain::Blank,
-            Terrain::Splitter,
-            Terrain::Blank,
-            Terrain::Splitter,
-            Terrain::Blank,
-            Terrain::Splitter,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Splitter,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-            Terrain::Blank,
-        ];
-        return (Grid::new(15, 16, expected_grid_values), expected_start);
+        let expected_grid = Grid::from_strings(&[
+            "...............",
+            "...............",
+            ".......^.......",
+            "...............",
+            "......^.^......",
+            "...............",
+            ".....^.^.^.....",
+            "...............",
+            "....^.^...^....",
+            "...............",
+            "...^.^...^.^...",
+            "...............",
+            "..^...^.....^..",
+            "...............",
+            ".^.^.^.^.^...^.",
+            "...............",
+        ])
+        .unwrap();
+        return (expected_grid, expected_start);
     }
 }
@@ -1,4 +1,6 @@
 extern crate filelib;
+extern crate gridlib;
+extern crate mathlib;
 
 pub use filelib::load_no_blanks;
 
@@ -9,33 +11,14 @@ use log::info;
 use std::println as info;
 use std::{cmp::Ordering, collections::HashSet};
 
-// Use i32 for parsing numbers, because we will be subtracting later, and it makes the math easier.
-type Num = i32;
+// Signed for parsing numbers, because we will be subtracting later, and i64 so part b's product fits.
+type Num = i64;
 // Sqrt gives a float, so we store distance as a float.
 type Dist = f64;
 // We have same big numbers at the end, so this can be changed if its too small.
 type Size = usize;
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
-struct Coord {
-    x: Num,
-    y: Num,
-    z: Num,
-}
-
-impl Coord {
-    pub fn euclidean_distance(&self, other: &Coord) -> Dist {
-        info!(
-            "euclidean_distance multiplication happening {:?} {:?}",
-            self, other
-        );
-        let dx = (self.x - other.x) as f64;
-        let dy = (self.y - other.y) as f64;
-        let dz = (self.z - other.z) as f64;
-
-        return (dx * dx + dy * dy + dz * dz).sqrt();
-    }
-}
+type Coord = gridlib::GridCoordinateInf3<Num>;
 
 // A possible connection
 #[derive(Debug, Copy, Clone, PartialEq)]
@@ -101,7 +84,7 @@ fn parse_coords(string_list: &Vec<String>) -> Vec<Coord> {
             let x: Num = x_str.parse().expect("Should fit in Num");
             let y: Num = y_str.parse().expect("Should fit in Num");
             let z: Num = z_str.parse().expect("Should fit in Num");
-            return Coord { x: x, y: y, z: z };
+            return Coord::new(x, y, z);
         })
         .collect();
 }
@@ -110,7 +93,7 @@ fn generate_all_edges(coords: &Vec<Coord>) -> Vec<Edge> {
     let mut edges = vec![];
     for i in 0..coords.len() {
         for j in (i + 1)..coords.len() {
-            let dist = coords[i].euclidean_distance(&coords[j]);
+            let dist = mathlib::euclidean_distance_nd(&coords[i], &coords[j]);
             edges.push(Edge {
                 u: i,
                 v: j,
@@ -192,8 +175,8 @@ pub fn puzzle_a(string_list: &Vec<String>, steps: usize) -> Size {
 
 fn kruskals_algorithm_b(coords: &Vec<Coord>, edges: &Vec<Edge>) -> (Coord, Coord) {
     let mut dsu = DSU::new(coords.len());
-    let mut from: Coord = Coord { x: 0, y: 0, z: 0 };
-    let mut to: Coord = Coord { x: 0, y: 0, z: 0 };
+    let mut from: Coord = Coord::new(0, 0, 0);
+    let mut to: Coord = Coord::new(0, 0, 0);
 
     for edge in edges {
         // Try to connect. union returns true if they weren't already connected.
@@ -240,3 +223,22 @@ pub fn puzzle_b(string_list: &Vec<String>) -> u64 {
     let result: u64 = (from.x * to.x) as u64;
     return result;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_large_coordinates() {
+        // Squared distances and the part b product are both well past i32 here
+        let vec1: Vec<String> = ["100000,100000,100000", "100010,100000,100000", "300000,0,0"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let edges = generate_all_edges(&parse_coords(&vec1));
+        assert_eq!(edges[0].distance, 10.0);
+        assert!(edges.iter().all(|e| e.distance.is_finite()));
+        assert_eq!(puzzle_a(&vec1, 1), 2);
+        assert_eq!(puzzle_b(&vec1), 100010 * 300000);
+    }
+}
@@ -7,10 +7,11 @@ use log::info;
 use std::println as info;
 
 pub use filelib::load_no_blanks;
-use gridlib::GridCoordinateInf;
+use gridlib::{GridCompressor, GridCoordinate, GridCoordinateInf, GridTraversable, Rectangle};
 
 type Num = i64;
 type Coord = GridCoordinateInf<Num>;
+type Rect = Rectangle<Coord>;
 
 fn parse_coordinates(string_list: &Vec<String>) -> Vec<Coord> {
     let mut parsed = vec![];
@@ -24,32 +25,19 @@ fn parse_coordinates(string_list: &Vec<String>) -> Vec<Coord> {
 }
 
 fn calc_area(a: Coord, b: Coord) -> Num {
-    let dx = ((a.x - b.x).abs() + 1) as Num; // need to add 1 as the border is counted
-    let dy = ((a.y - b.y).abs() + 1) as Num; // need to add 1 as the border is counted
-    return (dx * dy).try_into().expect("Should fit in Num");
+    // The border is counted
+    return Rect::from_corners(a, b).area();
 }
 
-#[derive(Debug, Copy, Clone, PartialEq)]
-struct PotentialRect {
-    u: usize,
-    v: usize,
-    area: Num,
-}
-
-fn find_all_rects(coords: Vec<Coord>) -> Vec<PotentialRect> {
-    let mut results = vec![];
+fn largest_rect_area(coords: &[Coord]) -> Num {
+    // Only the best is needed, so keep a running max rather than every pair.
+    let mut best = 0;
     for i in 0..coords.len() {
         for j in (i + 1)..coords.len() {
-            let area = calc_area(coords[i], coords[j]);
-            info!("{:?} to {:?} - area {}", coords[i], coords[j], area);
-            results.push(PotentialRect {
-                u: i,
-                v: j,
-                area: area,
-            });
+            best = best.max(calc_area(coords[i], coords[j]));
         }
     }
-    return results;
+    return best;
 }
 
 /// Find the largest red tile rectangle area
@@ -61,172 +49,137 @@ fn find_all_rects(coords: Vec<Coord>) -> Vec<PotentialRect> {
 /// ```
 pub fn puzzle_a(string_list: &Vec<String>) -> Num {
     let redtiles = parse_coordinates(string_list);
-    let mut all_sizes = find_all_rects(redtiles);
-    all_sizes.sort_by(|a, &b| b.area.cmp(&a.area));
-    return all_sizes.first().unwrap().area;
-}
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub struct Rect {
-    pub coord: Coord,
-    pub width: Num,
-    pub height: Num,
-    pub area: Num,
+    return largest_rect_area(&redtiles);
 }
 
-/// Helper struct for internal logic
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-struct Edge {
-    p1: Coord,
-    p2: Coord,
+/// The polygon squashed down so only the x and y values its vertices use matter.
+///
+/// Each value gets a cell, and so does the gap after it, as everything strictly between
+/// two neighbouring values looks the same.
+struct CompressedPolygon {
+    compressor: GridCompressor<Num>,
+    width: usize,
+    // outside_sums[y * (width + 1) + x] is how many outside cells are above and left of (x, y)
+    outside_sums: Vec<usize>,
 }
 
-// Raycast point is in polygon, need to use floats here.
-fn is_point_in_polygon(p: (f64, f64), edges: &Vec<Edge>) -> bool {
-    let (px, py) = p;
-    let mut inside = false;
-
-    for edge in edges {
-        let (p1x, p1y) = (edge.p1.x as f64, edge.p1.y as f64);
-        let (p2x, p2y) = (edge.p2.x as f64, edge.p2.y as f64);
-
-        // Check if ray crosses the edge's Y-range
-        if (p1y > py) != (p2y > py) {
-            // Calculate X intersection of the edge with the horizontal ray at py
-            let intersect_x = p1x + (py - p1y) * (p2x - p1x) / (p2y - p1y);
-            if px < intersect_x {
-                inside = !inside;
+impl CompressedPolygon {
+    fn new(vertices: &[Coord]) -> CompressedPolygon {
+        let compressor = GridCompressor::from_points(vertices, true);
+        let width = compressor.get_width();
+        let height = compressor.get_height();
+
+        // Draw the outline, edges all run along value cells
+        let mut boundary = compressor.grid(false);
+        for i in 0..vertices.len() {
+            let a = compressor.cell(vertices[i]).unwrap();
+            let b = compressor.cell(vertices[(i + 1) % vertices.len()]).unwrap();
+            for x in a.x.min(b.x)..=a.x.max(b.x) {
+                for y in a.y.min(b.y)..=a.y.max(b.y) {
+                    boundary.set_value(GridCoordinate::new(x, y), true);
+                }
             }
         }
-    }
-    return inside;
-}
-
-fn edge_crosses_rect_interior(
-    edge: &Edge,
-    r_min_x: Num,
-    r_max_x: Num,
-    r_min_y: Num,
-    r_max_y: Num,
-) -> bool {
-    // Normalize Coordinates
-    let e_min_x = edge.p1.x.min(edge.p2.x);
-    let e_max_x = edge.p1.x.max(edge.p2.x);
-    let e_min_y = edge.p1.y.min(edge.p2.y);
-    let e_max_y = edge.p1.y.max(edge.p2.y);
-
-    // If the edge bounding box doesn't overlap the rect, safe.
-    if e_max_x <= r_min_x || e_min_x >= r_max_x || e_max_y <= r_min_y || e_min_y >= r_max_y {
-        return false;
-    }
 
-    // An edge needs to be trictly inside the bounds for us to have an issue.
-    // Since this is a Rectilinear polygon, edges are either Vertical or Horizontal.
-    if edge.p1.x == edge.p2.x {
-        // Vertical, x must be between left and right
-        if edge.p1.x > r_min_x && edge.p1.x < r_max_x {
-            // AND its Y range must partially overlap the Rect's Y range strictly
-            let overlap_min = e_min_y.max(r_min_y);
-            let overlap_max = e_max_y.min(r_max_y);
-            if overlap_min < overlap_max {
-                return true;
+        // Rows of gaps only meet vertical edges, so scan across counting crossings like a
+        // ray cast. A value row has nothing but the outline between it and the gap row above,
+        // so off the outline it matches that, and the top row has only outside above it.
+        let mut is_outside = vec![true; width * height];
+        for y in 0..height {
+            let mut crossings = 0;
+            for x in 0..width {
+                let on_outline = boundary.get_value(GridCoordinate::new(x, y)).unwrap();
+                let outside = if on_outline {
+                    false
+                } else if y % 2 == 1 {
+                    crossings % 2 == 0
+                } else {
+                    y == 0 || is_outside[(y - 1) * width + x]
+                };
+                if on_outline && y % 2 == 1 {
+                    crossings += 1;
+                }
+                is_outside[y * width + x] = outside;
             }
         }
-    } else {
-        // Horizontal, y must be between top and bottom
-        if edge.p1.y > r_min_y && edge.p1.y < r_max_y {
-            // AND its X range must partially overlap the Rect's X range strictly
-            let overlap_min = e_min_x.max(r_min_x);
-            let overlap_max = e_max_x.min(r_max_x);
-            if overlap_min < overlap_max {
-                return true;
+        info!(
+            "Compressed to {}x{}, {} cells outside",
+            width,
+            height,
+            is_outside.iter().filter(|&&o| o).count()
+        );
+
+        // 2D prefix sums, so any block of cells can be checked at once
+        let mut outside_sums = vec![0; (width + 1) * (height + 1)];
+        for y in 0..height {
+            for x in 0..width {
+                outside_sums[(y + 1) * (width + 1) + x + 1] = is_outside[y * width + x] as usize
+                    + outside_sums[y * (width + 1) + x + 1]
+                    + outside_sums[(y + 1) * (width + 1) + x]
+                    - outside_sums[y * (width + 1) + x];
             }
         }
+        return CompressedPolygon {
+            compressor: compressor,
+            width: width,
+            outside_sums: outside_sums,
+        };
     }
-    return false;
-}
 
-// Generate the edge objects we will need
-fn make_edges(vertices: &Vec<Coord>) -> Vec<Edge> {
-    let mut edges = vec![];
-    for i in 0..vertices.len() {
-        edges.push(Edge {
-            p1: vertices[i],
-            p2: vertices[(i + 1) % vertices.len()],
-        });
+    // Outside cells from top_left to bottom_right, both included.
+    fn outside_count(&self, top_left: GridCoordinate, bottom_right: GridCoordinate) -> usize {
+        let row = self.width + 1;
+        let sum = |x: usize, y: usize| self.outside_sums[y * row + x];
+        return sum(bottom_right.x + 1, bottom_right.y + 1) + sum(top_left.x, top_left.y)
+            - sum(top_left.x, bottom_right.y + 1)
+            - sum(bottom_right.x + 1, top_left.y);
+    }
+
+    /// If the rectangle with these opposite corners is all inside or on the outline, in O(1).
+    fn contains_rect(&self, a: Coord, b: Coord) -> bool {
+        let a = self
+            .compressor
+            .cell(a)
+            .expect("Only vertices are compressed");
+        let b = self
+            .compressor
+            .cell(b)
+            .expect("Only vertices are compressed");
+        let top_left = GridCoordinate::new(a.x.min(b.x), a.y.min(b.y));
+        let bottom_right = GridCoordinate::new(a.x.max(b.x), a.y.max(b.y));
+        return self.outside_count(top_left, bottom_right) == 0;
     }
-    return edges;
 }
 
-// brute force check each pair.
-fn check_each_vertex_pair(vertices: &Vec<Coord>, edges: &Vec<Edge>) -> Rect {
-    let mut best_rect = Rect {
-        coord: Coord::new(0, 0),
-        width: 0,
-        height: 0,
-        area: 0,
-    };
+// Check each pair, with the compressed polygon doing the containment check.
+fn check_each_vertex_pair(vertices: &Vec<Coord>) -> Option<Rect> {
+    let polygon = CompressedPolygon::new(vertices);
+    let mut best_rect: Option<Rect> = None;
 
     for i in 0..vertices.len() {
         for j in (i + 1)..vertices.len() {
-            let p1 = vertices[i];
-            let p2 = vertices[j];
-
-            // Calculate Bounds
-            let min_x = p1.x.min(p2.x);
-            let max_x = p1.x.max(p2.x);
-            let min_y = p1.y.min(p2.y);
-            let max_y = p1.y.max(p2.y);
-
-            let width = (max_x - min_x) + 1;
-            let height = (max_y - min_y) + 1;
-            let area = width * height;
-
-            if area <= best_rect.area {
-                continue;
-            }
-
-            // Check if any polygon edge cuts through the rectangle
-            let mut crossed = false;
-            for edge in edges {
-                if edge_crosses_rect_interior(edge, min_x, max_x, min_y, max_y) {
-                    crossed = true;
-                    break;
-                }
-            }
-            if crossed {
+            let rect = Rect::from_corners(vertices[i], vertices[j]);
+            if rect.area() <= best_rect.map_or(0, |r| r.area()) {
                 continue;
             }
-
-            // Check if the rectangle is actually inside (vs floating in a hole)
-            // Currently checking center, but I honestly forgot why I did this because its 12:30 AM
-            // I feel like its because we haven't crossed lines it a valid choice but I would have to draw it out.
-            let center_x = (min_x as f64 + max_x as f64) / 2.0;
-            let center_y = (min_y as f64 + max_y as f64) / 2.0;
-
-            if !is_point_in_polygon((center_x, center_y), &edges) {
+            if !polygon.contains_rect(vertices[i], vertices[j]) {
                 continue;
             }
 
             // If we are here, it's valid!
-            best_rect = Rect {
-                coord: Coord::new(min_x, min_y),
-                width,
-                height,
-                area,
-            };
+            info!("New best {:?} - area {}", rect, rect.area());
+            best_rect = Some(rect);
         }
     }
     return best_rect;
 }
 
-fn find_largest_inscribed_rect(vertices: &Vec<Coord>) -> Rect {
+fn find_largest_inscribed_rect(vertices: &Vec<Coord>) -> Option<Rect> {
     if vertices.len() < 3 {
         panic!("Need at least 3 vertices to be a polygon");
     }
 
-    let edges = make_edges(vertices);
-    return check_each_vertex_pair(vertices, &edges);
+    return check_each_vertex_pair(vertices);
 }
 
 /// Find the largest rectangle within a polygon.
@@ -242,12 +195,39 @@ fn find_largest_inscribed_rect(vertices: &Vec<Coord>) -> Rect {
 pub fn puzzle_b(string_list: &Vec<String>) -> Num {
     let vertices = parse_coordinates(string_list);
     let rect = find_largest_inscribed_rect(&vertices);
-    return rect.area;
+    return rect.map_or(0, |r| r.area());
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use mathlib::{Cuboid, rect_in_rectilinear_polygon};
+
+    // The exact check on the real polygon, for every pair, to test the compressed one against.
+    fn check_each_vertex_pair_exact(vertices: &Vec<Coord>) -> Option<Rect> {
+        let polygon: Vec<(Num, Num)> = vertices.iter().map(|v| (v.x, v.y)).collect();
+        let mut best_rect: Option<Rect> = None;
+
+        for i in 0..vertices.len() {
+            for j in (i + 1)..vertices.len() {
+                let rect = Rect::from_corners(vertices[i], vertices[j]);
+                if rect.area() <= best_rect.map_or(0, |r| r.area()) {
+                    continue;
+                }
+
+                // No polygon edge can cut through the rectangle, and it can't be floating in a hole.
+                let bounds = Cuboid::from_corners(&polygon[i], &polygon[j]);
+                if !rect_in_rectilinear_polygon(&bounds, &polygon) {
+                    continue;
+                }
+
+                // If we are here, it's valid!
+                info!("New best {:?} - area {}", rect, rect.area());
+                best_rect = Some(rect);
+            }
+        }
+        return best_rect;
+    }
 
     #[test]
     fn test_parse_coords() {
@@ -287,13 +267,9 @@ mod tests {
             Coord::new(2, 3),
             Coord::new(7, 3),
         ];
-        let expected = Rect {
-            coord: Coord::new(2, 3),
-            width: 8,
-            height: 3,
-            area: 24,
-        };
-        assert_eq!(find_largest_inscribed_rect(&input), expected);
+        let expected = Rect::with_size(Coord::new(2, 3), 8, 3);
+        assert_eq!(expected.area(), 24);
+        assert_eq!(find_largest_inscribed_rect(&input), Some(expected));
     }
 
     #[test]
@@ -318,17 +294,13 @@ mod tests {
             Coord::new(7, 6),
             Coord::new(1, 6),
         ];
-        let expected = Rect {
-            coord: Coord::new(1, 0),
-            width: 7,
-            height: 3,
-            area: 21,
-        };
-        assert_eq!(find_largest_inscribed_rect(&input), expected);
+        let expected = Rect::with_size(Coord::new(1, 0), 7, 3);
+        assert_eq!(expected.area(), 21);
+        assert_eq!(find_largest_inscribed_rect(&input), Some(expected));
     }
 
     #[test]
-    fn test_find_all_rects() {
+    fn test_largest_rect_area() {
         let input: Vec<Coord> = vec![
             Coord::new(7, 1),
             Coord::new(11, 1),
@@ -339,148 +311,59 @@ mod tests {
             Coord::new(2, 3),
             Coord::new(7, 3),
         ];
-        let expected = vec![
-            PotentialRect {
-                u: 0,
-                v: 1,
-                area: 5,
-            },
-            PotentialRect {
-                u: 0,
-                v: 2,
-                area: 35,
-            },
-            PotentialRect {
-                u: 0,
-                v: 3,
-                area: 21,
-            },
-            PotentialRect {
-                u: 0,
-                v: 4,
-                area: 15,
-            },
-            PotentialRect {
-                u: 0,
-                v: 5,
-                area: 30,
-            },
-            PotentialRect {
-                u: 0,
-                v: 6,
-                area: 18,
-            },
-            PotentialRect {
-                u: 0,
-                v: 7,
-                area: 3,
-            },
-            PotentialRect {
-                u: 1,
-                v: 2,
-                area: 7,
-            },
-            PotentialRect {
-                u: 1,
-                v: 3,
-                area: 21,
-            },
-            PotentialRect {
-                u: 1,
-                v: 4,
-                area: 15,
-            },
-            PotentialRect {
-                u: 1,
-                v: 5,
-                area: 50,
-            },
-            PotentialRect {
-                u: 1,
-                v: 6,
-                area: 30,
-            },
-            PotentialRect {
-                u: 1,
-                v: 7,
-                area: 15,
-            },
-            PotentialRect {
-                u: 2,
-                v: 3,
-                area: 3,
-            },
-            PotentialRect {
-                u: 2,
-                v: 4,
-                area: 9,
-            },
-            PotentialRect {
-                u: 2,
-                v: 5,
-                area: 30,
-            },
-            PotentialRect {
-                u: 2,
-                v: 6,
-                area: 50,
-            },
-            PotentialRect {
-                u: 2,
-                v: 7,
-                area: 25,
-            },
-            PotentialRect {
-                u: 3,
-                v: 4,
-                area: 3,
-            },
-            PotentialRect {
-                u: 3,
-                v: 5,
-                area: 24,
-            },
-            PotentialRect {
-                u: 3,
-                v: 6,
-                area: 40,
-            },
-            PotentialRect {
-                u: 3,
-                v: 7,
-                area: 15,
-            },
-            PotentialRect {
-                u: 4,
-                v: 5,
-                area: 8,
-            },
-            PotentialRect {
-                u: 4,
-                v: 6,
-                area: 24,
-            },
-            PotentialRect {
-                u: 4,
-                v: 7,
-                area: 9,
-            },
-            PotentialRect {
-                u: 5,
-                v: 6,
-                area: 3,
-            },
-            PotentialRect {
-                u: 5,
-                v: 7,
-                area: 18,
-            },
-            PotentialRect {
-                u: 6,
-                v: 7,
-                area: 6,
-            },
-        ];
-        assert_eq!(find_all_rects(input), expected);
+        assert_eq!(largest_rect_area(&input), 50);
+        // Only the first pair, then a pair across the whole shape
+        assert_eq!(largest_rect_area(&input[..2]), 5);
+        assert_eq!(largest_rect_area(&[input[1], input[5]]), 50);
+        assert_eq!(largest_rect_area(&input[..1]), 0);
+    }
+
+    // A comb, teeth of different heights with uneven spacing, so lots of concave corners.
+    fn comb(teeth: usize) -> Vec<Coord> {
+        let x = |i: usize| (i * i + 7 * i) as Num;
+        let height = |k: usize| (3 + (k * 7) % 11) as Num * 10;
+        let mut result = vec![Coord::new(0, 0), Coord::new(x(2 * teeth), 0)];
+        for k in (0..teeth).rev() {
+            result.push(Coord::new(x(2 * k + 2), height(k)));
+            result.push(Coord::new(x(2 * k + 1), height(k)));
+            result.push(Coord::new(x(2 * k + 1), 5));
+            result.push(Coord::new(x(2 * k), 5));
+        }
+        // The last tooth's left side is the polygon's left side, join it up
+        result.pop();
+        result.pop();
+        result.push(Coord::new(0, height(0)));
+        return result;
+    }
+
+    #[test]
+    fn test_compressed_matches_exact() {
+        let example = parse_coordinates(
+            &["7,1", "11,1", "11,7", "9,7", "9,5", "2,5", "2,3", "7,3"]
+                .iter()
+                .map(|s| s.to_string())
+                .collect(),
+        );
+        for vertices in [example, comb(3), comb(12)] {
+            let polygon: Vec<(Num, Num)> = vertices.iter().map(|v| (v.x, v.y)).collect();
+            assert!(mathlib::is_simple_polygon(&polygon));
+            let compressed = CompressedPolygon::new(&vertices);
+            for i in 0..vertices.len() {
+                for j in (i + 1)..vertices.len() {
+                    let bounds = Cuboid::from_corners(&polygon[i], &polygon[j]);
+                    assert_eq!(
+                        compressed.contains_rect(vertices[i], vertices[j]),
+                        rect_in_rectilinear_polygon(&bounds, &polygon),
+                        "{:?} {:?}",
+                        vertices[i],
+                        vertices[j]
+                    );
+                }
+            }
+            assert_eq!(
+                check_each_vertex_pair(&vertices),
+                check_each_vertex_pair_exact(&vertices)
+            );
+        }
     }
 }
@@ -0,0 +1,308 @@
+use crate::grid::{Grid, GridTraversable};
+use crate::gridcoord::GridCoordinate;
+use crate::search::Adjacency;
+
+use std::collections::HashMap;
+use std::hash::Hash;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum UpdateMode {
+    // Every cell sees the previous generation.
+    Synchronous,
+    // Cells are updated in place, and only cells next to a change are looked at again.
+    // A generation is one pass over the worklist.
+    Worklist,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum StopReason {
+    FixedPoint,
+    GenerationLimit,
+    // The grid matched the state it had after generation `first_seen`.
+    Repeated { first_seen: usize },
+}
+
+/// What happened during `run`, changes has one entry per generation that changed something.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct AutomatonRun {
+    pub changes: Vec<usize>,
+    pub stop: StopReason,
+}
+
+impl AutomatonRun {
+    pub fn total_changes(&self) -> usize {
+        return self.changes.iter().sum();
+    }
+}
+
+/// Steps a grid with a rule of `(cell, neighbours) -> new cell`.
+///
+/// Neighbours are the values of the in bounds cells given by the adjacency.
+#[derive(Debug, Clone)]
+pub struct Automaton<T: Copy> {
+    grid: Grid<T>,
+    adjacency: Adjacency,
+    generation: usize,
+    // Cells to look at next generation in worklist mode, None means all of them.
+    worklist: Option<Vec<GridCoordinate>>,
+}
+
+impl<T: Copy + PartialEq> Automaton<T> {
+    pub fn new(grid: Grid<T>, adjacency: Adjacency) -> Automaton<T> {
+        return Automaton {
+            grid: grid,
+            adjacency: adjacency,
+            generation: 0,
+            worklist: None,
+        };
+    }
+
+    pub fn get_grid(&self) -> &Grid<T> {
+        return &self.grid;
+    }
+
+    pub fn into_grid(self) -> Grid<T> {
+        return self.grid;
+    }
+
+    pub fn get_generation(&self) -> usize {
+        return self.generation;
+    }
+
+    fn neighbor_values(&self, pos: GridCoordinate, buffer: &mut Vec<T>) {
+        buffer.clear();
+        for &direction in self.adjacency.directions() {
+            if let Some(next) = self.grid.get_coordinate_by_direction(pos, direction) {
+                buffer.push(self.grid.get_value(next).unwrap());
+            }
+        }
+    }
+
+    /// Apply the rule to every cell at once, returns how many cells changed.
+    pub fn step<F: FnMut(T, &[T]) -> T>(&mut self, mut rule: F) -> usize {
+        let mut next = self.grid.clone();
+        let mut neighbors = vec![];
+        let mut changes = 0;
+        for coord in self.grid.coord_iter() {
+            let old = self.grid.get_value(coord).unwrap();
+            self.neighbor_values(coord, &mut neighbors);
+            let new = rule(old, &neighbors);
+            if new != old {
+                next.set_value(coord, new);
+                changes += 1;
+            }
+        }
+        self.grid = next;
+        self.generation += 1;
+        // Synchronous steps invalidate whatever the worklist knew.
+        self.worklist = None;
+        return changes;
+    }
+
+    /// Apply the rule in place to the cells on the worklist, returns how many cells changed.
+    ///
+    /// Changed cells put themselves and their neighbours on the next worklist.
+    pub fn step_worklist<F: FnMut(T, &[T]) -> T>(&mut self, mut rule: F) -> usize {
+        let width = self.grid.get_width();
+        let current = match self.worklist.take() {
+            Some(list) => list,
+            None => self.grid.coord_iter().collect(),
+        };
+        let mut queued = vec![false; width * self.grid.get_height()];
+        let mut next_worklist = vec![];
+        let mut neighbors = vec![];
+        let mut changes = 0;
+
+        for coord in current {
+            let old = self.grid.get_value(coord).unwrap();
+            self.neighbor_values(coord, &mut neighbors);
+            let new = rule(old, &neighbors);
+            if new == old {
+                continue;
+            }
+            self.grid.set_value(coord, new);
+            changes += 1;
+
+            let mut affected = vec![coord];
+            for &direction in self.adjacency.directions() {
+                if let Some(n) = self.grid.get_coordinate_by_direction(coord, direction) {
+                    affected.push(n);
+                }
+            }
+            for pos in affected {
+                if !queued[pos.x + pos.y * width] {
+                    queued[pos.x + pos.y * width] = true;
+                    next_worklist.push(pos);
+                }
+            }
+        }
+
+        self.generation += 1;
+        self.worklist = Some(next_worklist);
+        return changes;
+    }
+
+    fn run_checked<F, C>(
+        &mut self,
+        mode: UpdateMode,
+        max_generations: Option<usize>,
+        mut rule: F,
+        mut check_repeat: C,
+    ) -> AutomatonRun
+    where
+        F: FnMut(T, &[T]) -> T,
+        C: FnMut(&Grid<T>, usize) -> Option<usize>,
+    {
+        let mut changes = vec![];
+        loop {
+            if let Some(limit) = max_generations {
+                if changes.len() >= limit {
+                    return AutomatonRun {
+                        changes: changes,
+                        stop: StopReason::GenerationLimit,
+                    };
+                }
+            }
+
+            let changed = match mode {
+                UpdateMode::Synchronous => self.step(&mut rule),
+                UpdateMode::Worklist => self.step_worklist(&mut rule),
+            };
+            if changed == 0 {
+                return AutomatonRun {
+                    changes: changes,
+                    stop: StopReason::FixedPoint,
+                };
+            }
+            changes.push(changed);
+
+            if let Some(first_seen) = check_repeat(&self.grid, self.generation) {
+                return AutomatonRun {
+                    changes: changes,
+                    stop: StopReason::Repeated {
+                        first_seen: first_seen,
+                    },
+                };
+            }
+        }
+    }
+
+    /// Step until nothing changes, or until max_generations generations changed something.
+    pub fn run<F: FnMut(T, &[T]) -> T>(
+        &mut self,
+        mode: UpdateMode,
+        max_generations: Option<usize>,
+        rule: F,
+    ) -> AutomatonRun {
+        return self.run_checked(mode, max_generations, rule, |_, _| None);
+    }
+
+    /// Same as `run`, but also stops as soon as the grid repeats an earlier state.
+    pub fn run_detecting_repeats<F: FnMut(T, &[T]) -> T>(
+        &mut self,
+        mode: UpdateMode,
+        max_generations: Option<usize>,
+        rule: F,
+    ) -> AutomatonRun
+    where
+        T: Hash + Eq,
+    {
+        let mut seen: HashMap<Vec<T>, usize> = HashMap::new();
+        seen.insert(self.grid.data_copy(), self.generation);
+        return self.run_checked(mode, max_generations, rule, |grid, generation| {
+            let state = grid.data_copy();
+            if let Some(&first_seen) = seen.get(&state) {
+                return Some(first_seen);
+            }
+            seen.insert(state, generation);
+            None
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn produce_grid(lines: &[&str]) -> Grid<char> {
+        let values: Vec<char> = lines.iter().flat_map(|l| l.chars()).collect();
+        return Grid::new(lines[0].len(), lines.len(), values);
+    }
+
+    fn life(cell: char, neighbors: &[char]) -> char {
+        let alive = neighbors.iter().filter(|&&c| c == '#').count();
+        return match (cell, alive) {
+            ('#', 2) | ('#', 3) | ('.', 3) => '#',
+            _ => '.',
+        };
+    }
+
+    #[test]
+    fn test_step_blinker() {
+        let grid = produce_grid(&[".....", "..#..", "..#..", "..#..", "....."]);
+        let mut automaton = Automaton::new(grid, Adjacency::All);
+        assert_eq!(automaton.step(life), 4);
+        assert_eq!(
+            automaton.get_grid().grid_strings(),
+            vec![".....", ".....", ".###.", ".....", "....."]
+        );
+        assert_eq!(automaton.get_generation(), 1);
+    }
+
+    #[test]
+    fn test_detect_repeat() {
+        let grid = produce_grid(&[".....", "..#..", "..#..", "..#..", "....."]);
+        let mut automaton = Automaton::new(grid, Adjacency::All);
+        let run = automaton.run_detecting_repeats(UpdateMode::Synchronous, None, life);
+        assert_eq!(run.stop, StopReason::Repeated { first_seen: 0 });
+        assert_eq!(run.changes, vec![4, 4]);
+        assert_eq!(automaton.get_generation(), 2);
+    }
+
+    #[test]
+    fn test_generation_limit() {
+        let grid = produce_grid(&[".....", "..#..", "..#..", "..#..", "....."]);
+        let mut automaton = Automaton::new(grid, Adjacency::All);
+        let run = automaton.run(UpdateMode::Synchronous, Some(5), life);
+        assert_eq!(run.stop, StopReason::GenerationLimit);
+        assert_eq!(run.changes.len(), 5);
+        assert_eq!(run.total_changes(), 20);
+    }
+
+    #[test]
+    fn test_fixed_point() {
+        // A block never changes
+        let grid = produce_grid(&["....", ".##.", ".##.", "...."]);
+        let mut automaton = Automaton::new(grid.clone(), Adjacency::All);
+        let run = automaton.run(UpdateMode::Synchronous, None, life);
+        assert_eq!(run.stop, StopReason::FixedPoint);
+        assert!(run.changes.is_empty());
+        assert_eq!(automaton.into_grid(), grid);
+    }
+
+    #[test]
+    fn test_worklist_erosion() {
+        // Remove any '#' with fewer than 2 '#' cardinal neighbours, wave by wave.
+        let grid = produce_grid(&["#....", "##...", "###..", "####."]);
+        let erode = |cell: char, neighbors: &[char]| {
+            if cell == '#' && neighbors.iter().filter(|&&c| c == '#').count() < 2 {
+                '.'
+            } else {
+                cell
+            }
+        };
+        let mut sync = Automaton::new(grid.clone(), Adjacency::Cardinal);
+        let sync_run = sync.run(UpdateMode::Synchronous, None, erode);
+        let mut worklist = Automaton::new(grid, Adjacency::Cardinal);
+        let worklist_run = worklist.run(UpdateMode::Worklist, None, erode);
+
+        assert_eq!(sync_run.stop, StopReason::FixedPoint);
+        assert_eq!(worklist_run.stop, StopReason::FixedPoint);
+        assert_eq!(sync_run.total_changes(), worklist_run.total_changes());
+        assert_eq!(sync.get_grid(), worklist.get_grid());
+        assert_eq!(
+            worklist.get_grid().grid_strings(),
+            vec![".....", "##...", "###..", "###.."]
+        );
+    }
+}
@@ -0,0 +1,298 @@
+use crate::direction::Direction;
+use crate::grid::{Grid, GridOverlay, GridTraversable, SimpleGridOverlay};
+use crate::gridcoord::GridCoordinate;
+use crate::heading::Heading;
+
+use num_bigint::BigUint;
+use num_traits::{One, Zero};
+
+use std::collections::{HashMap, HashSet, VecDeque};
+
+/// A beam is a position and the way it is travelling.
+pub type Beam = Heading<GridCoordinate>;
+
+/// What a cell does to a beam inside it.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum BeamAction {
+    // Carry on the way it was going.
+    Pass,
+    // The beam stops here.
+    Absorb,
+    // Leave facing the new direction.
+    Reflect(Direction),
+    // One beam leaves facing each direction.
+    Split(Vec<Direction>),
+    // One beam for each (step, facing), placed a step away and then facing its own way.
+    Spawn(Vec<(Direction, Direction)>),
+}
+
+/// Everything a set of beams reached, with merged beams only followed once.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BeamTrace {
+    pub visited: HashSet<Beam>,
+    // Cells where one beam turned into more than one, even if some left the grid.
+    pub splits: HashSet<GridCoordinate>,
+}
+
+impl BeamTrace {
+    pub fn energized(&self) -> HashSet<GridCoordinate> {
+        return self.visited.iter().map(|b| b.position).collect();
+    }
+
+    pub fn energized_count(&self) -> usize {
+        return self.energized().len();
+    }
+
+    pub fn energized_grid(&self, width: usize, height: usize) -> Grid<bool> {
+        let mut grid = Grid::new(width, height, vec![false; width * height]);
+        for b in self.visited.iter() {
+            grid.set_value(b.position, true);
+        }
+        return grid;
+    }
+
+    /// An arrow where one beam went through, otherwise how many.
+    pub fn overlay(&self) -> Vec<SimpleGridOverlay> {
+        let mut directions: HashMap<GridCoordinate, Vec<Direction>> = HashMap::new();
+        for b in self.visited.iter() {
+            directions.entry(b.position).or_default().push(b.facing);
+        }
+        let mut result: Vec<SimpleGridOverlay> = directions
+            .into_iter()
+            .map(|(pos, dirs)| {
+                let c = match dirs.len() {
+                    1 => dirs[0].to_arrow().unwrap_or('*'),
+                    n => char::from_digit(n.min(9) as u32, 10).unwrap(),
+                };
+                SimpleGridOverlay::new(c, pos)
+            })
+            .collect();
+        result.sort_by_key(|o| (o.get_position().y, o.get_position().x));
+        return result;
+    }
+}
+
+/// Moves beams over a grid, asking rule what each cell does to a beam.
+pub struct BeamSimulator<'a, G: GridTraversable, F> {
+    grid: &'a G,
+    rule: F,
+}
+
+impl<'a, G, F> BeamSimulator<'a, G, F>
+where
+    G: GridTraversable,
+    F: FnMut(Beam, G::Item) -> BeamAction,
+{
+    pub fn new(grid: &'a G, rule: F) -> BeamSimulator<'a, G, F> {
+        return BeamSimulator {
+            grid: grid,
+            rule: rule,
+        };
+    }
+
+    /// Where a beam goes after the cell it is in, None for each beam that left the grid.
+    pub fn next_beams(&mut self, beam: Beam) -> Vec<Option<Beam>> {
+        let value = self.grid.get_value(beam.position).expect("Beam is on grid");
+        let moves = match (self.rule)(beam, value) {
+            BeamAction::Pass => vec![(beam.facing, beam.facing)],
+            BeamAction::Absorb => vec![],
+            BeamAction::Reflect(d) => vec![(d, d)],
+            BeamAction::Split(dirs) => dirs.into_iter().map(|d| (d, d)).collect(),
+            BeamAction::Spawn(moves) => moves,
+        };
+        return moves
+            .into_iter()
+            .map(|(step, facing)| {
+                self.grid
+                    .get_coordinate_by_direction(beam.position, step)
+                    .map(|pos| Heading::new(pos, facing))
+            })
+            .collect();
+    }
+
+    /// Follow every beam from the starts until they leave, stop, or join a beam already seen.
+    pub fn trace<I: IntoIterator<Item = Beam>>(&mut self, starts: I) -> BeamTrace {
+        let mut visited = HashSet::new();
+        let mut splits = HashSet::new();
+        let mut queue = VecDeque::new();
+        for start in starts {
+            if visited.insert(start) {
+                queue.push_back(start);
+            }
+        }
+        while let Some(beam) = queue.pop_front() {
+            let next = self.next_beams(beam);
+            if next.len() > 1 {
+                splits.insert(beam.position);
+            }
+            for b in next.into_iter().flatten() {
+                if visited.insert(b) {
+                    queue.push_back(b);
+                }
+            }
+        }
+        return BeamTrace {
+            visited: visited,
+            splits: splits,
+        };
+    }
+
+    /// How many different ways a beam from start can end, by leaving the grid or being absorbed.
+    ///
+    /// A split or spawn that would put a beam off the grid counts as that beam leaving, so it is
+    /// a finished history, not dropped. Every split doubles the histories, so this can be huge.
+    /// None if a beam can loop forever.
+    pub fn count_histories(&mut self, start: Beam) -> Option<BigUint> {
+        let mut counts: HashMap<Beam, BigUint> = HashMap::new();
+        let mut in_progress: HashSet<Beam> = HashSet::new();
+        // Beams still to expand have no next beams yet.
+        let mut stack: Vec<(Beam, Option<Vec<Option<Beam>>>)> = vec![(start, None)];
+
+        while let Some((beam, next)) = stack.pop() {
+            match next {
+                None => {
+                    if counts.contains_key(&beam) {
+                        continue;
+                    }
+                    if !in_progress.insert(beam) {
+                        // Came back around to a beam on the current path
+                        return None;
+                    }
+                    let next = self.next_beams(beam);
+                    let children: Vec<Beam> = next.iter().flatten().copied().collect();
+                    stack.push((beam, Some(next)));
+                    for child in children {
+                        stack.push((child, None));
+                    }
+                }
+                Some(next) => {
+                    let total = if next.is_empty() {
+                        BigUint::one()
+                    } else {
+                        next.iter().fold(BigUint::zero(), |acc, b| match b {
+                            Some(b) => acc + &counts[b],
+                            None => acc + 1u32,
+                        })
+                    };
+                    in_progress.remove(&beam);
+                    counts.insert(beam, total);
+                }
+            }
+        }
+        return counts.remove(&start);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::GridPrintable;
+
+    // The mirror cave, / and \ turn beams, | and - split them across their path.
+    fn mirror_rule(beam: Beam, c: char) -> BeamAction {
+        let horizontal = beam.facing == Direction::EAST || beam.facing == Direction::WEST;
+        return match (c, horizontal) {
+            ('/', true) => BeamAction::Reflect(beam.facing.turn_left()),
+            ('/', false) => BeamAction::Reflect(beam.facing.turn_right()),
+            ('\\', true) => BeamAction::Reflect(beam.facing.turn_right()),
+            ('\\', false) => BeamAction::Reflect(beam.facing.turn_left()),
+            ('|', true) => BeamAction::Split(vec![Direction::NORTH, Direction::SOUTH]),
+            ('-', false) => BeamAction::Split(vec![Direction::WEST, Direction::EAST]),
+            ('#', _) => BeamAction::Absorb,
+            _ => BeamAction::Pass,
+        };
+    }
+
+    fn mirror_grid() -> Grid<char> {
+        return Grid::parse(
+            r".|...\....
+|.-.\.....
+.....|-...
+........|.
+..........
+.........\
+..../.\\..
+.-.-/..|..
+.|....-|.\
+..//.|....",
+        )
+        .unwrap();
+    }
+
+    // A beam heading down splits sideways at ^, like a tachyon manifold.
+    fn splitter_rule(_: Beam, c: char) -> BeamAction {
+        return match c {
+            '^' => BeamAction::Spawn(vec![
+                (Direction::WEST, Direction::SOUTH),
+                (Direction::EAST, Direction::SOUTH),
+            ]),
+            _ => BeamAction::Pass,
+        };
+    }
+
+    #[test]
+    fn test_trace_mirrors() {
+        let grid = mirror_grid();
+        let mut sim = BeamSimulator::new(&grid, mirror_rule);
+        let start = Heading::new(GridCoordinate::new(0, 0), Direction::EAST);
+        let trace = sim.trace([start]);
+        assert_eq!(trace.energized_count(), 46);
+        // Loops are only followed once, so this terminates, but has no finite history count
+        assert_eq!(sim.count_histories(start), None);
+    }
+
+    #[test]
+    fn test_absorb_and_reflect() {
+        let grid = Grid::parse("..\\\n.#.\n...").unwrap();
+        let mut sim = BeamSimulator::new(&grid, mirror_rule);
+        let trace = sim.trace([Heading::new(GridCoordinate::new(0, 0), Direction::EAST)]);
+        assert_eq!(trace.energized_count(), 5);
+        assert!(trace.splits.is_empty());
+        let energized = trace.energized_grid(3, 3);
+        assert_eq!(energized.get_value(GridCoordinate::new(2, 2)), Some(true));
+        assert_eq!(energized.get_value(GridCoordinate::new(1, 1)), Some(false));
+        let overlay: Vec<char> = trace.overlay().iter().map(|o| o.get_character()).collect();
+        assert_eq!(overlay, vec!['>', '>', '>', 'v', 'v']);
+    }
+
+    #[test]
+    fn test_merge_and_count_histories() {
+        let grid = Grid::parse("...\n.^.\n...\n^.^\n...").unwrap();
+        let mut sim = BeamSimulator::new(&grid, splitter_rule);
+        let start = Heading::new(GridCoordinate::new(1, 0), Direction::SOUTH);
+        let trace = sim.trace([start]);
+        assert_eq!(trace.splits.len(), 3);
+        // Both outer splitters send a beam into the middle column, it is only followed once
+        assert_eq!(
+            trace
+                .visited
+                .iter()
+                .filter(|b| b.position == GridCoordinate::new(1, 4))
+                .count(),
+            1
+        );
+        // Each outer splitter loses one side off the grid
+        assert_eq!(sim.count_histories(start), Some(BigUint::from(4u32)));
+    }
+
+    #[test]
+    fn test_count_histories_big() {
+        // 80 rows of splitters side by side, each doubling the histories
+        let width = 161;
+        let mut lines = vec![];
+        for y in 0..80 {
+            lines.push(".".repeat(width));
+            let mut row = vec!['.'; width];
+            for x in (80 - y..=80 + y).step_by(2) {
+                row[x] = '^';
+            }
+            lines.push(row.into_iter().collect());
+        }
+        let grid = Grid::from_strings(&lines).unwrap();
+        let mut sim = BeamSimulator::new(&grid, splitter_rule);
+        let count = sim
+            .count_histories(Heading::new(GridCoordinate::new(80, 0), Direction::SOUTH))
+            .unwrap();
+        assert_eq!(count, BigUint::from(1u32) << 80);
+    }
+}
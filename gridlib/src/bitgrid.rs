@@ -0,0 +1,579 @@
+use crate::direction::Direction;
+use crate::grid::{bounded_coordinate_by_direction, Grid, GridTraversable};
+use crate::gridcoord::GridCoordinate;
+
+use std::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, Not};
+
+const WORD_BITS: usize = 64;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BitGrid {
+    /* Grid of booleans, one bit per cell.
+     *
+     * Every row starts on a fresh u64, bit x of a row lives in
+     * word x / 64 at bit x % 64. Bits past the width are always kept 0.
+     */
+    width: usize,
+    height: usize,
+    words_per_row: usize,
+    words: Vec<u64>,
+}
+
+impl BitGrid {
+    pub fn new(width: usize, height: usize) -> BitGrid {
+        let words_per_row = width.div_ceil(WORD_BITS);
+        return BitGrid {
+            width: width,
+            height: height,
+            words_per_row: words_per_row,
+            words: vec![0; words_per_row * height],
+        };
+    }
+
+    pub fn from_coordinates<I>(width: usize, height: usize, coords: I) -> BitGrid
+    where
+        I: IntoIterator<Item = GridCoordinate>,
+    {
+        let mut result = BitGrid::new(width, height);
+        for coord in coords {
+            result.set_value(coord, true);
+        }
+        return result;
+    }
+
+    pub fn get_width(&self) -> usize {
+        return self.width;
+    }
+
+    pub fn get_height(&self) -> usize {
+        return self.height;
+    }
+
+    #[inline]
+    pub fn get_row(&self, y: usize) -> &[u64] {
+        return &self.words[y * self.words_per_row..(y + 1) * self.words_per_row];
+    }
+
+    /// Number of set cells.
+    pub fn count_ones(&self) -> usize {
+        return self.words.iter().map(|w| w.count_ones() as usize).sum();
+    }
+
+    pub fn any(&self) -> bool {
+        return self.words.iter().any(|&w| w != 0);
+    }
+
+    /// All set cells, in reading order.
+    pub fn ones(&self) -> Vec<GridCoordinate> {
+        let mut result = vec![];
+        for y in 0..self.height {
+            for (i, &word) in self.get_row(y).iter().enumerate() {
+                let mut remaining = word;
+                while remaining != 0 {
+                    let bit = remaining.trailing_zeros() as usize;
+                    result.push(GridCoordinate::new(i * WORD_BITS + bit, y));
+                    remaining &= remaining - 1;
+                }
+            }
+        }
+        return result;
+    }
+
+    pub fn to_grid(&self) -> Grid<bool> {
+        let mut values = vec![false; self.width * self.height];
+        for coord in self.ones() {
+            values[coord.x + coord.y * self.width] = true;
+        }
+        return Grid::new(self.width, self.height, values);
+    }
+
+    #[inline]
+    fn last_word_mask(&self) -> u64 {
+        let used = self.width % WORD_BITS;
+        if used == 0 {
+            return u64::MAX;
+        }
+        return (1u64 << used) - 1;
+    }
+
+    fn clear_padding(&mut self) {
+        if self.words_per_row == 0 {
+            return;
+        }
+        let mask = self.last_word_mask();
+        for y in 0..self.height {
+            self.words[(y + 1) * self.words_per_row - 1] &= mask;
+        }
+    }
+
+    fn shift_row_east(row: &mut [u64]) {
+        let mut carry = 0;
+        for word in row.iter_mut() {
+            let next_carry = *word >> (WORD_BITS - 1);
+            *word = (*word << 1) | carry;
+            carry = next_carry;
+        }
+    }
+
+    fn shift_row_west(row: &mut [u64]) {
+        let mut carry = 0;
+        for word in row.iter_mut().rev() {
+            let next_carry = *word << (WORD_BITS - 1);
+            *word = (*word >> 1) | carry;
+            carry = next_carry;
+        }
+    }
+
+    /// Move every set cell one step in direction, cells pushed off the edge are lost.
+    pub fn shift(&self, direction: Direction) -> BitGrid {
+        let mut result = self.clone();
+        let row_len = self.words_per_row;
+        if row_len == 0 || self.height == 0 {
+            return result;
+        }
+
+        match direction {
+            Direction::NORTH | Direction::NORTHEAST | Direction::NORTHWEST => {
+                result.words.copy_within(row_len.., 0);
+                let start = result.words.len() - row_len;
+                result.words[start..].fill(0);
+            }
+            Direction::SOUTH | Direction::SOUTHEAST | Direction::SOUTHWEST => {
+                let end = result.words.len() - row_len;
+                result.words.copy_within(..end, row_len);
+                result.words[..row_len].fill(0);
+            }
+            Direction::EAST | Direction::WEST => {}
+        }
+
+        match direction {
+            Direction::EAST | Direction::NORTHEAST | Direction::SOUTHEAST => {
+                result
+                    .words
+                    .chunks_mut(row_len)
+                    .for_each(BitGrid::shift_row_east);
+            }
+            Direction::WEST | Direction::NORTHWEST | Direction::SOUTHWEST => {
+                result
+                    .words
+                    .chunks_mut(row_len)
+                    .for_each(BitGrid::shift_row_west);
+            }
+            Direction::NORTH | Direction::SOUTH => {}
+        }
+        result.clear_padding();
+        return result;
+    }
+
+    /// Neighbour counts as 4 bit planes, plane i holds bit i of each cell's count.
+    fn neighbor_count_planes(&self) -> [Vec<u64>; 4] {
+        let mut planes: [Vec<u64>; 4] = std::array::from_fn(|_| vec![0; self.words.len()]);
+        for &direction in Direction::iterator() {
+            let shifted = self.shift(direction);
+            for (i, &word) in shifted.words.iter().enumerate() {
+                // Ripple carry adder, one bit per cell in parallel
+                let mut carry = word;
+                for plane in planes.iter_mut() {
+                    let next_carry = plane[i] & carry;
+                    plane[i] ^= carry;
+                    carry = next_carry;
+                }
+            }
+        }
+        return planes;
+    }
+
+    /// How many of the eight surrounding cells are set, for every cell.
+    pub fn neighbor_counts(&self) -> Grid<u8> {
+        let planes = self.neighbor_count_planes();
+        let mut values = vec![0; self.width * self.height];
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let index = y * self.words_per_row + x / WORD_BITS;
+                let bit = x % WORD_BITS;
+                let mut count = 0;
+                for (i, plane) in planes.iter().enumerate() {
+                    count |= (((plane[index] >> bit) & 1) as u8) << i;
+                }
+                values[x + y * self.width] = count;
+            }
+        }
+        return Grid::new(self.width, self.height, values);
+    }
+
+    /// Cells with at least `n` of their eight neighbours set.
+    pub fn neighbors_at_least(&self, n: u8) -> BitGrid {
+        let planes = self.neighbor_count_planes();
+        let mut result = BitGrid::new(self.width, self.height);
+        for count in n..=8 {
+            for i in 0..result.words.len() {
+                let mut matches = u64::MAX;
+                for (bit, plane) in planes.iter().enumerate() {
+                    if (count >> bit) & 1 == 1 {
+                        matches &= plane[i];
+                    } else {
+                        matches &= !plane[i];
+                    }
+                }
+                result.words[i] |= matches;
+            }
+        }
+        result.clear_padding();
+        return result;
+    }
+
+    /// Bits of one shape row, moved `at_x` cells east, that land in word `target` of a row.
+    #[inline]
+    fn shifted_shape_word(row: &[u64], at_x: usize, target: usize) -> u64 {
+        let first = at_x / WORD_BITS;
+        let offset = at_x % WORD_BITS;
+        if target < first {
+            return 0;
+        }
+        let j = target - first;
+        let mut bits = 0;
+        if j < row.len() {
+            bits |= row[j] << offset;
+        }
+        if offset != 0 && j >= 1 && j - 1 < row.len() {
+            // High bits spilling over from the previous word
+            bits |= row[j - 1] >> (WORD_BITS - offset);
+        }
+        return bits;
+    }
+
+    /// Range of our words in a row that shape can touch when placed at x.
+    #[inline]
+    fn shape_word_range(&self, shape: &BitGrid, x: usize) -> std::ops::Range<usize> {
+        let first = x / WORD_BITS;
+        let last = (first + shape.words_per_row + 1).min(self.words_per_row);
+        return first.min(last)..last;
+    }
+
+    /// Does any set cell of shape, placed with its top left at `at`, overlap ours.
+    /// Parts of shape outside of this grid are ignored.
+    #[inline]
+    pub fn collides_at(&self, shape: &BitGrid, at: GridCoordinate) -> bool {
+        let range = self.shape_word_range(shape, at.x);
+        for sy in 0..shape.height {
+            // Past the bottom, or so far down the sum overflows
+            let y = match at.y.checked_add(sy) {
+                Some(y) if y < self.height => y,
+                _ => break,
+            };
+            let row = shape.get_row(sy);
+            for target in range.clone() {
+                let bits = BitGrid::shifted_shape_word(row, at.x, target);
+                if self.words[y * self.words_per_row + target] & bits != 0 {
+                    return true;
+                }
+            }
+        }
+        return false;
+    }
+
+    /// shape is entirely inside this grid at `at` and doesn't collide.
+    #[inline]
+    pub fn fits_at(&self, shape: &BitGrid, at: GridCoordinate) -> bool {
+        let right = at.x.checked_add(shape.width);
+        let bottom = at.y.checked_add(shape.height);
+        if right.is_none_or(|r| r > self.width) || bottom.is_none_or(|b| b > self.height) {
+            return false;
+        }
+        return !self.collides_at(shape, at);
+    }
+
+    #[inline]
+    fn stamp(&mut self, shape: &BitGrid, at: GridCoordinate, value: bool) {
+        let range = self.shape_word_range(shape, at.x);
+        let last_mask = self.last_word_mask();
+        for sy in 0..shape.height {
+            // Past the bottom, or so far down the sum overflows
+            let y = match at.y.checked_add(sy) {
+                Some(y) if y < self.height => y,
+                _ => break,
+            };
+            let row = shape.get_row(sy);
+            for target in range.clone() {
+                let mut bits = BitGrid::shifted_shape_word(row, at.x, target);
+                if target == self.words_per_row - 1 {
+                    bits &= last_mask;
+                }
+                let index = y * self.words_per_row + target;
+                if value {
+                    self.words[index] |= bits;
+                } else {
+                    self.words[index] &= !bits;
+                }
+            }
+        }
+    }
+
+    /// Set every cell covered by shape, parts outside of this grid are dropped.
+    #[inline]
+    pub fn place(&mut self, shape: &BitGrid, at: GridCoordinate) {
+        self.stamp(shape, at, true);
+    }
+
+    /// Clear every cell covered by shape.
+    #[inline]
+    pub fn remove(&mut self, shape: &BitGrid, at: GridCoordinate) {
+        self.stamp(shape, at, false);
+    }
+
+    /// Cells set here but not in other.
+    pub fn and_not(&self, other: &BitGrid) -> BitGrid {
+        return self.combine(other, |a, b| a & !b);
+    }
+
+    fn combine<F: Fn(u64, u64) -> u64>(&self, other: &BitGrid, f: F) -> BitGrid {
+        assert_eq!(self.width, other.width);
+        assert_eq!(self.height, other.height);
+        let mut result = self.clone();
+        for (word, &other_word) in result.words.iter_mut().zip(other.words.iter()) {
+            *word = f(*word, other_word);
+        }
+        return result;
+    }
+}
+
+impl GridTraversable for BitGrid {
+    type Item = bool;
+
+    fn get_value(&self, pos: GridCoordinate) -> Option<bool> {
+        if pos.y >= self.height || pos.x >= self.width {
+            return None;
+        }
+        let word = self.words[pos.y * self.words_per_row + pos.x / WORD_BITS];
+        return Some((word >> (pos.x % WORD_BITS)) & 1 == 1);
+    }
+
+    fn set_value(&mut self, pos: GridCoordinate, value: bool) {
+        if pos.y >= self.height || pos.x >= self.width {
+            return;
+        }
+        let index = pos.y * self.words_per_row + pos.x / WORD_BITS;
+        let bit = 1u64 << (pos.x % WORD_BITS);
+        if value {
+            self.words[index] |= bit;
+        } else {
+            self.words[index] &= !bit;
+        }
+    }
+
+    fn get_coordinate_by_direction(
+        &self,
+        pos: GridCoordinate,
+        direction: Direction,
+    ) -> Option<GridCoordinate> {
+        return bounded_coordinate_by_direction(self.width, self.height, pos, direction);
+    }
+}
+
+impl From<&Grid<bool>> for BitGrid {
+    fn from(grid: &Grid<bool>) -> BitGrid {
+        let mut result = BitGrid::new(grid.get_width(), grid.get_height());
+        for coord in grid.coord_iter() {
+            if grid.get_value(coord).unwrap() {
+                result.set_value(coord, true);
+            }
+        }
+        return result;
+    }
+}
+
+impl BitAnd for &BitGrid {
+    type Output = BitGrid;
+
+    fn bitand(self, other: &BitGrid) -> BitGrid {
+        return self.combine(other, |a, b| a & b);
+    }
+}
+
+impl BitOr for &BitGrid {
+    type Output = BitGrid;
+
+    fn bitor(self, other: &BitGrid) -> BitGrid {
+        return self.combine(other, |a, b| a | b);
+    }
+}
+
+impl BitXor for &BitGrid {
+    type Output = BitGrid;
+
+    fn bitxor(self, other: &BitGrid) -> BitGrid {
+        return self.combine(other, |a, b| a ^ b);
+    }
+}
+
+impl Not for &BitGrid {
+    type Output = BitGrid;
+
+    fn not(self) -> BitGrid {
+        let mut result = self.clone();
+        for word in result.words.iter_mut() {
+            *word = !*word;
+        }
+        result.clear_padding();
+        return result;
+    }
+}
+
+impl BitAndAssign<&BitGrid> for BitGrid {
+    fn bitand_assign(&mut self, other: &BitGrid) {
+        *self = &*self & other;
+    }
+}
+
+impl BitOrAssign<&BitGrid> for BitGrid {
+    fn bitor_assign(&mut self, other: &BitGrid) {
+        *self = &*self | other;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn produce_bitgrid(lines: &[&str]) -> BitGrid {
+        let mut result = BitGrid::new(lines[0].len(), lines.len());
+        for (y, line) in lines.iter().enumerate() {
+            for (x, c) in line.chars().enumerate() {
+                result.set_value(GridCoordinate::new(x, y), c == '#');
+            }
+        }
+        return result;
+    }
+
+    #[test]
+    fn test_get_set() {
+        let mut grid = BitGrid::new(130, 3);
+        let coord = GridCoordinate::new(129, 2);
+        assert_eq!(grid.get_value(coord), Some(false));
+        grid.set_value(coord, true);
+        assert_eq!(grid.get_value(coord), Some(true));
+        assert_eq!(grid.count_ones(), 1);
+        grid.set_value(coord, false);
+        assert_eq!(grid.count_ones(), 0);
+        assert_eq!(grid.get_value(GridCoordinate::new(130, 0)), None);
+        assert_eq!(grid.get_row(0).len(), 3);
+    }
+
+    #[test]
+    fn test_grid_round_trip() {
+        let grid = Grid::new(3, 2, vec![true, false, true, false, false, true]);
+        let bits = BitGrid::from(&grid);
+        assert_eq!(bits.count_ones(), 3);
+        assert_eq!(
+            bits.ones(),
+            vec![
+                GridCoordinate::new(0, 0),
+                GridCoordinate::new(2, 0),
+                GridCoordinate::new(2, 1)
+            ]
+        );
+        assert_eq!(bits.to_grid(), grid);
+    }
+
+    #[test]
+    fn test_shift_across_words() {
+        let mut grid = BitGrid::new(100, 2);
+        grid.set_value(GridCoordinate::new(63, 0), true);
+        grid.set_value(GridCoordinate::new(99, 1), true);
+        let east = grid.shift(Direction::EAST);
+        // 99 falls off the edge
+        assert_eq!(east.ones(), vec![GridCoordinate::new(64, 0)]);
+        let west = east.shift(Direction::WEST);
+        assert_eq!(west.ones(), vec![GridCoordinate::new(63, 0)]);
+        let southwest = grid.shift(Direction::SOUTHWEST);
+        assert_eq!(southwest.ones(), vec![GridCoordinate::new(62, 1)]);
+        let north = grid.shift(Direction::NORTH);
+        assert_eq!(north.ones(), vec![GridCoordinate::new(99, 0)]);
+    }
+
+    #[test]
+    fn test_operators() {
+        let a = produce_bitgrid(&["##..", "#..#"]);
+        let b = produce_bitgrid(&["#.#.", "#..."]);
+        assert_eq!((&a & &b).ones().len(), 2);
+        assert_eq!((&a | &b).ones().len(), 5);
+        assert_eq!((&a ^ &b).ones().len(), 3);
+        assert_eq!(a.and_not(&b), produce_bitgrid(&[".#..", "...#"]));
+        assert_eq!(!&a, produce_bitgrid(&["..##", ".##."]));
+        let mut c = a.clone();
+        c |= &b;
+        c &= &a;
+        assert_eq!(c, a);
+    }
+
+    #[test]
+    fn test_neighbor_counts() {
+        let grid = produce_bitgrid(&["###", "#.#", "###"]);
+        let counts = grid.neighbor_counts();
+        assert_eq!(counts.data_copy(), vec![2, 4, 2, 4, 8, 4, 2, 4, 2]);
+        assert_eq!(
+            grid.neighbors_at_least(4),
+            produce_bitgrid(&[".#.", "###", ".#."])
+        );
+        assert_eq!(grid.neighbors_at_least(0).count_ones(), 9);
+    }
+
+    #[test]
+    fn test_neighbor_counts_match_grid() {
+        // Wide enough to cross word boundaries
+        let mut grid = BitGrid::new(150, 4);
+        for x in (0..150).step_by(3) {
+            grid.set_value(GridCoordinate::new(x, x % 4), true);
+            grid.set_value(GridCoordinate::new(x + 1, (x + 1) % 4), true);
+        }
+        let counts = grid.neighbor_counts();
+        for coord in counts.coord_iter() {
+            let expected = grid
+                .get_all_adjacent_coordinates(coord)
+                .into_iter()
+                .filter(|&c| grid.get_value(c).unwrap())
+                .count() as u8;
+            assert_eq!(counts.get_value(coord), Some(expected), "at {}", coord);
+        }
+    }
+
+    #[test]
+    fn test_place_and_collide() {
+        let shape = produce_bitgrid(&["##", "#."]);
+        let mut board = BitGrid::new(70, 3);
+        let at = GridCoordinate::new(63, 1);
+        assert!(board.fits_at(&shape, at));
+        board.place(&shape, at);
+        assert_eq!(
+            board.ones(),
+            vec![
+                GridCoordinate::new(63, 1),
+                GridCoordinate::new(64, 1),
+                GridCoordinate::new(63, 2)
+            ]
+        );
+        assert!(!board.collides_at(&shape, GridCoordinate::new(65, 0)));
+        assert!(board.collides_at(&shape, GridCoordinate::new(62, 1)));
+        assert!(!board.fits_at(&shape, GridCoordinate::new(69, 0)));
+        assert!(!board.fits_at(&shape, GridCoordinate::new(0, 2)));
+        board.remove(&shape, at);
+        assert!(!board.any());
+    }
+
+    #[test]
+    fn test_place_far_away() {
+        // Sums past usize::MAX don't fit, and placing there touches nothing
+        let shape = produce_bitgrid(&["##", "#."]);
+        let mut board = BitGrid::new(70, 3);
+        for at in [
+            GridCoordinate::new(usize::MAX, 0),
+            GridCoordinate::new(0, usize::MAX),
+            GridCoordinate::new(usize::MAX - 1, usize::MAX - 1),
+        ] {
+            assert!(!board.fits_at(&shape, at));
+            assert!(!board.collides_at(&shape, at));
+            board.place(&shape, at);
+        }
+        assert!(!board.any());
+    }
+}
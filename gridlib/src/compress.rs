@@ -0,0 +1,216 @@
+use crate::grid::{Grid, GridTraversable};
+use crate::gridcoord::GridCoordinate;
+use crate::rectangle::{GridPoint, Rectangle};
+
+use mathlib::CoordinateCompressor;
+use num_traits::PrimInt;
+
+/// Squashes a set of points onto a small `Grid`, one `CoordinateCompressor` per axis.
+///
+/// Each cell stands for a block of real points, its weight is how many.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GridCompressor<T: PrimInt> {
+    xs: CoordinateCompressor<T>,
+    ys: CoordinateCompressor<T>,
+}
+
+impl<T: PrimInt> GridCompressor<T> {
+    pub fn new(xs: CoordinateCompressor<T>, ys: CoordinateCompressor<T>) -> GridCompressor<T> {
+        return GridCompressor { xs: xs, ys: ys };
+    }
+
+    /// Compress on every x and y the points use, with gap cells between them if gaps is set.
+    pub fn from_points<P: GridPoint<Scalar = T>>(points: &[P], gaps: bool) -> GridCompressor<T> {
+        let xs = points.iter().map(|p| p.get_x());
+        let ys = points.iter().map(|p| p.get_y());
+        if gaps {
+            return GridCompressor::new(
+                CoordinateCompressor::with_gaps(xs),
+                CoordinateCompressor::with_gaps(ys),
+            );
+        }
+        return GridCompressor::new(CoordinateCompressor::new(xs), CoordinateCompressor::new(ys));
+    }
+
+    pub fn xs(&self) -> &CoordinateCompressor<T> {
+        return &self.xs;
+    }
+
+    pub fn ys(&self) -> &CoordinateCompressor<T> {
+        return &self.ys;
+    }
+
+    pub fn get_width(&self) -> usize {
+        return self.xs.len();
+    }
+
+    pub fn get_height(&self) -> usize {
+        return self.ys.len();
+    }
+
+    /// The cell a real point falls in, None if it is outside or between cells.
+    pub fn cell<P: GridPoint<Scalar = T>>(&self, p: P) -> Option<GridCoordinate> {
+        return Some(GridCoordinate::new(
+            self.xs.index_containing(p.get_x())?,
+            self.ys.index_containing(p.get_y())?,
+        ));
+    }
+
+    /// The real point at a cell, None unless both x and y are values rather than gaps.
+    pub fn point<P: GridPoint<Scalar = T>>(&self, c: GridCoordinate) -> Option<P> {
+        return Some(P::from_xy(self.xs.value_at(c.x)?, self.ys.value_at(c.y)?));
+    }
+
+    /// The real points a cell stands for, None for a gap with nothing in it.
+    pub fn cell_rectangle<P: GridPoint<Scalar = T>>(
+        &self,
+        c: GridCoordinate,
+    ) -> Option<Rectangle<P>> {
+        let (min_x, max_x) = self.xs.cell_range(c.x)?;
+        let (min_y, max_y) = self.ys.cell_range(c.y)?;
+        return Some(Rectangle::from_corners(
+            P::from_xy(min_x, min_y),
+            P::from_xy(max_x, max_y),
+        ));
+    }
+
+    /// How many real points a cell stands for.
+    ///
+    /// Panics if that doesn't fit in a u128, which only 128 bit axes can reach.
+    pub fn cell_weight(&self, c: GridCoordinate) -> u128 {
+        return self
+            .xs
+            .weight(c.x)
+            .checked_mul(self.ys.weight(c.y))
+            .expect("Weight should fit in a u128");
+    }
+
+    /// How many real points a block of cells stands for, both corners included.
+    ///
+    /// Panics if that doesn't fit in a u128, like every point of two full range u64 axes.
+    pub fn rectangle_weight(&self, cells: &Rectangle<GridCoordinate>) -> u128 {
+        return self
+            .xs
+            .span_weight(cells.min_x(), cells.max_x())
+            .checked_mul(self.ys.span_weight(cells.min_y(), cells.max_y()))
+            .expect("Weight should fit in a u128");
+    }
+
+    /// A compressed grid with every cell set to fill.
+    pub fn grid<V: Copy>(&self, fill: V) -> Grid<V> {
+        let (width, height) = (self.get_width(), self.get_height());
+        return Grid::new(width, height, vec![fill; width * height]);
+    }
+
+    /// A compressed grid with the cells of points set to marked, the rest empty.
+    /// Points outside the compressor are skipped.
+    pub fn compress_points<P: GridPoint<Scalar = T>, V: Copy>(
+        &self,
+        points: &[P],
+        empty: V,
+        marked: V,
+    ) -> Grid<V> {
+        let mut grid = self.grid(empty);
+        for c in points.iter().filter_map(|&p| self.cell(p)) {
+            grid.set_value(c, marked);
+        }
+        return grid;
+    }
+
+    /// The real number of points in cells that match, weighting each cell by what it stands for.
+    ///
+    /// Panics if that doesn't fit in a u128, the same as `rectangle_weight`.
+    pub fn weighted_count<V: Copy, F: Fn(V) -> bool>(&self, grid: &Grid<V>, predicate: F) -> u128 {
+        return grid
+            .coord_iter()
+            .filter(|&c| grid.get_value(c).is_some_and(&predicate))
+            .try_fold(0u128, |acc, c| acc.checked_add(self.cell_weight(c)))
+            .expect("Weight should fit in a u128");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gridcoord::GridCoordinateInf64;
+    use mathlib::PointLocation;
+
+    fn points(coords: &[(i64, i64)]) -> Vec<GridCoordinateInf64> {
+        return coords
+            .iter()
+            .map(|&(x, y)| GridCoordinateInf64::new(x, y))
+            .collect();
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let corners = points(&[(100, -5), (7, 20), (40, 20)]);
+        let compressor = GridCompressor::from_points(&corners, true);
+        assert_eq!(compressor.get_width(), 5);
+        assert_eq!(compressor.get_height(), 3);
+        for &p in corners.iter() {
+            let c = compressor.cell(p).unwrap();
+            assert_eq!(compressor.point::<GridCoordinateInf64>(c), Some(p));
+        }
+        let between = compressor.cell(GridCoordinateInf64::new(50, 0)).unwrap();
+        assert_eq!(between, GridCoordinate::new(3, 1));
+        assert_eq!(compressor.point::<GridCoordinateInf64>(between), None);
+        assert_eq!(compressor.cell_weight(between), 59 * 24);
+        assert_eq!(
+            compressor.cell_rectangle(between),
+            Some(Rectangle::from_corners(
+                GridCoordinateInf64::new(41, -4),
+                GridCoordinateInf64::new(99, 19)
+            ))
+        );
+        assert_eq!(compressor.cell(GridCoordinateInf64::new(0, 0)), None);
+
+        let grid = compressor.compress_points(&corners, '.', '#');
+        assert_eq!(grid.grid_strings(), vec!["....#", ".....", "#.#.."]);
+    }
+
+    #[test]
+    fn test_weighted_polygon_area() {
+        // Each compressed cell is wholly inside or wholly outside, so marking them by one real
+        // point and weighting them gives the area, boundary included
+        let outline = [(0, 0), (30, 0), (30, 10), (12, 10), (12, 25), (0, 25)];
+        let compressor = GridCompressor::from_points(&points(&outline), true);
+        let mut grid = compressor.grid(false);
+        for c in grid.coord_iter() {
+            if let Some(cell) = compressor.cell_rectangle::<GridCoordinateInf64>(c) {
+                let p = (cell.top_left.x, cell.top_left.y);
+                let inside = mathlib::point_in_polygon(p, &outline) != PointLocation::Outside;
+                grid.set_value(c, inside);
+            }
+        }
+        let expected = 31 * 11 + 13 * 15;
+        assert_eq!(compressor.weighted_count(&grid, |v| v), expected);
+        let everything = Rectangle::from_corners(
+            GridCoordinate::new(0, 0),
+            GridCoordinate::new(grid.get_width() - 1, grid.get_height() - 1),
+        );
+        assert_eq!(compressor.rectangle_weight(&everything), 31 * 26);
+        assert_eq!(compressor.weighted_count(&grid, |v| !v), 31 * 26 - expected);
+    }
+
+    #[test]
+    fn test_full_range_weights() {
+        let corners = points(&[(i64::MIN, i64::MIN), (i64::MAX, i64::MAX)]);
+        let compressor = GridCompressor::from_points(&corners, true);
+        let middle = GridCoordinate::new(1, 1);
+        assert_eq!(compressor.cell_weight(middle), ((1u128 << 64) - 2).pow(2));
+        let row = Rectangle::from_corners(GridCoordinate::new(0, 0), GridCoordinate::new(2, 0));
+        assert_eq!(compressor.rectangle_weight(&row), 1 << 64);
+    }
+
+    #[test]
+    #[should_panic(expected = "Weight should fit in a u128")]
+    fn test_weight_past_u128() {
+        // 2^64 by 2^64 points
+        let corners = points(&[(i64::MIN, i64::MIN), (i64::MAX, i64::MAX)]);
+        let compressor = GridCompressor::from_points(&corners, true);
+        let everything =
+            Rectangle::from_corners(GridCoordinate::new(0, 0), GridCoordinate::new(2, 2));
+        compressor.rectangle_weight(&everything);
+    }
+}
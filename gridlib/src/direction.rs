@@ -2,6 +2,7 @@ use std::fmt::{Display, Formatter};
 use std::slice::Iter;
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Direction {
     NORTH,
     EAST,
@@ -63,6 +64,120 @@ impl Direction {
         ];
         DIRECTIONS.iter()
     }
+
+    // Position in the clockwise order of `iterator`, NORTH is 0.
+    fn clockwise_index(&self) -> usize {
+        return Direction::iterator().position(|d| d == self).unwrap();
+    }
+
+    fn from_clockwise_index(index: usize) -> Direction {
+        return *Direction::iterator().nth(index % 8).unwrap();
+    }
+
+    /// 90 degrees anticlockwise.
+    pub fn turn_left(&self) -> Direction {
+        return Direction::from_clockwise_index(self.clockwise_index() + 6);
+    }
+
+    /// 90 degrees clockwise.
+    pub fn turn_right(&self) -> Direction {
+        return Direction::from_clockwise_index(self.clockwise_index() + 2);
+    }
+
+    pub fn turn_left_45(&self) -> Direction {
+        return Direction::from_clockwise_index(self.clockwise_index() + 7);
+    }
+
+    pub fn turn_right_45(&self) -> Direction {
+        return Direction::from_clockwise_index(self.clockwise_index() + 1);
+    }
+
+    pub fn opposite(&self) -> Direction {
+        return Direction::from_clockwise_index(self.clockwise_index() + 4);
+    }
+
+    pub fn turn(&self, turn: Turn) -> Direction {
+        return match turn {
+            Turn::Left => self.turn_left(),
+            Turn::Right => self.turn_right(),
+        };
+    }
+
+    pub fn is_cardinal(&self) -> bool {
+        return self.clockwise_index().is_multiple_of(2);
+    }
+
+    pub fn is_diagonal(&self) -> bool {
+        return !self.is_cardinal();
+    }
+
+    /// (dx, dy) of a single step, y increases to the south.
+    pub fn offset(&self) -> (i32, i32) {
+        return match self {
+            Direction::NORTH => (0, -1),
+            Direction::EAST => (1, 0),
+            Direction::SOUTH => (0, 1),
+            Direction::WEST => (-1, 0),
+            Direction::NORTHEAST => (1, -1),
+            Direction::SOUTHEAST => (1, 1),
+            Direction::SOUTHWEST => (-1, 1),
+            Direction::NORTHWEST => (-1, -1),
+        };
+    }
+
+    pub fn from_offset(dx: i32, dy: i32) -> Option<Direction> {
+        return Direction::iterator()
+            .find(|d| d.offset() == (dx, dy))
+            .copied();
+    }
+
+    /// Arrows `^>v<`, `UDLR` or compass letters `NESW`.
+    pub fn from_char(c: char) -> Option<Direction> {
+        return match c {
+            '^' | 'U' | 'N' => Some(Direction::NORTH),
+            '>' | 'R' | 'E' => Some(Direction::EAST),
+            'v' | 'D' | 'S' => Some(Direction::SOUTH),
+            '<' | 'L' | 'W' => Some(Direction::WEST),
+            _ => None,
+        };
+    }
+
+    /// The arrow for a cardinal direction, None for diagonals.
+    pub fn to_arrow(&self) -> Option<char> {
+        return match self {
+            Direction::NORTH => Some('^'),
+            Direction::EAST => Some('>'),
+            Direction::SOUTH => Some('v'),
+            Direction::WEST => Some('<'),
+            _ => None,
+        };
+    }
+}
+
+/// A relative turn, as in instructions like `R5, L3`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Turn {
+    Left,
+    Right,
+}
+
+impl Turn {
+    pub fn from_char(c: char) -> Option<Turn> {
+        return match c {
+            'L' => Some(Turn::Left),
+            'R' => Some(Turn::Right),
+            _ => None,
+        };
+    }
+
+    /// Split an instruction like `R12` into the turn and the distance after it.
+    pub fn parse_instruction(s: &str) -> Option<(Turn, usize)> {
+        let s = s.trim();
+        let turn = Turn::from_char(s.chars().next()?)?;
+        let distance = s[1..].parse().ok()?;
+        return Some((turn, distance));
+    }
 }
 
 #[cfg(test)]
@@ -80,4 +195,54 @@ mod tests {
         assert_eq!(format!("{}", Direction::SOUTHEAST), "SOUTHEAST");
         assert_eq!(format!("{}", Direction::SOUTHWEST), "SOUTHWEST");
     }
+
+    #[test]
+    fn test_turns() {
+        assert_eq!(Direction::NORTH.turn_left(), Direction::WEST);
+        assert_eq!(Direction::NORTH.turn_right(), Direction::EAST);
+        assert_eq!(Direction::NORTHWEST.turn_right(), Direction::NORTHEAST);
+        assert_eq!(Direction::NORTH.turn_left_45(), Direction::NORTHWEST);
+        assert_eq!(Direction::SOUTHEAST.turn_right_45(), Direction::SOUTH);
+        assert_eq!(Direction::WEST.turn(Turn::Right), Direction::NORTH);
+        for &d in Direction::iterator() {
+            assert_eq!(d.turn_left().turn_right(), d);
+            assert_eq!(d.opposite().opposite(), d);
+            assert_eq!(d.turn_right().turn_right(), d.opposite());
+            assert_eq!(d.turn_right_45().is_cardinal(), d.is_diagonal());
+        }
+    }
+
+    #[test]
+    fn test_offsets() {
+        for &d in Direction::iterator() {
+            let (dx, dy) = d.offset();
+            let (ox, oy) = d.opposite().offset();
+            assert_eq!((dx + ox, dy + oy), (0, 0));
+            assert_eq!(Direction::from_offset(dx, dy), Some(d));
+        }
+        assert_eq!(Direction::from_offset(0, 0), None);
+        assert_eq!(Direction::from_offset(2, 0), None);
+    }
+
+    #[test]
+    fn test_parse_directions() {
+        let arrows: Vec<Direction> = "^>v<".chars().filter_map(Direction::from_char).collect();
+        let udlr: Vec<Direction> = "URDL".chars().filter_map(Direction::from_char).collect();
+        let nesw: Vec<Direction> = "NESW".chars().filter_map(Direction::from_char).collect();
+        let expected: Vec<Direction> = Direction::cardinal_iterator().copied().collect();
+        assert_eq!(arrows, expected);
+        assert_eq!(udlr, expected);
+        assert_eq!(nesw, expected);
+        assert_eq!(Direction::from_char('x'), None);
+        let back: String = expected.iter().filter_map(|d| d.to_arrow()).collect();
+        assert_eq!(back, "^>v<");
+    }
+
+    #[test]
+    fn test_parse_turns() {
+        assert_eq!(Turn::from_char('L'), Some(Turn::Left));
+        assert_eq!(Turn::parse_instruction(" R12"), Some((Turn::Right, 12)));
+        assert_eq!(Turn::parse_instruction("L"), None);
+        assert_eq!(Turn::parse_instruction("X3"), None);
+    }
 }
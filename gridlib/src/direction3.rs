@@ -0,0 +1,211 @@
+use crate::direction::Direction;
+
+use std::fmt::{Display, Formatter};
+use std::slice::Iter;
+
+/// Directions between cells in 3D. The planar names match `Direction`,
+/// UP is +z and DOWN is -z.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Direction3 {
+    NORTH,
+    NORTHEAST,
+    EAST,
+    SOUTHEAST,
+    SOUTH,
+    SOUTHWEST,
+    WEST,
+    NORTHWEST,
+    UP,
+    UPNORTH,
+    UPNORTHEAST,
+    UPEAST,
+    UPSOUTHEAST,
+    UPSOUTH,
+    UPSOUTHWEST,
+    UPWEST,
+    UPNORTHWEST,
+    DOWN,
+    DOWNNORTH,
+    DOWNNORTHEAST,
+    DOWNEAST,
+    DOWNSOUTHEAST,
+    DOWNSOUTH,
+    DOWNSOUTHWEST,
+    DOWNWEST,
+    DOWNNORTHWEST,
+}
+
+impl Display for Direction3 {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            Direction3::NORTH => "NORTH",
+            Direction3::NORTHEAST => "NORTHEAST",
+            Direction3::EAST => "EAST",
+            Direction3::SOUTHEAST => "SOUTHEAST",
+            Direction3::SOUTH => "SOUTH",
+            Direction3::SOUTHWEST => "SOUTHWEST",
+            Direction3::WEST => "WEST",
+            Direction3::NORTHWEST => "NORTHWEST",
+            Direction3::UP => "UP",
+            Direction3::UPNORTH => "UPNORTH",
+            Direction3::UPNORTHEAST => "UPNORTHEAST",
+            Direction3::UPEAST => "UPEAST",
+            Direction3::UPSOUTHEAST => "UPSOUTHEAST",
+            Direction3::UPSOUTH => "UPSOUTH",
+            Direction3::UPSOUTHWEST => "UPSOUTHWEST",
+            Direction3::UPWEST => "UPWEST",
+            Direction3::UPNORTHWEST => "UPNORTHWEST",
+            Direction3::DOWN => "DOWN",
+            Direction3::DOWNNORTH => "DOWNNORTH",
+            Direction3::DOWNNORTHEAST => "DOWNNORTHEAST",
+            Direction3::DOWNEAST => "DOWNEAST",
+            Direction3::DOWNSOUTHEAST => "DOWNSOUTHEAST",
+            Direction3::DOWNSOUTH => "DOWNSOUTH",
+            Direction3::DOWNSOUTHWEST => "DOWNSOUTHWEST",
+            Direction3::DOWNWEST => "DOWNWEST",
+            Direction3::DOWNNORTHWEST => "DOWNNORTHWEST",
+        };
+        return write!(f, "{}", s);
+    }
+}
+
+impl From<Direction> for Direction3 {
+    fn from(direction: Direction) -> Direction3 {
+        return match direction {
+            Direction::NORTH => Direction3::NORTH,
+            Direction::NORTHEAST => Direction3::NORTHEAST,
+            Direction::EAST => Direction3::EAST,
+            Direction::SOUTHEAST => Direction3::SOUTHEAST,
+            Direction::SOUTH => Direction3::SOUTH,
+            Direction::SOUTHWEST => Direction3::SOUTHWEST,
+            Direction::WEST => Direction3::WEST,
+            Direction::NORTHWEST => Direction3::NORTHWEST,
+        };
+    }
+}
+
+impl Direction3 {
+    /// (dx, dy, dz) of a single step.
+    pub fn offset(&self) -> (i32, i32, i32) {
+        return match self {
+            Direction3::NORTH => (0, -1, 0),
+            Direction3::NORTHEAST => (1, -1, 0),
+            Direction3::EAST => (1, 0, 0),
+            Direction3::SOUTHEAST => (1, 1, 0),
+            Direction3::SOUTH => (0, 1, 0),
+            Direction3::SOUTHWEST => (-1, 1, 0),
+            Direction3::WEST => (-1, 0, 0),
+            Direction3::NORTHWEST => (-1, -1, 0),
+            Direction3::UP => (0, 0, 1),
+            Direction3::UPNORTH => (0, -1, 1),
+            Direction3::UPNORTHEAST => (1, -1, 1),
+            Direction3::UPEAST => (1, 0, 1),
+            Direction3::UPSOUTHEAST => (1, 1, 1),
+            Direction3::UPSOUTH => (0, 1, 1),
+            Direction3::UPSOUTHWEST => (-1, 1, 1),
+            Direction3::UPWEST => (-1, 0, 1),
+            Direction3::UPNORTHWEST => (-1, -1, 1),
+            Direction3::DOWN => (0, 0, -1),
+            Direction3::DOWNNORTH => (0, -1, -1),
+            Direction3::DOWNNORTHEAST => (1, -1, -1),
+            Direction3::DOWNEAST => (1, 0, -1),
+            Direction3::DOWNSOUTHEAST => (1, 1, -1),
+            Direction3::DOWNSOUTH => (0, 1, -1),
+            Direction3::DOWNSOUTHWEST => (-1, 1, -1),
+            Direction3::DOWNWEST => (-1, 0, -1),
+            Direction3::DOWNNORTHWEST => (-1, -1, -1),
+        };
+    }
+
+    pub fn from_offset(dx: i32, dy: i32, dz: i32) -> Option<Direction3> {
+        return Direction3::iterator()
+            .find(|d| d.offset() == (dx, dy, dz))
+            .copied();
+    }
+
+    pub fn opposite(&self) -> Direction3 {
+        let (dx, dy, dz) = self.offset();
+        return Direction3::from_offset(-dx, -dy, -dz).unwrap();
+    }
+
+    /// The 6 directions sharing a face with the cell.
+    pub fn face_iterator() -> Iter<'static, Direction3> {
+        static DIRECTIONS: [Direction3; 6] = [
+            Direction3::NORTH,
+            Direction3::EAST,
+            Direction3::SOUTH,
+            Direction3::WEST,
+            Direction3::UP,
+            Direction3::DOWN,
+        ];
+        return DIRECTIONS.iter();
+    }
+
+    /// All 26 directions sharing a face, edge or corner with the cell.
+    pub fn iterator() -> Iter<'static, Direction3> {
+        static DIRECTIONS: [Direction3; 26] = [
+            Direction3::NORTH,
+            Direction3::NORTHEAST,
+            Direction3::EAST,
+            Direction3::SOUTHEAST,
+            Direction3::SOUTH,
+            Direction3::SOUTHWEST,
+            Direction3::WEST,
+            Direction3::NORTHWEST,
+            Direction3::UP,
+            Direction3::UPNORTH,
+            Direction3::UPNORTHEAST,
+            Direction3::UPEAST,
+            Direction3::UPSOUTHEAST,
+            Direction3::UPSOUTH,
+            Direction3::UPSOUTHWEST,
+            Direction3::UPWEST,
+            Direction3::UPNORTHWEST,
+            Direction3::DOWN,
+            Direction3::DOWNNORTH,
+            Direction3::DOWNNORTHEAST,
+            Direction3::DOWNEAST,
+            Direction3::DOWNSOUTHEAST,
+            Direction3::DOWNSOUTH,
+            Direction3::DOWNSOUTHWEST,
+            Direction3::DOWNWEST,
+            Direction3::DOWNNORTHWEST,
+        ];
+        return DIRECTIONS.iter();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_offsets_unique() {
+        let offsets: HashSet<(i32, i32, i32)> =
+            Direction3::iterator().map(|d| d.offset()).collect();
+        assert_eq!(offsets.len(), 26);
+        assert!(!offsets.contains(&(0, 0, 0)));
+        for d in Direction3::face_iterator() {
+            let (dx, dy, dz) = d.offset();
+            assert_eq!(dx.abs() + dy.abs() + dz.abs(), 1);
+        }
+    }
+
+    #[test]
+    fn test_opposite_and_from() {
+        assert_eq!(Direction3::UP.opposite(), Direction3::DOWN);
+        assert_eq!(
+            Direction3::UPNORTHEAST.opposite(),
+            Direction3::DOWNSOUTHWEST
+        );
+        assert_eq!(Direction3::from(Direction::WEST), Direction3::WEST);
+        assert_eq!(
+            Direction3::from_offset(1, 1, -1),
+            Some(Direction3::DOWNSOUTHEAST)
+        );
+        assert_eq!(Direction3::from_offset(2, 0, 0), None);
+        assert_eq!(format!("{}", Direction3::DOWNNORTH), "DOWNNORTH");
+    }
+}
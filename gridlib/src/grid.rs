@@ -1,9 +1,18 @@
 use crate::direction::Direction;
 use crate::gridcoord::GridCoordinate;
+use crate::render::Color;
 
 use std::clone::Clone;
 
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(
+        try_from = "GridData<T>",
+        bound(deserialize = "T: serde::Deserialize<'de>")
+    )
+)]
 pub struct Grid<T: Copy> {
     /* Variable sized Grid.
      *
@@ -44,26 +53,46 @@ impl<T: Copy> Grid<T> {
         };
     }
 
+    // A whole row at once, for bulk copies that would be slow cell by cell.
+    pub(crate) fn row(&self, y: usize) -> &[T] {
+        return &self.values[y * self.width..(y + 1) * self.width];
+    }
+
+    pub(crate) fn row_mut(&mut self, y: usize) -> &mut [T] {
+        return &mut self.values[y * self.width..(y + 1) * self.width];
+    }
+
     pub fn data_copy(&self) -> Vec<T>
     where
         T: Clone,
     {
         return self.values.clone();
     }
+}
 
-    fn coord_direction_iterator(
-        &self,
-        pos: GridCoordinate,
-        direction_iter: std::slice::Iter<Direction>,
-    ) -> Vec<(GridCoordinate, Direction)> {
-        let mut result: Vec<(GridCoordinate, Direction)> = Vec::new();
-        for &direction in direction_iter {
-            let coord = self.get_coordinate_by_direction(pos, direction);
-            if let Some(cur_pos) = coord {
-                result.push((cur_pos, direction));
-            }
+// What a serialized grid looks like, checked before it becomes a Grid.
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct GridData<T> {
+    width: usize,
+    height: usize,
+    values: Vec<T>,
+}
+
+#[cfg(feature = "serde")]
+impl<T: Copy> TryFrom<GridData<T>> for Grid<T> {
+    type Error = String;
+
+    fn try_from(data: GridData<T>) -> Result<Self, Self::Error> {
+        if data.width.checked_mul(data.height) != Some(data.values.len()) {
+            return Err(format!(
+                "{} values can't fill a {}x{} grid",
+                data.values.len(),
+                data.width,
+                data.height
+            ));
         }
-        return result;
+        return Ok(Grid::new(data.width, data.height, data.values));
     }
 }
 
@@ -119,21 +148,105 @@ pub trait GridTraversable {
         pos: GridCoordinate,
         direction: Direction,
     ) -> Option<GridCoordinate>;
-    fn get_adjacent_coordinates(&self, pos: GridCoordinate) -> Vec<GridCoordinate>;
+
+    fn get_adjacent_coordinates(&self, pos: GridCoordinate) -> Vec<GridCoordinate> {
+        return coord_direction_iterator(self, pos, Direction::cardinal_iterator())
+            .into_iter()
+            .map(|x| x.0)
+            .collect();
+    }
+
     fn get_adjacent_coordinates_and_direction(
         &self,
         pos: GridCoordinate,
-    ) -> Vec<(GridCoordinate, Direction)>;
-    fn get_diag_adjacent_coordinates(&self, pos: GridCoordinate) -> Vec<GridCoordinate>;
+    ) -> Vec<(GridCoordinate, Direction)> {
+        return coord_direction_iterator(self, pos, Direction::cardinal_iterator());
+    }
+
+    fn get_diag_adjacent_coordinates(&self, pos: GridCoordinate) -> Vec<GridCoordinate> {
+        return coord_direction_iterator(self, pos, Direction::diagonal_iterator())
+            .into_iter()
+            .map(|x| x.0)
+            .collect();
+    }
+
     fn get_diag_adjacent_coordinates_and_direction(
         &self,
         pos: GridCoordinate,
-    ) -> Vec<(GridCoordinate, Direction)>;
-    fn get_all_adjacent_coordinates(&self, pos: GridCoordinate) -> Vec<GridCoordinate>;
+    ) -> Vec<(GridCoordinate, Direction)> {
+        return coord_direction_iterator(self, pos, Direction::diagonal_iterator());
+    }
+
+    fn get_all_adjacent_coordinates(&self, pos: GridCoordinate) -> Vec<GridCoordinate> {
+        return coord_direction_iterator(self, pos, Direction::iterator())
+            .into_iter()
+            .map(|x| x.0)
+            .collect();
+    }
+
     fn get_all_adjacent_coordinates_and_direction(
         &self,
         pos: GridCoordinate,
-    ) -> Vec<(GridCoordinate, Direction)>;
+    ) -> Vec<(GridCoordinate, Direction)> {
+        return coord_direction_iterator(self, pos, Direction::iterator());
+    }
+}
+
+fn coord_direction_iterator<G: GridTraversable + ?Sized>(
+    grid: &G,
+    pos: GridCoordinate,
+    direction_iter: std::slice::Iter<Direction>,
+) -> Vec<(GridCoordinate, Direction)> {
+    let mut result: Vec<(GridCoordinate, Direction)> = Vec::new();
+    for &direction in direction_iter {
+        let coord = grid.get_coordinate_by_direction(pos, direction);
+        if let Some(cur_pos) = coord {
+            result.push((cur_pos, direction));
+        }
+    }
+    return result;
+}
+
+/// Step from pos in direction, staying inside a width * height grid.
+pub(crate) fn bounded_coordinate_by_direction(
+    width: usize,
+    height: usize,
+    pos: GridCoordinate,
+    direction: Direction,
+) -> Option<GridCoordinate> {
+    let mut possible_y: Option<usize> = Some(pos.y);
+    let mut possible_x: Option<usize> = Some(pos.x);
+    match direction {
+        Direction::NORTH => possible_y = pos.y.checked_sub(1),
+        Direction::EAST => possible_x = pos.x.checked_add(1),
+        Direction::SOUTH => possible_y = pos.y.checked_add(1),
+        Direction::WEST => possible_x = pos.x.checked_sub(1),
+        Direction::NORTHEAST => {
+            possible_x = pos.x.checked_add(1);
+            possible_y = pos.y.checked_sub(1);
+        }
+        Direction::SOUTHEAST => {
+            possible_x = pos.x.checked_add(1);
+            possible_y = pos.y.checked_add(1);
+        }
+        Direction::SOUTHWEST => {
+            possible_x = pos.x.checked_sub(1);
+            possible_y = pos.y.checked_add(1);
+        }
+        Direction::NORTHWEST => {
+            possible_x = pos.x.checked_sub(1);
+            possible_y = pos.y.checked_sub(1);
+        }
+    }
+    if let Some(new_x) = possible_x {
+        if let Some(new_y) = possible_y {
+            if new_x >= width || new_y >= height {
+                return None;
+            }
+            return Some(GridCoordinate::new(new_x, new_y));
+        }
+    }
+    return None;
 }
 
 impl<T: Copy> GridTraversable for Grid<T> {
@@ -162,84 +275,7 @@ impl<T: Copy> GridTraversable for Grid<T> {
         pos: GridCoordinate,
         direction: Direction,
     ) -> Option<GridCoordinate> {
-        let mut possible_y: Option<usize> = Some(pos.y);
-        let mut possible_x: Option<usize> = Some(pos.x);
-        match direction {
-            Direction::NORTH => possible_y = pos.y.checked_sub(1),
-            Direction::EAST => possible_x = pos.x.checked_add(1),
-            Direction::SOUTH => possible_y = pos.y.checked_add(1),
-            Direction::WEST => possible_x = pos.x.checked_sub(1),
-            Direction::NORTHEAST => {
-                possible_x = pos.x.checked_add(1);
-                possible_y = pos.y.checked_sub(1);
-            }
-            Direction::SOUTHEAST => {
-                possible_x = pos.x.checked_add(1);
-                possible_y = pos.y.checked_add(1);
-            }
-            Direction::SOUTHWEST => {
-                possible_x = pos.x.checked_sub(1);
-                possible_y = pos.y.checked_add(1);
-            }
-            Direction::NORTHWEST => {
-                possible_x = pos.x.checked_sub(1);
-                possible_y = pos.y.checked_sub(1);
-            }
-        }
-        if let Some(new_x) = possible_x {
-            if let Some(new_y) = possible_y {
-                if new_x > self.width - 1 || new_y > self.height - 1 {
-                    return None;
-                }
-                return Some(GridCoordinate::new(new_x, new_y));
-            }
-        }
-        return None;
-    }
-
-    fn get_adjacent_coordinates(&self, pos: GridCoordinate) -> Vec<GridCoordinate> {
-        return self
-            .coord_direction_iterator(pos, Direction::cardinal_iterator())
-            .into_iter()
-            .map(|x| x.0)
-            .collect();
-    }
-
-    fn get_adjacent_coordinates_and_direction(
-        &self,
-        pos: GridCoordinate,
-    ) -> Vec<(GridCoordinate, Direction)> {
-        return self.coord_direction_iterator(pos, Direction::cardinal_iterator());
-    }
-
-    fn get_diag_adjacent_coordinates(&self, pos: GridCoordinate) -> Vec<GridCoordinate> {
-        return self
-            .coord_direction_iterator(pos, Direction::diagonal_iterator())
-            .into_iter()
-            .map(|x| x.0)
-            .collect();
-    }
-
-    fn get_diag_adjacent_coordinates_and_direction(
-        &self,
-        pos: GridCoordinate,
-    ) -> Vec<(GridCoordinate, Direction)> {
-        return self.coord_direction_iterator(pos, Direction::diagonal_iterator());
-    }
-
-    fn get_all_adjacent_coordinates(&self, pos: GridCoordinate) -> Vec<GridCoordinate> {
-        return self
-            .coord_direction_iterator(pos, Direction::iterator())
-            .into_iter()
-            .map(|x| x.0)
-            .collect();
-    }
-
-    fn get_all_adjacent_coordinates_and_direction(
-        &self,
-        pos: GridCoordinate,
-    ) -> Vec<(GridCoordinate, Direction)> {
-        return self.coord_direction_iterator(pos, Direction::iterator());
+        return bounded_coordinate_by_direction(self.width, self.height, pos, direction);
     }
 }
 
@@ -271,6 +307,15 @@ impl<T: Copy> GridRotation for Grid<T> {
 
 pub trait GridPrintable {
     fn get_character(&self) -> char;
+
+    // Only used by the terminal renderer, None leaves the terminal's own colour.
+    fn get_foreground(&self) -> Option<Color> {
+        return None;
+    }
+
+    fn get_background(&self) -> Option<Color> {
+        return None;
+    }
 }
 
 impl GridPrintable for char {
@@ -279,6 +324,92 @@ impl GridPrintable for char {
     }
 }
 
+/// The inverse of `GridPrintable`, None for characters that aren't a valid cell.
+pub trait GridParsable: Sized {
+    fn from_character(c: char) -> Option<Self>;
+}
+
+impl GridParsable for char {
+    fn from_character(c: char) -> Option<Self> {
+        return Some(c);
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GridParseError {
+    Empty,
+    // Line y doesn't have the width of the first line.
+    RaggedLine {
+        y: usize,
+        expected: usize,
+        found: usize,
+    },
+    UnknownCharacter {
+        c: char,
+        position: GridCoordinate,
+    },
+}
+
+impl std::fmt::Display for GridParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        return match self {
+            GridParseError::Empty => write!(f, "No lines to parse"),
+            GridParseError::RaggedLine { y, expected, found } => {
+                write!(f, "Line {} has width {}, expected {}", y, found, expected)
+            }
+            GridParseError::UnknownCharacter { c, position } => {
+                write!(f, "Unknown character {} at {}", c, position)
+            }
+        };
+    }
+}
+
+impl std::error::Error for GridParseError {}
+
+impl<T: Copy + GridParsable> Grid<T> {
+    /// One line per row, every line the same width.
+    pub fn from_strings<S: AsRef<str>>(lines: &[S]) -> Result<Grid<T>, GridParseError> {
+        if lines.is_empty() {
+            return Err(GridParseError::Empty);
+        }
+        let width = lines[0].as_ref().chars().count();
+        let mut values = Vec::with_capacity(width * lines.len());
+        for (y, line) in lines.iter().enumerate() {
+            let mut found = 0;
+            for (x, c) in line.as_ref().chars().enumerate() {
+                match T::from_character(c) {
+                    Some(v) => values.push(v),
+                    None => {
+                        return Err(GridParseError::UnknownCharacter {
+                            c: c,
+                            position: GridCoordinate::new(x, y),
+                        })
+                    }
+                }
+                found += 1;
+            }
+            if found != width {
+                return Err(GridParseError::RaggedLine {
+                    y: y,
+                    expected: width,
+                    found: found,
+                });
+            }
+        }
+        return Ok(Grid::new(width, lines.len(), values));
+    }
+
+    /// Same as `from_strings`, on newline separated text. Blank lines are skipped.
+    pub fn parse(text: &str) -> Result<Grid<T>, GridParseError> {
+        let lines: Vec<&str> = text
+            .lines()
+            .map(|l| l.trim_end_matches('\r'))
+            .filter(|l| !l.is_empty())
+            .collect();
+        return Grid::from_strings(&lines);
+    }
+}
+
 pub trait GridOverlay: GridPrintable {
     fn get_position(&self) -> GridCoordinate;
 }
@@ -287,6 +418,8 @@ pub trait GridOverlay: GridPrintable {
 pub struct SimpleGridOverlay {
     character: char,
     position: GridCoordinate,
+    foreground: Option<Color>,
+    background: Option<Color>,
 }
 
 impl GridOverlay for SimpleGridOverlay {
@@ -299,6 +432,14 @@ impl GridPrintable for SimpleGridOverlay {
     fn get_character(&self) -> char {
         return self.character;
     }
+
+    fn get_foreground(&self) -> Option<Color> {
+        return self.foreground;
+    }
+
+    fn get_background(&self) -> Option<Color> {
+        return self.background;
+    }
 }
 
 impl SimpleGridOverlay {
@@ -306,6 +447,20 @@ impl SimpleGridOverlay {
         return SimpleGridOverlay {
             character: c,
             position: pos,
+            foreground: None,
+            background: None,
+        };
+    }
+
+    pub fn with_colors(
+        self,
+        foreground: Option<Color>,
+        background: Option<Color>,
+    ) -> SimpleGridOverlay {
+        return SimpleGridOverlay {
+            foreground: foreground,
+            background: background,
+            ..self
         };
     }
 }
@@ -348,6 +503,10 @@ impl<T: Copy + GridPrintable> Grid<T> {
 
         for v in overlay {
             let coord = v.get_position();
+            if coord.x >= width || coord.y >= self.get_height() {
+                // Off the grid, TerminalRenderer reports these instead.
+                continue;
+            }
             let index = coord.x + coord.y * width;
             values[index] = v.get_character();
         }
@@ -518,6 +677,21 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_traversable_as_dyn() {
+        // Works behind a trait object, so different grid types can share a Vec or a Box
+        let mut grid = produce_grid();
+        let dyn_grid: &mut dyn GridTraversable<Item = i32> = &mut grid;
+        dyn_grid.set_value(GridCoordinate::new(1, 1), 42);
+        assert_eq!(dyn_grid.get_value(GridCoordinate::new(1, 1)), Some(42));
+        assert_eq!(
+            dyn_grid
+                .get_adjacent_coordinates(GridCoordinate::new(0, 0))
+                .len(),
+            2
+        );
+    }
+
     #[test]
     fn test_get_width() {
         let grid = produce_grid();
@@ -695,4 +869,104 @@ mod tests {
         let strings = grid.grid_strings_with_overlay(overlay).join("\n");
         assert_eq!(strings, "+---+\n@@..|\n|@@.|\n|...|\n+--@+");
     }
+
+    #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+    enum Cell {
+        Open,
+        Wall,
+    }
+
+    impl GridPrintable for Cell {
+        fn get_character(&self) -> char {
+            return match self {
+                Cell::Open => '.',
+                Cell::Wall => '#',
+            };
+        }
+    }
+
+    impl GridParsable for Cell {
+        fn from_character(c: char) -> Option<Self> {
+            return match c {
+                '.' => Some(Cell::Open),
+                '#' => Some(Cell::Wall),
+                _ => None,
+            };
+        }
+    }
+
+    #[test]
+    fn test_parse_round_trip() {
+        let lines = vec!["#..#", ".##.", "...."];
+        let grid: Grid<Cell> = Grid::from_strings(&lines).unwrap();
+        assert_eq!(grid.get_width(), 4);
+        assert_eq!(grid.get_height(), 3);
+        assert_eq!(grid.get_value(GridCoordinate::new(1, 1)), Some(Cell::Wall));
+        assert_eq!(grid.grid_strings(), lines);
+
+        let parsed: Grid<Cell> = Grid::parse("#..#\r\n.##.\r\n....\r\n").unwrap();
+        assert_eq!(parsed, grid);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            Grid::<Cell>::from_strings(&Vec::<String>::new()),
+            Err(GridParseError::Empty)
+        );
+        assert_eq!(
+            Grid::<Cell>::parse("..\n...\n"),
+            Err(GridParseError::RaggedLine {
+                y: 1,
+                expected: 2,
+                found: 3
+            })
+        );
+        assert_eq!(
+            Grid::<Cell>::parse("..\n.x\n"),
+            Err(GridParseError::UnknownCharacter {
+                c: 'x',
+                position: GridCoordinate::new(1, 1)
+            })
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip() {
+        let grid = Grid::new(3, 2, vec!['a', 'b', 'c', 'd', 'e', 'f']);
+        let json = serde_json::to_string(&grid).unwrap();
+        assert_eq!(
+            json,
+            r#"{"width":3,"height":2,"values":["a","b","c","d","e","f"]}"#
+        );
+        let back: Grid<char> = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, grid);
+
+        let bad = r#"{"width":3,"height":3,"values":["a","b","c"]}"#;
+        assert!(serde_json::from_str::<Grid<char>>(bad).is_err());
+        // width * height wraps to 0, which would match the empty values
+        let huge = format!(
+            r#"{{"width":{},"height":2,"values":[]}}"#,
+            usize::MAX / 2 + 1
+        );
+        assert!(serde_json::from_str::<Grid<char>>(&huge).is_err());
+
+        let pos: GridCoordinate = serde_json::from_str(r#"{"x":4,"y":7}"#).unwrap();
+        assert_eq!(pos, GridCoordinate::new(4, 7));
+        let direction: Direction = serde_json::from_str(r#""NORTHEAST""#).unwrap();
+        assert_eq!(direction, Direction::NORTHEAST);
+    }
+
+    #[test]
+    fn test_print_grid_overlay_out_of_bounds() {
+        let grid = Grid::new(3, 2, vec!['.'; 6]);
+        let overlay = vec![
+            SimpleGridOverlay::new('@', GridCoordinate::new(3, 0)),
+            SimpleGridOverlay::new('@', GridCoordinate::new(0, 2)),
+            SimpleGridOverlay::new('@', GridCoordinate::new(1, 1)),
+        ];
+        let strings = grid.grid_strings_with_overlay(overlay);
+        assert_eq!(strings, vec!["...", ".@."]);
+    }
 }
@@ -0,0 +1,301 @@
+use crate::direction3::Direction3;
+use crate::gridcoord3::GridCoordinate3;
+use crate::search::bfs_by;
+
+/// Dense 3D grid, a stack of `depth` layers of width * height cells.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(
+        try_from = "Grid3Data<T>",
+        bound(deserialize = "T: serde::Deserialize<'de>")
+    )
+)]
+pub struct Grid3<T: Copy> {
+    /* index by: x + (y * width) + (z * width * height)
+     * 0,0,0 is a corner, every axis increases away from it.
+     */
+    width: usize,
+    height: usize,
+    depth: usize,
+    values: Vec<T>,
+}
+
+// What a serialized grid looks like, checked before it becomes a Grid3.
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct Grid3Data<T> {
+    width: usize,
+    height: usize,
+    depth: usize,
+    values: Vec<T>,
+}
+
+#[cfg(feature = "serde")]
+impl<T: Copy> TryFrom<Grid3Data<T>> for Grid3<T> {
+    type Error = String;
+
+    fn try_from(data: Grid3Data<T>) -> Result<Self, Self::Error> {
+        let size = data
+            .width
+            .checked_mul(data.height)
+            .and_then(|area| area.checked_mul(data.depth));
+        if size != Some(data.values.len()) {
+            return Err(format!(
+                "{} values can't fill a {}x{}x{} grid",
+                data.values.len(),
+                data.width,
+                data.height,
+                data.depth
+            ));
+        }
+        return Ok(Grid3::new(data.width, data.height, data.depth, data.values));
+    }
+}
+
+impl<T: Copy> Grid3<T> {
+    pub fn new(width: usize, height: usize, depth: usize, values: Vec<T>) -> Grid3<T> {
+        assert_eq!(width * height * depth, values.len());
+        return Grid3 {
+            width: width,
+            height: height,
+            depth: depth,
+            values: values,
+        };
+    }
+
+    pub fn get_width(&self) -> usize {
+        return self.width;
+    }
+
+    pub fn get_height(&self) -> usize {
+        return self.height;
+    }
+
+    pub fn get_depth(&self) -> usize {
+        return self.depth;
+    }
+
+    fn index(&self, pos: GridCoordinate3) -> Option<usize> {
+        if pos.x >= self.width || pos.y >= self.height || pos.z >= self.depth {
+            return None;
+        }
+        return Some(pos.x + pos.y * self.width + pos.z * self.width * self.height);
+    }
+
+    /// Every coordinate, in the order the values are stored.
+    pub fn coord_iter(&self) -> impl Iterator<Item = GridCoordinate3> {
+        let (width, height, depth) = (self.width, self.height, self.depth);
+        return (0..depth).flat_map(move |z| {
+            (0..height).flat_map(move |y| (0..width).map(move |x| GridCoordinate3::new(x, y, z)))
+        });
+    }
+
+    pub fn get_value(&self, pos: GridCoordinate3) -> Option<T> {
+        return self.index(pos).map(|i| self.values[i]);
+    }
+
+    /// Like `Grid`, writes outside the grid are ignored.
+    pub fn set_value(&mut self, pos: GridCoordinate3, value: T) {
+        if let Some(index) = self.index(pos) {
+            self.values[index] = value;
+        }
+    }
+
+    pub fn get_coordinate_by_direction(
+        &self,
+        pos: GridCoordinate3,
+        direction: Direction3,
+    ) -> Option<GridCoordinate3> {
+        let (dx, dy, dz) = direction.offset();
+        let step = |v: usize, d: i32, max: usize| {
+            v.checked_add_signed(d as isize).filter(|&next| next < max)
+        };
+        return Some(GridCoordinate3::new(
+            step(pos.x, dx, self.width)?,
+            step(pos.y, dy, self.height)?,
+            step(pos.z, dz, self.depth)?,
+        ));
+    }
+
+    /// In bounds neighbours sharing a face with pos.
+    pub fn get_face_adjacent_coordinates(&self, pos: GridCoordinate3) -> Vec<GridCoordinate3> {
+        return Direction3::face_iterator()
+            .filter_map(|&d| self.get_coordinate_by_direction(pos, d))
+            .collect();
+    }
+
+    /// In bounds neighbours sharing a face, edge or corner with pos.
+    pub fn get_all_adjacent_coordinates(&self, pos: GridCoordinate3) -> Vec<GridCoordinate3> {
+        return Direction3::iterator()
+            .filter_map(|&d| self.get_coordinate_by_direction(pos, d))
+            .collect();
+    }
+
+    /// Every cell reachable from start through faces, stepping only where
+    /// `connected(current_value, next_value)` holds. Sorted.
+    pub fn flood_fill<F: FnMut(T, T) -> bool>(
+        &self,
+        start: GridCoordinate3,
+        mut connected: F,
+    ) -> Vec<GridCoordinate3> {
+        if self.get_value(start).is_none() {
+            return vec![];
+        }
+        let found = bfs_by(vec![start], |cur| {
+            let value = self.get_value(cur).unwrap();
+            self.get_face_adjacent_coordinates(cur)
+                .into_iter()
+                .filter(|&next| connected(value, self.get_value(next).unwrap()))
+                .collect()
+        });
+        let mut cells: Vec<GridCoordinate3> = found.reached().map(|(pos, _)| pos).collect();
+        cells.sort();
+        return cells;
+    }
+
+    /// Faces of solid cells that touch a non solid cell or the edge of the grid.
+    pub fn surface_area<F: Fn(T) -> bool>(&self, solid: F) -> usize {
+        let mut area = 0;
+        for pos in self.coord_iter() {
+            if !solid(self.get_value(pos).unwrap()) {
+                continue;
+            }
+            for &d in Direction3::face_iterator() {
+                match self.get_coordinate_by_direction(pos, d) {
+                    Some(next) if solid(self.get_value(next).unwrap()) => {}
+                    _ => area += 1,
+                }
+            }
+        }
+        return area;
+    }
+
+    /// Like `surface_area`, but faces of enclosed air pockets don't count.
+    pub fn exterior_surface_area<F: Fn(T) -> bool>(&self, solid: F) -> usize {
+        // Flood the air from a one cell border around the grid, shifted by 1 so it fits in usize.
+        let outside = |pos: GridCoordinate3| {
+            pos.x == 0
+                || pos.y == 0
+                || pos.z == 0
+                || pos.x > self.width
+                || pos.y > self.height
+                || pos.z > self.depth
+        };
+        let is_solid = |pos: GridCoordinate3| {
+            !outside(pos)
+                && solid(
+                    self.get_value(GridCoordinate3::new(pos.x - 1, pos.y - 1, pos.z - 1))
+                        .unwrap(),
+                )
+        };
+        let padded = Grid3::new(
+            self.width + 2,
+            self.height + 2,
+            self.depth + 2,
+            vec![(); (self.width + 2) * (self.height + 2) * (self.depth + 2)],
+        );
+
+        let mut area = 0;
+        bfs_by(vec![GridCoordinate3::new(0, 0, 0)], |cur| {
+            let mut air = vec![];
+            for next in padded.get_face_adjacent_coordinates(cur) {
+                if is_solid(next) {
+                    area += 1;
+                } else {
+                    air.push(next);
+                }
+            }
+            air
+        });
+        return area;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Two cubes sharing a face, inside a 3x1x1 grid.
+    fn produce_pair() -> Grid3<bool> {
+        return Grid3::new(3, 1, 1, vec![true, true, false]);
+    }
+
+    // A 3x3x3 cube with the center missing.
+    fn produce_hollow() -> Grid3<bool> {
+        let mut values = vec![true; 27];
+        values[13] = false;
+        return Grid3::new(3, 3, 3, values);
+    }
+
+    #[test]
+    fn test_get_set() {
+        let mut grid = Grid3::new(2, 3, 4, vec![0; 24]);
+        let pos = GridCoordinate3::new(1, 2, 3);
+        grid.set_value(pos, 7);
+        assert_eq!(grid.get_value(pos), Some(7));
+        assert_eq!(grid.get_value(GridCoordinate3::new(2, 0, 0)), None);
+        // Off the grid is ignored, as with Grid
+        grid.set_value(GridCoordinate3::new(0, 0, 4), 9);
+        assert!(grid.coord_iter().all(|c| grid.get_value(c) != Some(9)));
+        assert_eq!(grid.coord_iter().count(), 24);
+        assert_eq!(grid.coord_iter().last(), Some(pos));
+    }
+
+    #[test]
+    fn test_neighbors() {
+        let grid = Grid3::new(3, 3, 3, vec![0; 27]);
+        let center = GridCoordinate3::new(1, 1, 1);
+        let corner = GridCoordinate3::new(0, 0, 0);
+        assert_eq!(grid.get_face_adjacent_coordinates(center).len(), 6);
+        assert_eq!(grid.get_all_adjacent_coordinates(center).len(), 26);
+        assert_eq!(grid.get_face_adjacent_coordinates(corner).len(), 3);
+        assert_eq!(grid.get_all_adjacent_coordinates(corner).len(), 7);
+        assert_eq!(
+            grid.get_coordinate_by_direction(corner, Direction3::UPSOUTHEAST),
+            Some(GridCoordinate3::new(1, 1, 1))
+        );
+        assert_eq!(
+            grid.get_coordinate_by_direction(corner, Direction3::DOWN),
+            None
+        );
+    }
+
+    #[test]
+    fn test_flood_fill() {
+        let grid = produce_hollow();
+        let solid = grid.flood_fill(GridCoordinate3::new(0, 0, 0), |a, b| a == b);
+        assert_eq!(solid.len(), 26);
+        let air = grid.flood_fill(GridCoordinate3::new(1, 1, 1), |a, b| a == b);
+        assert_eq!(air, vec![GridCoordinate3::new(1, 1, 1)]);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip() {
+        let grid = produce_pair();
+        let json = serde_json::to_string(&grid).unwrap();
+        let back: Grid3<bool> = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, grid);
+
+        let bad = r#"{"width":3,"height":1,"depth":2,"values":[true,true,false]}"#;
+        assert!(serde_json::from_str::<Grid3<bool>>(bad).is_err());
+        // The product wraps to 0, which would match the empty values
+        let huge = format!(
+            r#"{{"width":2,"height":{},"depth":2,"values":[]}}"#,
+            usize::MAX / 4 + 1
+        );
+        assert!(serde_json::from_str::<Grid3<bool>>(&huge).is_err());
+    }
+
+    #[test]
+    fn test_surface_area() {
+        assert_eq!(produce_pair().surface_area(|v| v), 10);
+        assert_eq!(produce_pair().exterior_surface_area(|v| v), 10);
+
+        let hollow = produce_hollow();
+        assert_eq!(hollow.surface_area(|v| v), 54 + 6);
+        assert_eq!(hollow.exterior_surface_area(|v| v), 54);
+    }
+}
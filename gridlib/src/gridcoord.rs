@@ -1,10 +1,12 @@
 pub use crate::direction::Direction;
 
+use num_traits::Signed;
 use std::cmp::Ordering;
 use std::fmt::{Display, Formatter};
-use std::ops::Add;
+use std::ops::{Add, Mul, Neg, Sub};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GridCoordinate {
     pub x: usize,
     pub y: usize,
@@ -14,6 +16,30 @@ impl GridCoordinate {
     pub fn new(x: usize, y: usize) -> GridCoordinate {
         return GridCoordinate { x: x, y: y };
     }
+
+    /// Move by offset, None if that leaves a width * height grid.
+    pub fn checked_add_offset(
+        &self,
+        offset: GridOffset,
+        width: usize,
+        height: usize,
+    ) -> Option<GridCoordinate> {
+        let x = self.x as i64 + offset.dx;
+        let y = self.y as i64 + offset.dy;
+        if x < 0 || y < 0 || x >= width as i64 || y >= height as i64 {
+            return None;
+        }
+        return Some(GridCoordinate::new(x as usize, y as usize));
+    }
+
+    pub fn manhattan_distance(&self, other: &GridCoordinate) -> usize {
+        return self.x.abs_diff(other.x) + self.y.abs_diff(other.y);
+    }
+
+    /// Distance when diagonal steps are allowed.
+    pub fn chebyshev_distance(&self, other: &GridCoordinate) -> usize {
+        return self.x.abs_diff(other.x).max(self.y.abs_diff(other.y));
+    }
 }
 
 impl Display for GridCoordinate {
@@ -33,6 +59,26 @@ impl std::ops::Add for GridCoordinate {
     }
 }
 
+impl Sub for GridCoordinate {
+    type Output = GridOffset;
+
+    /// The offset that takes other to self.
+    fn sub(self, other: GridCoordinate) -> GridOffset {
+        return GridOffset::new(
+            self.x as i64 - other.x as i64,
+            self.y as i64 - other.y as i64,
+        );
+    }
+}
+
+impl Mul<usize> for GridCoordinate {
+    type Output = GridCoordinate;
+
+    fn mul(self, scale: usize) -> GridCoordinate {
+        return GridCoordinate::new(self.x * scale, self.y * scale);
+    }
+}
+
 impl Ord for GridCoordinate {
     fn cmp(&self, other: &Self) -> Ordering {
         return self.y.cmp(&other.y).then_with(|| self.x.cmp(&other.x));
@@ -45,13 +91,90 @@ impl PartialOrd for GridCoordinate {
     }
 }
 
+/// A signed step between two coordinates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GridOffset {
+    pub dx: i64,
+    pub dy: i64,
+}
+
+impl GridOffset {
+    pub fn new(dx: i64, dy: i64) -> GridOffset {
+        return GridOffset { dx: dx, dy: dy };
+    }
+
+    pub fn manhattan_length(&self) -> i64 {
+        return self.dx.abs() + self.dy.abs();
+    }
+
+    pub fn chebyshev_length(&self) -> i64 {
+        return self.dx.abs().max(self.dy.abs());
+    }
+}
+
+impl From<Direction> for GridOffset {
+    fn from(direction: Direction) -> GridOffset {
+        let (dx, dy) = direction.offset();
+        return GridOffset::new(dx as i64, dy as i64);
+    }
+}
+
+impl Display for GridOffset {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        return write!(f, "<{}, {}>", self.dx, self.dy);
+    }
+}
+
+impl Add for GridOffset {
+    type Output = GridOffset;
+
+    fn add(self, other: GridOffset) -> GridOffset {
+        return GridOffset::new(self.dx + other.dx, self.dy + other.dy);
+    }
+}
+
+impl Sub for GridOffset {
+    type Output = GridOffset;
+
+    fn sub(self, other: GridOffset) -> GridOffset {
+        return GridOffset::new(self.dx - other.dx, self.dy - other.dy);
+    }
+}
+
+impl Neg for GridOffset {
+    type Output = GridOffset;
+
+    fn neg(self) -> GridOffset {
+        return GridOffset::new(-self.dx, -self.dy);
+    }
+}
+
+impl Mul<i64> for GridOffset {
+    type Output = GridOffset;
+
+    fn mul(self, scale: i64) -> GridOffset {
+        return GridOffset::new(self.dx * scale, self.dy * scale);
+    }
+}
+
+// -1, 0 or 1 as a T, for turning direction offsets into steps.
+pub(crate) fn unit<T: Signed>(d: i32) -> T {
+    return match d.signum() {
+        1 => T::one(),
+        -1 => -T::one(),
+        _ => T::zero(),
+    };
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-pub struct GridCoordinateInf<T: Clone + Copy + Add<Output = T> + From<i32> + Ord> {
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GridCoordinateInf<T: Signed + Copy + Ord> {
     pub x: T,
     pub y: T,
 }
 
-impl<T: Clone + Copy + Add<Output = T> + From<i32> + Ord> std::ops::Add for GridCoordinateInf<T> {
+impl<T: Signed + Copy + Ord> Add for GridCoordinateInf<T> {
     type Output = GridCoordinateInf<T>;
 
     fn add(self, other: GridCoordinateInf<T>) -> GridCoordinateInf<T> {
@@ -62,68 +185,54 @@ impl<T: Clone + Copy + Add<Output = T> + From<i32> + Ord> std::ops::Add for Grid
     }
 }
 
-impl<T: Clone + Copy + Add<Output = T> + From<i32> + Ord> GridCoordinateInf<T> {
+impl<T: Signed + Copy + Ord> Sub for GridCoordinateInf<T> {
+    type Output = GridCoordinateInf<T>;
+
+    fn sub(self, other: GridCoordinateInf<T>) -> GridCoordinateInf<T> {
+        return GridCoordinateInf::new(self.x - other.x, self.y - other.y);
+    }
+}
+
+impl<T: Signed + Copy + Ord> Mul<T> for GridCoordinateInf<T> {
+    type Output = GridCoordinateInf<T>;
+
+    fn mul(self, scale: T) -> GridCoordinateInf<T> {
+        return GridCoordinateInf::new(self.x * scale, self.y * scale);
+    }
+}
+
+impl<T: Signed + Copy + Ord> GridCoordinateInf<T> {
     pub fn new(x: T, y: T) -> GridCoordinateInf<T> {
         return GridCoordinateInf { x: x, y: y };
     }
 
     pub fn move_dir(&self, direction: Direction) -> GridCoordinateInf<T> {
-        let plus_one: T = 1.into();
-        let zero: T = 0.into();
-        let neg_one: T = (-1).into();
-        let north_move = GridCoordinateInf::<T>::new(zero, neg_one);
-        let south_move = GridCoordinateInf::<T>::new(zero, plus_one);
-        let west_move = GridCoordinateInf::<T>::new(neg_one, zero);
-        let east_move = GridCoordinateInf::<T>::new(plus_one, zero);
-
-        return *self
-            + match direction {
-                Direction::NORTH => north_move,
-                Direction::EAST => east_move,
-                Direction::SOUTH => south_move,
-                Direction::WEST => west_move,
-                Direction::NORTHEAST => north_move + east_move,
-                Direction::NORTHWEST => north_move + west_move,
-                Direction::SOUTHEAST => south_move + east_move,
-                Direction::SOUTHWEST => south_move + west_move,
-            };
+        let (dx, dy) = direction.offset();
+        return *self + GridCoordinateInf::new(unit(dx), unit(dy));
     }
-}
-impl<T: Clone + Copy + Add<Output = T> + From<i32> + std::ops::Mul<Output = T> + Ord>
-    GridCoordinateInf<T>
-{
+
     pub fn move_dir_dist(&self, direction: Direction, distance: T) -> GridCoordinateInf<T> {
-        let plus_one: T = 1.into();
-        let zero: T = 0.into();
-        let neg_one: T = (-1).into();
-        let north_move = GridCoordinateInf::<T>::new(zero, neg_one * distance);
-        let south_move = GridCoordinateInf::<T>::new(zero, plus_one * distance);
-        let west_move = GridCoordinateInf::<T>::new(neg_one * distance, zero);
-        let east_move = GridCoordinateInf::<T>::new(plus_one * distance, zero);
-
-        return *self
-            + match direction {
-                Direction::NORTH => north_move,
-                Direction::EAST => east_move,
-                Direction::SOUTH => south_move,
-                Direction::WEST => west_move,
-                Direction::NORTHEAST => north_move + east_move,
-                Direction::NORTHWEST => north_move + west_move,
-                Direction::SOUTHEAST => south_move + east_move,
-                Direction::SOUTHWEST => south_move + west_move,
-            };
+        let (dx, dy) = direction.offset();
+        return *self + GridCoordinateInf::new(unit(dx), unit(dy)) * distance;
+    }
+
+    pub fn manhattan_distance(&self, other: &GridCoordinateInf<T>) -> T {
+        return (self.x - other.x).abs() + (self.y - other.y).abs();
+    }
+
+    /// Distance when diagonal steps are allowed.
+    pub fn chebyshev_distance(&self, other: &GridCoordinateInf<T>) -> T {
+        return (self.x - other.x).abs().max((self.y - other.y).abs());
     }
 }
 
-impl<T: Clone + Copy + Add<Output = T> + From<i32> + Display + Ord> Display
-    for GridCoordinateInf<T>
-{
+impl<T: Signed + Copy + Ord + Display> Display for GridCoordinateInf<T> {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         return write!(f, "({}, {})", self.x, self.y);
     }
 }
 
-impl<T: Clone + Copy + Add<Output = T> + From<i32> + Ord> Ord for GridCoordinateInf<T> {
+impl<T: Signed + Copy + Ord> Ord for GridCoordinateInf<T> {
     fn cmp(&self, other: &Self) -> std::cmp::Ordering {
         // You can customize the ordering logic here.
         // For example, you might prioritize y-coordinate, then x-coordinate:
@@ -131,14 +240,52 @@ impl<T: Clone + Copy + Add<Output = T> + From<i32> + Ord> Ord for GridCoordinate
     }
 }
 
-impl<T: Clone + Copy + Add<Output = T> + From<i32> + Ord> PartialOrd for GridCoordinateInf<T> {
+impl<T: Signed + Copy + Ord> PartialOrd for GridCoordinateInf<T> {
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
         Some(self.cmp(other))
     }
 }
 
+impl<T: Signed + Copy + Ord> mathlib::Point<T, 2> for GridCoordinateInf<T> {
+    fn coordinates(&self) -> [T; 2] {
+        return [self.x, self.y];
+    }
+}
+
+impl mathlib::Point<i64, 2> for GridCoordinate {
+    fn coordinates(&self) -> [i64; 2] {
+        return [self.x as i64, self.y as i64];
+    }
+}
+
 pub type GridCoordinateInf64 = GridCoordinateInf<i64>;
 
+impl Add<GridOffset> for GridCoordinateInf64 {
+    type Output = GridCoordinateInf64;
+
+    fn add(self, offset: GridOffset) -> GridCoordinateInf64 {
+        return GridCoordinateInf64::new(self.x + offset.dx, self.y + offset.dy);
+    }
+}
+
+impl From<GridCoordinate> for GridCoordinateInf64 {
+    fn from(pos: GridCoordinate) -> GridCoordinateInf64 {
+        return GridCoordinateInf64::new(pos.x as i64, pos.y as i64);
+    }
+}
+
+impl TryFrom<GridCoordinateInf64> for GridCoordinate {
+    type Error = std::num::TryFromIntError;
+
+    /// Fails for negative coordinates.
+    fn try_from(pos: GridCoordinateInf64) -> Result<GridCoordinate, Self::Error> {
+        return Ok(GridCoordinate::new(
+            usize::try_from(pos.x)?,
+            usize::try_from(pos.y)?,
+        ));
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -257,4 +404,62 @@ mod tests {
         cur = cur.move_dir(Direction::SOUTHWEST);
         assert_eq!(cur, GridCoordinateInf64::new(0, 0));
     }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_inf() {
+        let a = GridCoordinateInf64::new(-3, 12);
+        let json = serde_json::to_string(&a).unwrap();
+        assert_eq!(json, r#"{"x":-3,"y":12}"#);
+        assert_eq!(
+            serde_json::from_str::<GridCoordinateInf64>(&json).unwrap(),
+            a
+        );
+    }
+
+    #[test]
+    fn test_offsets() {
+        let a = GridCoordinate::new(3, 5);
+        let b = GridCoordinate::new(7, 2);
+        let offset = b - a;
+        assert_eq!(offset, GridOffset::new(4, -3));
+        assert_eq!(offset.manhattan_length(), 7);
+        assert_eq!(offset.chebyshev_length(), 4);
+        assert_eq!(a.checked_add_offset(offset, 8, 8), Some(b));
+        assert_eq!(a.checked_add_offset(offset, 7, 8), None);
+        assert_eq!(a.checked_add_offset(offset * 2, 20, 20), None);
+        assert_eq!(b.checked_add_offset(-offset, 8, 8), Some(a));
+        assert_eq!(
+            GridOffset::from(Direction::NORTHWEST) + GridOffset::from(Direction::EAST),
+            GridOffset::new(0, -1)
+        );
+        assert_eq!(a * 2, GridCoordinate::new(6, 10));
+    }
+
+    #[test]
+    fn test_distances() {
+        let a = GridCoordinate::new(3, 5);
+        let b = GridCoordinate::new(7, 2);
+        assert_eq!(a.manhattan_distance(&b), 7);
+        assert_eq!(a.chebyshev_distance(&b), 4);
+        let c = GridCoordinateInf64::new(-3, 5);
+        let d = GridCoordinateInf64::new(7, -2);
+        assert_eq!(c.manhattan_distance(&d), 17);
+        assert_eq!(c.chebyshev_distance(&d), 10);
+        assert_eq!(d - c, GridCoordinateInf64::new(10, -7));
+        assert_eq!(c * 2, GridCoordinateInf64::new(-6, 10));
+    }
+
+    #[test]
+    fn test_conversions() {
+        let a = GridCoordinate::new(3, 5);
+        let inf = GridCoordinateInf64::from(a);
+        assert_eq!(inf, GridCoordinateInf64::new(3, 5));
+        assert_eq!(GridCoordinate::try_from(inf), Ok(a));
+        assert!(GridCoordinate::try_from(GridCoordinateInf64::new(-1, 0)).is_err());
+        assert_eq!(
+            inf + GridOffset::new(-5, 1),
+            GridCoordinateInf64::new(-2, 6)
+        );
+    }
 }
@@ -0,0 +1,193 @@
+use crate::direction3::Direction3;
+use crate::gridcoord::unit;
+
+use num_traits::Signed;
+use std::cmp::Ordering;
+use std::fmt::{Display, Formatter};
+use std::ops::{Add, Sub};
+
+/// A cell of a `Grid3`. Ordered by z, then y, then x.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GridCoordinate3 {
+    pub x: usize,
+    pub y: usize,
+    pub z: usize,
+}
+
+impl GridCoordinate3 {
+    pub fn new(x: usize, y: usize, z: usize) -> GridCoordinate3 {
+        return GridCoordinate3 { x: x, y: y, z: z };
+    }
+}
+
+impl Display for GridCoordinate3 {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        return write!(f, "({}, {}, {})", self.x, self.y, self.z);
+    }
+}
+
+impl Add for GridCoordinate3 {
+    type Output = GridCoordinate3;
+
+    fn add(self, other: GridCoordinate3) -> GridCoordinate3 {
+        return GridCoordinate3::new(self.x + other.x, self.y + other.y, self.z + other.z);
+    }
+}
+
+impl Ord for GridCoordinate3 {
+    fn cmp(&self, other: &Self) -> Ordering {
+        return self
+            .z
+            .cmp(&other.z)
+            .then_with(|| self.y.cmp(&other.y))
+            .then_with(|| self.x.cmp(&other.x));
+    }
+}
+
+impl PartialOrd for GridCoordinate3 {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        return Some(self.cmp(other));
+    }
+}
+
+impl mathlib::Point<i64, 3> for GridCoordinate3 {
+    fn coordinates(&self) -> [i64; 3] {
+        return [self.x as i64, self.y as i64, self.z as i64];
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GridCoordinateInf3<T: Signed + Copy + Ord> {
+    pub x: T,
+    pub y: T,
+    pub z: T,
+}
+
+impl<T: Signed + Copy + Ord> GridCoordinateInf3<T> {
+    pub fn new(x: T, y: T, z: T) -> GridCoordinateInf3<T> {
+        return GridCoordinateInf3 { x: x, y: y, z: z };
+    }
+
+    pub fn move_dir(&self, direction: Direction3) -> GridCoordinateInf3<T> {
+        let (dx, dy, dz) = direction.offset();
+        return *self + GridCoordinateInf3::new(unit(dx), unit(dy), unit(dz));
+    }
+}
+
+impl<T: Signed + Copy + Ord> GridCoordinateInf3<T> {
+    pub fn move_dir_dist(&self, direction: Direction3, distance: T) -> GridCoordinateInf3<T> {
+        let (dx, dy, dz) = direction.offset();
+        let step = |d: i32| unit::<T>(d) * distance;
+        return *self + GridCoordinateInf3::new(step(dx), step(dy), step(dz));
+    }
+}
+
+impl<T: Signed + Copy + Ord> Add for GridCoordinateInf3<T> {
+    type Output = GridCoordinateInf3<T>;
+
+    fn add(self, other: GridCoordinateInf3<T>) -> GridCoordinateInf3<T> {
+        return GridCoordinateInf3::new(self.x + other.x, self.y + other.y, self.z + other.z);
+    }
+}
+
+impl<T: Signed + Copy + Ord> Sub for GridCoordinateInf3<T> {
+    type Output = GridCoordinateInf3<T>;
+
+    fn sub(self, other: GridCoordinateInf3<T>) -> GridCoordinateInf3<T> {
+        return GridCoordinateInf3::new(self.x - other.x, self.y - other.y, self.z - other.z);
+    }
+}
+
+impl<T: Signed + Copy + Ord + Display> Display for GridCoordinateInf3<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        return write!(f, "({}, {}, {})", self.x, self.y, self.z);
+    }
+}
+
+impl<T: Signed + Copy + Ord> Ord for GridCoordinateInf3<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        return self
+            .z
+            .cmp(&other.z)
+            .then_with(|| self.y.cmp(&other.y))
+            .then_with(|| self.x.cmp(&other.x));
+    }
+}
+
+impl<T: Signed + Copy + Ord> PartialOrd for GridCoordinateInf3<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        return Some(self.cmp(other));
+    }
+}
+
+impl<T: Signed + Copy + Ord> mathlib::Point<T, 3> for GridCoordinateInf3<T> {
+    fn coordinates(&self) -> [T; 3] {
+        return [self.x, self.y, self.z];
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_coord3() {
+        assert_eq!(format!("{}", GridCoordinate3::new(1, 2, 3)), "(1, 2, 3)");
+        assert_eq!(
+            format!("{}", GridCoordinateInf3::new(-1, 2, -3)),
+            "(-1, 2, -3)"
+        );
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let a = GridCoordinateInf3::new(1i64, 2, 3);
+        let b = GridCoordinateInf3::new(5i64, -2, 0);
+        assert_eq!(a + b, GridCoordinateInf3::new(6, 0, 3));
+        assert_eq!(a - b, GridCoordinateInf3::new(-4, 4, 3));
+        assert_eq!(
+            a.move_dir(Direction3::UPNORTHWEST),
+            GridCoordinateInf3::new(0, 1, 4)
+        );
+        assert_eq!(
+            a.move_dir_dist(Direction3::DOWN, 5),
+            GridCoordinateInf3::new(1, 2, -2)
+        );
+    }
+
+    #[test]
+    fn test_order_coords3() {
+        let mut list = vec![
+            GridCoordinate3::new(0, 0, 1),
+            GridCoordinate3::new(0, 1, 0),
+            GridCoordinate3::new(1, 0, 0),
+            GridCoordinate3::new(0, 0, 0),
+        ];
+        list.sort();
+        assert_eq!(
+            list,
+            vec![
+                GridCoordinate3::new(0, 0, 0),
+                GridCoordinate3::new(1, 0, 0),
+                GridCoordinate3::new(0, 1, 0),
+                GridCoordinate3::new(0, 0, 1)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_distances() {
+        let a = GridCoordinateInf3::new(162i64, 817, 812);
+        let b = GridCoordinateInf3::new(425i64, 690, 689);
+        assert_eq!(mathlib::manhattan_distance_nd(&a, &b), 263 + 127 + 123);
+        assert_eq!(
+            mathlib::euclidean_distance_squared_nd(&a, &b),
+            263 * 263 + 127 * 127 + 123 * 123
+        );
+        let c = GridCoordinate3::new(0, 3, 0);
+        let d = GridCoordinate3::new(4, 0, 0);
+        assert_eq!(mathlib::euclidean_distance_nd(&c, &d), 5.0);
+    }
+}
@@ -0,0 +1,98 @@
+use crate::direction::{Direction, Turn};
+use crate::grid::GridTraversable;
+use crate::gridcoord::{GridCoordinate, GridCoordinateInf};
+
+use num_traits::Signed;
+
+/// A position and the direction it is facing, for anything that walks around a grid.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Heading<P> {
+    pub position: P,
+    pub facing: Direction,
+}
+
+impl<P: Copy> Heading<P> {
+    pub fn new(position: P, facing: Direction) -> Heading<P> {
+        return Heading {
+            position: position,
+            facing: facing,
+        };
+    }
+
+    pub fn turn_left(&self) -> Heading<P> {
+        return Heading::new(self.position, self.facing.turn_left());
+    }
+
+    pub fn turn_right(&self) -> Heading<P> {
+        return Heading::new(self.position, self.facing.turn_right());
+    }
+
+    pub fn turn_around(&self) -> Heading<P> {
+        return Heading::new(self.position, self.facing.opposite());
+    }
+
+    pub fn turn(&self, turn: Turn) -> Heading<P> {
+        return Heading::new(self.position, self.facing.turn(turn));
+    }
+}
+
+impl Heading<GridCoordinate> {
+    /// One step forward, None if that leaves the grid.
+    pub fn advance<G: GridTraversable>(&self, grid: &G) -> Option<Heading<GridCoordinate>> {
+        return grid
+            .get_coordinate_by_direction(self.position, self.facing)
+            .map(|next| Heading::new(next, self.facing));
+    }
+}
+
+impl<T: Signed + Copy + Ord> Heading<GridCoordinateInf<T>> {
+    pub fn advance(&self) -> Heading<GridCoordinateInf<T>> {
+        return Heading::new(self.position.move_dir(self.facing), self.facing);
+    }
+
+    pub fn advance_by(&self, distance: T) -> Heading<GridCoordinateInf<T>> {
+        return Heading::new(
+            self.position.move_dir_dist(self.facing, distance),
+            self.facing,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::Grid;
+    use crate::gridcoord::GridCoordinateInf64;
+
+    #[test]
+    fn test_advance_checked() {
+        let grid = Grid::new(3, 3, vec!['.'; 9]);
+        let mut heading = Heading::new(GridCoordinate::new(1, 1), Direction::NORTH);
+        heading = heading.advance(&grid).unwrap();
+        assert_eq!(heading.position, GridCoordinate::new(1, 0));
+        assert_eq!(heading.advance(&grid), None);
+        heading = heading.turn_right().advance(&grid).unwrap();
+        assert_eq!(
+            heading,
+            Heading::new(GridCoordinate::new(2, 0), Direction::EAST)
+        );
+        assert_eq!(heading.advance(&grid), None);
+    }
+
+    #[test]
+    fn test_follow_instructions() {
+        // R2, L3 ends 2 east and 3 north of the start
+        let mut heading = Heading::new(GridCoordinateInf64::new(0, 0), Direction::NORTH);
+        for instruction in "R2, L3".split(',') {
+            let (turn, distance) = Turn::parse_instruction(instruction).unwrap();
+            heading = heading.turn(turn).advance_by(distance as i64);
+        }
+        assert_eq!(heading.position, GridCoordinateInf64::new(2, -3));
+        assert_eq!(heading.facing, Direction::NORTH);
+        assert_eq!(
+            heading.turn_around().advance().position,
+            GridCoordinateInf64::new(2, -2)
+        );
+    }
+}
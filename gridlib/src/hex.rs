@@ -0,0 +1,363 @@
+use crate::direction::Direction;
+use crate::grid::GridPrintable;
+use crate::search::{bfs_by, SearchResult};
+
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::ops::{Add, Sub};
+use std::slice::Iter;
+
+/// Axial hex coordinate, the cube coordinate s is always -q - r.
+///
+/// Neighbours don't depend on orientation, only the names of the directions
+/// and the way a map is drawn do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HexCoordinate {
+    pub q: i64,
+    pub r: i64,
+}
+
+// Axial offsets of the six neighbours, going round anticlockwise.
+static HEX_OFFSETS: [(i64, i64); 6] = [(1, 0), (1, -1), (0, -1), (-1, 0), (-1, 1), (0, 1)];
+
+impl HexCoordinate {
+    pub fn new(q: i64, r: i64) -> HexCoordinate {
+        return HexCoordinate { q: q, r: r };
+    }
+
+    pub fn from_cube(q: i64, r: i64, s: i64) -> HexCoordinate {
+        assert_eq!(q + r + s, 0, "Cube coordinates must sum to 0");
+        return HexCoordinate::new(q, r);
+    }
+
+    pub fn s(&self) -> i64 {
+        return -self.q - self.r;
+    }
+
+    pub fn cube(&self) -> (i64, i64, i64) {
+        return (self.q, self.r, self.s());
+    }
+
+    pub fn neighbors(&self) -> [HexCoordinate; 6] {
+        return HEX_OFFSETS.map(|(dq, dr)| HexCoordinate::new(self.q + dq, self.r + dr));
+    }
+
+    /// Step once in a compass direction, None if the orientation has no such side.
+    pub fn move_dir(
+        &self,
+        orientation: HexOrientation,
+        direction: Direction,
+    ) -> Option<HexCoordinate> {
+        return orientation.offset(direction).map(|offset| *self + offset);
+    }
+
+    pub fn distance(&self, other: &HexCoordinate) -> i64 {
+        let diff = *self - *other;
+        return (diff.q.abs() + diff.r.abs() + diff.s().abs()) / 2;
+    }
+
+    /// Cells exactly radius steps away, going round anticlockwise.
+    pub fn ring(&self, radius: i64) -> Vec<HexCoordinate> {
+        if radius == 0 {
+            return vec![*self];
+        }
+        // Start radius steps out in the 5th direction, so the walk starts by heading along the 1st.
+        let (dq, dr) = HEX_OFFSETS[4];
+        let mut cur = HexCoordinate::new(self.q + dq * radius, self.r + dr * radius);
+        let mut result = vec![];
+        for &(dq, dr) in HEX_OFFSETS.iter() {
+            for _ in 0..radius {
+                result.push(cur);
+                cur = HexCoordinate::new(cur.q + dq, cur.r + dr);
+            }
+        }
+        return result;
+    }
+
+    /// This cell, then every ring out to radius.
+    pub fn spiral(&self, radius: i64) -> Vec<HexCoordinate> {
+        return (0..=radius).flat_map(|r| self.ring(r)).collect();
+    }
+}
+
+impl Display for HexCoordinate {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        return write!(f, "({}, {})", self.q, self.r);
+    }
+}
+
+impl Add for HexCoordinate {
+    type Output = HexCoordinate;
+
+    fn add(self, other: HexCoordinate) -> HexCoordinate {
+        return HexCoordinate::new(self.q + other.q, self.r + other.r);
+    }
+}
+
+impl Sub for HexCoordinate {
+    type Output = HexCoordinate;
+
+    fn sub(self, other: HexCoordinate) -> HexCoordinate {
+        return HexCoordinate::new(self.q - other.q, self.r - other.r);
+    }
+}
+
+impl Ord for HexCoordinate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        return self.r.cmp(&other.r).then_with(|| self.q.cmp(&other.q));
+    }
+}
+
+impl PartialOrd for HexCoordinate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        return Some(self.cmp(other));
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum HexOrientation {
+    // Rows of hexes, neighbours to the east and west.
+    PointyTop,
+    // Columns of hexes, neighbours to the north and south.
+    FlatTop,
+}
+
+impl HexOrientation {
+    /// The six compass directions with a neighbour in this orientation.
+    pub fn directions(&self) -> Iter<'static, Direction> {
+        static POINTY: [Direction; 6] = [
+            Direction::EAST,
+            Direction::NORTHEAST,
+            Direction::NORTHWEST,
+            Direction::WEST,
+            Direction::SOUTHWEST,
+            Direction::SOUTHEAST,
+        ];
+        static FLAT: [Direction; 6] = [
+            Direction::SOUTHEAST,
+            Direction::NORTHEAST,
+            Direction::NORTH,
+            Direction::NORTHWEST,
+            Direction::SOUTHWEST,
+            Direction::SOUTH,
+        ];
+        return match self {
+            HexOrientation::PointyTop => POINTY.iter(),
+            HexOrientation::FlatTop => FLAT.iter(),
+        };
+    }
+
+    pub fn offset(&self, direction: Direction) -> Option<HexCoordinate> {
+        return self
+            .directions()
+            .position(|&d| d == direction)
+            .map(|i| HexCoordinate::new(HEX_OFFSETS[i].0, HEX_OFFSETS[i].1));
+    }
+
+    // Text column and row of a cell, before shifting everything to start at 0.
+    fn text_position(&self, pos: HexCoordinate) -> (i64, i64) {
+        return match self {
+            HexOrientation::PointyTop => (2 * pos.q + pos.r, pos.r),
+            HexOrientation::FlatTop => (pos.q, 2 * pos.r + pos.q),
+        };
+    }
+}
+
+/// A map of hex cells, any shape, cells not in the map are walls.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct HexGrid<T: Copy> {
+    values: HashMap<HexCoordinate, T>,
+}
+
+impl<T: Copy> HexGrid<T> {
+    pub fn new() -> HexGrid<T> {
+        return HexGrid {
+            values: HashMap::new(),
+        };
+    }
+
+    /// A hexagon shaped map of radius rings around (0, 0).
+    pub fn hexagon(radius: i64, value: T) -> HexGrid<T> {
+        let mut grid = HexGrid::new();
+        for pos in HexCoordinate::new(0, 0).spiral(radius) {
+            grid.set_value(pos, value);
+        }
+        return grid;
+    }
+
+    pub fn len(&self) -> usize {
+        return self.values.len();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.values.is_empty();
+    }
+
+    pub fn get_value(&self, pos: HexCoordinate) -> Option<T> {
+        return self.values.get(&pos).copied();
+    }
+
+    pub fn set_value(&mut self, pos: HexCoordinate, value: T) {
+        self.values.insert(pos, value);
+    }
+
+    /// Every cell, sorted.
+    pub fn coords(&self) -> Vec<HexCoordinate> {
+        let mut result: Vec<HexCoordinate> = self.values.keys().copied().collect();
+        result.sort();
+        return result;
+    }
+
+    /// Neighbours of pos that are in the map, with their values.
+    pub fn get_adjacent(&self, pos: HexCoordinate) -> Vec<(HexCoordinate, T)> {
+        return pos
+            .neighbors()
+            .into_iter()
+            .filter_map(|n| self.get_value(n).map(|v| (n, v)))
+            .collect();
+    }
+
+    /// Same as the square grid `bfs`, `passable(pos, value)` says if a cell can be entered.
+    pub fn bfs<P: FnMut(HexCoordinate, T) -> bool>(
+        &self,
+        start: HexCoordinate,
+        mut passable: P,
+    ) -> SearchResult<HexCoordinate, usize> {
+        return bfs_by(vec![start], |cur| {
+            self.get_adjacent(cur)
+                .into_iter()
+                .filter_map(|(next, value)| passable(next, value).then_some(next))
+                .collect()
+        });
+    }
+}
+
+impl<T: Copy + GridPrintable> HexGrid<T> {
+    /// Draw the map as text, cells are offset by half a cell each row (pointy top)
+    /// or column (flat top), gaps are spaces.
+    pub fn grid_strings(&self, orientation: HexOrientation) -> Vec<String> {
+        if self.values.is_empty() {
+            return vec![];
+        }
+        let positions: Vec<((i64, i64), char)> = self
+            .values
+            .iter()
+            .map(|(&pos, v)| (orientation.text_position(pos), v.get_character()))
+            .collect();
+        let min_x = positions.iter().map(|((x, _), _)| *x).min().unwrap();
+        let min_y = positions.iter().map(|((_, y), _)| *y).min().unwrap();
+        let max_x = positions.iter().map(|((x, _), _)| *x).max().unwrap();
+        let max_y = positions.iter().map(|((_, y), _)| *y).max().unwrap();
+
+        let width = (max_x - min_x + 1) as usize;
+        let mut lines = vec![vec![' '; width]; (max_y - min_y + 1) as usize];
+        for ((x, y), c) in positions {
+            lines[(y - min_y) as usize][(x - min_x) as usize] = c;
+        }
+        return lines
+            .into_iter()
+            .map(|l| l.into_iter().collect::<String>().trim_end().to_string())
+            .collect();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cube_and_distance() {
+        let a = HexCoordinate::from_cube(1, -3, 2);
+        assert_eq!(a.cube(), (1, -3, 2));
+        assert_eq!(a.distance(&HexCoordinate::new(0, 0)), 3);
+        assert_eq!(a.distance(&HexCoordinate::new(-2, 0)), 3);
+        for n in a.neighbors() {
+            assert_eq!(a.distance(&n), 1);
+        }
+    }
+
+    #[test]
+    fn test_move_dir() {
+        let origin = HexCoordinate::new(0, 0);
+        let pointy = HexOrientation::PointyTop;
+        let flat = HexOrientation::FlatTop;
+        assert_eq!(
+            origin.move_dir(pointy, Direction::EAST),
+            Some(HexCoordinate::new(1, 0))
+        );
+        assert_eq!(origin.move_dir(pointy, Direction::NORTH), None);
+        assert_eq!(
+            origin.move_dir(flat, Direction::NORTH),
+            Some(HexCoordinate::new(0, -1))
+        );
+        assert_eq!(origin.move_dir(flat, Direction::EAST), None);
+        for orientation in [pointy, flat] {
+            for &d in orientation.directions() {
+                assert_eq!(
+                    origin.move_dir(orientation, d).unwrap().distance(&origin),
+                    1
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_rings_and_spiral() {
+        let center = HexCoordinate::new(2, -1);
+        assert_eq!(center.ring(0), vec![center]);
+        for radius in 1..4 {
+            let ring = center.ring(radius);
+            assert_eq!(ring.len(), 6 * radius as usize);
+            assert!(ring.iter().all(|h| h.distance(&center) == radius));
+        }
+        // 1 + 6 + 12
+        let spiral = center.spiral(2);
+        assert_eq!(spiral.len(), 19);
+        assert_eq!(spiral[0], center);
+    }
+
+    #[test]
+    fn test_bfs_around_wall() {
+        let mut grid = HexGrid::hexagon(2, '.');
+        for pos in [
+            HexCoordinate::new(0, -1),
+            HexCoordinate::new(0, 0),
+            HexCoordinate::new(0, 1),
+        ] {
+            grid.set_value(pos, '#');
+        }
+        let start = HexCoordinate::new(-1, 0);
+        let goal = HexCoordinate::new(1, 0);
+        let result = grid.bfs(start, |_, v| v == '.');
+        assert_eq!(start.distance(&goal), 2);
+        // Round the end of the wall at (0, -2)
+        assert_eq!(result.distance(goal), Some(5));
+        assert_eq!(result.path_to(goal).unwrap().len(), 6);
+
+        // bfs_by works on hexes without the map too
+        let open = bfs_by(vec![start], |h| {
+            h.neighbors()
+                .to_vec()
+                .into_iter()
+                .filter(|n| n.distance(&start) <= 2)
+                .collect()
+        });
+        assert_eq!(open.len(), 19);
+    }
+
+    #[test]
+    fn test_grid_strings() {
+        let mut grid = HexGrid::hexagon(1, '.');
+        grid.set_value(HexCoordinate::new(0, 0), 'o');
+        assert_eq!(
+            grid.grid_strings(HexOrientation::PointyTop),
+            vec![" . .", ". o .", " . ."]
+        );
+        assert_eq!(
+            grid.grid_strings(HexOrientation::FlatTop),
+            vec![" .", ". .", " o", ". .", " ."]
+        );
+    }
+}
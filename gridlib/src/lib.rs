@@ -3,16 +3,116 @@ mod gridcoord;
 pub use crate::gridcoord::GridCoordinate;
 pub use crate::gridcoord::GridCoordinateInf;
 pub use crate::gridcoord::GridCoordinateInf64;
+pub use crate::gridcoord::GridOffset;
 
 mod direction;
 
 pub use crate::direction::Direction;
+pub use crate::direction::Turn;
 
 mod grid;
 
 pub use crate::grid::Grid;
 pub use crate::grid::GridOverlay;
+pub use crate::grid::GridParsable;
+pub use crate::grid::GridParseError;
 pub use crate::grid::GridPrintable;
 pub use crate::grid::GridRotation;
 pub use crate::grid::GridTraversable;
 pub use crate::grid::SimpleGridOverlay;
+
+mod search;
+
+pub use crate::search::astar;
+pub use crate::search::astar_by;
+pub use crate::search::bfs;
+pub use crate::search::bfs_by;
+pub use crate::search::dijkstra;
+pub use crate::search::dijkstra_by;
+pub use crate::search::path_overlay;
+pub use crate::search::Adjacency;
+pub use crate::search::SearchResult;
+
+mod region;
+
+pub use crate::region::flood_fill;
+pub use crate::region::label_regions;
+pub use crate::region::label_regions_by;
+pub use crate::region::Region;
+pub use crate::region::RegionId;
+
+mod bitgrid;
+
+pub use crate::bitgrid::BitGrid;
+
+mod automaton;
+
+pub use crate::automaton::Automaton;
+pub use crate::automaton::AutomatonRun;
+pub use crate::automaton::StopReason;
+pub use crate::automaton::UpdateMode;
+
+mod render;
+
+pub use crate::render::Color;
+pub use crate::render::FrameRecorder;
+pub use crate::render::GridColorable;
+pub use crate::render::GridImage;
+pub use crate::render::ImageFormat;
+
+mod terminal;
+
+pub use crate::terminal::RenderedGrid;
+pub use crate::terminal::TerminalRenderer;
+
+mod direction3;
+
+pub use crate::direction3::Direction3;
+
+mod gridcoord3;
+
+pub use crate::gridcoord3::GridCoordinate3;
+pub use crate::gridcoord3::GridCoordinateInf3;
+
+mod grid3;
+
+pub use crate::grid3::Grid3;
+
+mod hex;
+
+pub use crate::hex::HexCoordinate;
+pub use crate::hex::HexGrid;
+pub use crate::hex::HexOrientation;
+
+mod heading;
+
+pub use crate::heading::Heading;
+
+mod rectangle;
+
+pub use crate::rectangle::GridPoint;
+pub use crate::rectangle::Rectangle;
+
+mod ray;
+
+pub use crate::ray::bresenham_line;
+pub use crate::ray::bresenham_linecoords;
+pub use crate::ray::GridRays;
+pub use crate::ray::Ray;
+pub use crate::ray::RayCast;
+
+mod beam;
+
+pub use crate::beam::Beam;
+pub use crate::beam::BeamAction;
+pub use crate::beam::BeamSimulator;
+pub use crate::beam::BeamTrace;
+
+mod view;
+
+pub use crate::view::GridView;
+pub use crate::view::GridViewMut;
+
+mod compress;
+
+pub use crate::compress::GridCompressor;
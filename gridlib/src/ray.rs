@@ -0,0 +1,272 @@
+use crate::direction::Direction;
+use crate::grid::GridTraversable;
+use crate::gridcoord::{GridCoordinate, GridCoordinateInf};
+use crate::rectangle::GridPoint;
+use crate::search::Adjacency;
+
+use num_traits::{NumCast, ToPrimitive};
+
+/// Cells stepping from a start in one direction, not including the start, until the grid edge.
+#[derive(Debug, Clone)]
+pub struct Ray<'a, G: GridTraversable + ?Sized> {
+    grid: &'a G,
+    pos: Option<GridCoordinate>,
+    direction: Direction,
+}
+
+impl<'a, G: GridTraversable + ?Sized> Ray<'a, G> {
+    pub fn new(grid: &'a G, pos: GridCoordinate, direction: Direction) -> Ray<'a, G> {
+        return Ray {
+            grid: grid,
+            pos: Some(pos),
+            direction: direction,
+        };
+    }
+}
+
+impl<G: GridTraversable + ?Sized> Iterator for Ray<'_, G> {
+    type Item = GridCoordinate;
+
+    fn next(&mut self) -> Option<GridCoordinate> {
+        self.pos = self
+            .grid
+            .get_coordinate_by_direction(self.pos?, self.direction);
+        return self.pos;
+    }
+}
+
+/// Where a cast stopped.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct RayCast {
+    // Last cell before the hit or the edge, the start if nothing was passed.
+    pub last: GridCoordinate,
+    // None if the edge was reached first.
+    pub hit: Option<GridCoordinate>,
+}
+
+/// Rays and line of sight, for anything `GridTraversable`.
+pub trait GridRays: GridTraversable {
+    /// Cells from pos in direction, pos not included, up to the edge.
+    fn ray(&self, pos: GridCoordinate, direction: Direction) -> Ray<'_, Self> {
+        return Ray::new(self, pos, direction);
+    }
+
+    /// Walk from pos in direction until predicate holds for a cell, or the edge.
+    fn cast_until<F: FnMut(GridCoordinate, Self::Item) -> bool>(
+        &self,
+        pos: GridCoordinate,
+        direction: Direction,
+        predicate: F,
+    ) -> RayCast {
+        return cast_until(self, pos, direction, predicate);
+    }
+
+    /// Cells in line of sight from pos, including the blocking cells themselves.
+    fn visible_from<F: FnMut(GridCoordinate, Self::Item) -> bool>(
+        &self,
+        pos: GridCoordinate,
+        adjacency: Adjacency,
+        blocks: F,
+    ) -> Vec<GridCoordinate> {
+        return visible_from(self, pos, adjacency, blocks);
+    }
+}
+
+impl<G: GridTraversable + ?Sized> GridRays for G {}
+
+/// Walk a ray until the predicate is true for a cell.
+fn cast_until<G, F>(
+    grid: &G,
+    pos: GridCoordinate,
+    direction: Direction,
+    mut predicate: F,
+) -> RayCast
+where
+    G: GridTraversable + ?Sized,
+    F: FnMut(GridCoordinate, G::Item) -> bool,
+{
+    let mut last = pos;
+    for next in Ray::new(grid, pos, direction) {
+        let v = grid.get_value(next).expect("Ray stays in bounds");
+        if predicate(next, v) {
+            return RayCast {
+                last: last,
+                hit: Some(next),
+            };
+        }
+        last = next;
+    }
+    return RayCast {
+        last: last,
+        hit: None,
+    };
+}
+
+/// Every cell seen along the adjacency's directions, a blocking cell is seen but hides what is behind it.
+fn visible_from<G, F>(
+    grid: &G,
+    pos: GridCoordinate,
+    adjacency: Adjacency,
+    mut blocks: F,
+) -> Vec<GridCoordinate>
+where
+    G: GridTraversable + ?Sized,
+    F: FnMut(GridCoordinate, G::Item) -> bool,
+{
+    let mut result = vec![];
+    for &direction in adjacency.directions() {
+        for next in Ray::new(grid, pos, direction) {
+            result.push(next);
+            if blocks(next, grid.get_value(next).expect("Ray stays in bounds")) {
+                break;
+            }
+        }
+    }
+    return result;
+}
+
+/// Cells on the line from a to b, both ends included, by Bresenham's algorithm.
+pub fn bresenham_line<P: GridPoint>(a: P, b: P) -> Vec<P> {
+    let to_i64 = |v: P::Scalar| v.to_i64().expect("Coordinate fits in i64");
+    let (mut x, mut y) = (to_i64(a.get_x()), to_i64(a.get_y()));
+    let (end_x, end_y) = (to_i64(b.get_x()), to_i64(b.get_y()));
+    let dx = (end_x - x).abs();
+    let dy = -(end_y - y).abs();
+    let step_x = (end_x - x).signum();
+    let step_y = (end_y - y).signum();
+    let mut err = dx + dy;
+
+    // Every point is between the two ends, so converting back can't fail.
+    let from_i64 = |v: i64| -> P::Scalar { NumCast::from(v).unwrap() };
+    let mut result = Vec::with_capacity((dx.max(-dy) + 1) as usize);
+    loop {
+        result.push(P::from_xy(from_i64(x), from_i64(y)));
+        if x == end_x && y == end_y {
+            break;
+        }
+        let doubled = 2 * err;
+        if doubled >= dy {
+            err += dy;
+            x += step_x;
+        }
+        if doubled <= dx {
+            err += dx;
+            y += step_y;
+        }
+    }
+    return result;
+}
+
+/// Rasterize the x1, y1, x2, y2 tuple from filelib's `parse_line_to_linecoords`.
+pub fn bresenham_linecoords(line: (i32, i32, i32, i32)) -> Vec<GridCoordinateInf<i32>> {
+    let (x1, y1, x2, y2) = line;
+    return bresenham_line(
+        GridCoordinateInf::new(x1, y1),
+        GridCoordinateInf::new(x2, y2),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::Grid;
+
+    fn produce_grid() -> Grid<char> {
+        return Grid::parse("..#..\n.....\n#.@.#\n.....\n..#..").unwrap();
+    }
+
+    #[test]
+    fn test_ray() {
+        let grid = produce_grid();
+        let start = GridCoordinate::new(2, 2);
+        let east: Vec<GridCoordinate> = grid.ray(start, Direction::EAST).collect();
+        assert_eq!(
+            east,
+            vec![GridCoordinate::new(3, 2), GridCoordinate::new(4, 2)]
+        );
+        assert_eq!(grid.ray(start, Direction::NORTHWEST).count(), 2);
+        assert_eq!(
+            grid.ray(GridCoordinate::new(0, 0), Direction::NORTH).next(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_cast_until() {
+        let grid = produce_grid();
+        let start = GridCoordinate::new(2, 2);
+        let cast = grid.cast_until(start, Direction::WEST, |_, c| c == '#');
+        assert_eq!(cast.hit, Some(GridCoordinate::new(0, 2)));
+        assert_eq!(cast.last, GridCoordinate::new(1, 2));
+
+        let cast = grid.cast_until(start, Direction::SOUTHEAST, |_, c| c == '#');
+        assert_eq!(cast.hit, None);
+        assert_eq!(cast.last, GridCoordinate::new(4, 4));
+
+        // Blocked straight away leaves last at the start
+        let cast = grid.cast_until(GridCoordinate::new(1, 2), Direction::WEST, |_, c| c == '#');
+        assert_eq!(cast.last, GridCoordinate::new(1, 2));
+    }
+
+    #[test]
+    fn test_visible_from() {
+        let grid = produce_grid();
+        let start = GridCoordinate::new(2, 2);
+        let seen = grid.visible_from(start, Adjacency::Cardinal, |_, c| c == '#');
+        assert_eq!(seen.len(), 8);
+        assert!(seen.contains(&GridCoordinate::new(2, 0)));
+        let seen = grid.visible_from(start, Adjacency::Diagonal, |_, c| c == '#');
+        assert_eq!(seen.len(), 8);
+        let blockers = grid
+            .visible_from(start, Adjacency::All, |_, c| c == '#')
+            .iter()
+            .filter(|&&p| grid.get_value(p) == Some('#'))
+            .count();
+        assert_eq!(blockers, 4);
+    }
+
+    #[test]
+    fn test_bresenham() {
+        let line = bresenham_line(GridCoordinate::new(0, 0), GridCoordinate::new(6, 2));
+        assert_eq!(
+            line,
+            vec![
+                GridCoordinate::new(0, 0),
+                GridCoordinate::new(1, 0),
+                GridCoordinate::new(2, 1),
+                GridCoordinate::new(3, 1),
+                GridCoordinate::new(4, 1),
+                GridCoordinate::new(5, 2),
+                GridCoordinate::new(6, 2),
+            ]
+        );
+        let single = bresenham_line(GridCoordinate::new(3, 3), GridCoordinate::new(3, 3));
+        assert_eq!(single, vec![GridCoordinate::new(3, 3)]);
+
+        // Steep and heading back towards negative
+        let steep = bresenham_line(
+            GridCoordinateInf::new(1i64, 3),
+            GridCoordinateInf::new(-1, -3),
+        );
+        assert_eq!(steep.len(), 7);
+        assert_eq!(steep[3], GridCoordinateInf::new(0, 0));
+        for pair in steep.windows(2) {
+            assert!(pair[0].chebyshev_distance(&pair[1]) == 1);
+        }
+    }
+
+    #[test]
+    fn test_bresenham_linecoords() {
+        // "9,7 -> 7,9"
+        let line = bresenham_linecoords((9, 7, 7, 9));
+        assert_eq!(
+            line,
+            vec![
+                GridCoordinateInf::new(9, 7),
+                GridCoordinateInf::new(8, 8),
+                GridCoordinateInf::new(7, 9),
+            ]
+        );
+        assert_eq!(bresenham_linecoords((0, 9, 5, 9)).len(), 6);
+    }
+}
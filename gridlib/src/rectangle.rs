@@ -0,0 +1,331 @@
+use crate::gridcoord::{GridCoordinate, GridCoordinateInf};
+
+use num_traits::{One, PrimInt, Signed};
+
+/// A coordinate type a `Rectangle` can be built from.
+pub trait GridPoint: Copy + Eq {
+    type Scalar: PrimInt;
+
+    fn get_x(&self) -> Self::Scalar;
+    fn get_y(&self) -> Self::Scalar;
+    fn from_xy(x: Self::Scalar, y: Self::Scalar) -> Self;
+}
+
+impl GridPoint for GridCoordinate {
+    type Scalar = usize;
+
+    fn get_x(&self) -> usize {
+        return self.x;
+    }
+
+    fn get_y(&self) -> usize {
+        return self.y;
+    }
+
+    fn from_xy(x: usize, y: usize) -> GridCoordinate {
+        return GridCoordinate::new(x, y);
+    }
+}
+
+impl<T: PrimInt + Signed> GridPoint for GridCoordinateInf<T> {
+    type Scalar = T;
+
+    fn get_x(&self) -> T {
+        return self.x;
+    }
+
+    fn get_y(&self) -> T {
+        return self.y;
+    }
+
+    fn from_xy(x: T, y: T) -> GridCoordinateInf<T> {
+        return GridCoordinateInf::new(x, y);
+    }
+}
+
+/// Axis aligned rectangle of cells, both corners are inside it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Rectangle<P: GridPoint> {
+    pub top_left: P,
+    pub bottom_right: P,
+}
+
+impl<P: GridPoint> Rectangle<P> {
+    /// From any two opposite corners.
+    pub fn from_corners(a: P, b: P) -> Rectangle<P> {
+        return Rectangle {
+            top_left: P::from_xy(a.get_x().min(b.get_x()), a.get_y().min(b.get_y())),
+            bottom_right: P::from_xy(a.get_x().max(b.get_x()), a.get_y().max(b.get_y())),
+        };
+    }
+
+    /// width and height in cells, both at least 1.
+    pub fn with_size(top_left: P, width: P::Scalar, height: P::Scalar) -> Rectangle<P> {
+        let one = P::Scalar::one();
+        assert!(width >= one && height >= one, "Rectangle can't be empty");
+        return Rectangle {
+            top_left: top_left,
+            bottom_right: P::from_xy(
+                top_left.get_x() + width - one,
+                top_left.get_y() + height - one,
+            ),
+        };
+    }
+
+    pub fn min_x(&self) -> P::Scalar {
+        return self.top_left.get_x();
+    }
+
+    pub fn min_y(&self) -> P::Scalar {
+        return self.top_left.get_y();
+    }
+
+    pub fn max_x(&self) -> P::Scalar {
+        return self.bottom_right.get_x();
+    }
+
+    pub fn max_y(&self) -> P::Scalar {
+        return self.bottom_right.get_y();
+    }
+
+    /// In cells, so a single cell is 1 wide.
+    pub fn get_width(&self) -> P::Scalar {
+        return self.max_x() - self.min_x() + P::Scalar::one();
+    }
+
+    pub fn get_height(&self) -> P::Scalar {
+        return self.max_y() - self.min_y() + P::Scalar::one();
+    }
+
+    /// Number of cells covered, counting the border.
+    pub fn area(&self) -> P::Scalar {
+        return self.get_width() * self.get_height();
+    }
+
+    /// Area between the corner points, as if they were points rather than cells.
+    pub fn area_exclusive(&self) -> P::Scalar {
+        return (self.max_x() - self.min_x()) * (self.max_y() - self.min_y());
+    }
+
+    pub fn contains_point(&self, p: P) -> bool {
+        return p.get_x() >= self.min_x()
+            && p.get_x() <= self.max_x()
+            && p.get_y() >= self.min_y()
+            && p.get_y() <= self.max_y();
+    }
+
+    pub fn contains(&self, other: &Rectangle<P>) -> bool {
+        return self.contains_point(other.top_left) && self.contains_point(other.bottom_right);
+    }
+
+    pub fn intersects(&self, other: &Rectangle<P>) -> bool {
+        return self.intersection(other).is_some();
+    }
+
+    pub fn intersection(&self, other: &Rectangle<P>) -> Option<Rectangle<P>> {
+        let min_x = self.min_x().max(other.min_x());
+        let min_y = self.min_y().max(other.min_y());
+        let max_x = self.max_x().min(other.max_x());
+        let max_y = self.max_y().min(other.max_y());
+        if min_x > max_x || min_y > max_y {
+            return None;
+        }
+        return Some(Rectangle {
+            top_left: P::from_xy(min_x, min_y),
+            bottom_right: P::from_xy(max_x, max_y),
+        });
+    }
+
+    /// Smallest rectangle covering both.
+    pub fn union(&self, other: &Rectangle<P>) -> Rectangle<P> {
+        return Rectangle {
+            top_left: P::from_xy(
+                self.min_x().min(other.min_x()),
+                self.min_y().min(other.min_y()),
+            ),
+            bottom_right: P::from_xy(
+                self.max_x().max(other.max_x()),
+                self.max_y().max(other.max_y()),
+            ),
+        };
+    }
+
+    /// Split into the columns left of x and the columns from x on, either side may be empty.
+    pub fn split_at_x(&self, x: P::Scalar) -> (Option<Rectangle<P>>, Option<Rectangle<P>>) {
+        let left = (x > self.min_x()).then(|| Rectangle {
+            top_left: self.top_left,
+            bottom_right: P::from_xy((x - P::Scalar::one()).min(self.max_x()), self.max_y()),
+        });
+        let right = (x <= self.max_x()).then(|| Rectangle {
+            top_left: P::from_xy(x.max(self.min_x()), self.min_y()),
+            bottom_right: self.bottom_right,
+        });
+        return (left, right);
+    }
+
+    /// Split into the rows above y and the rows from y on, either side may be empty.
+    pub fn split_at_y(&self, y: P::Scalar) -> (Option<Rectangle<P>>, Option<Rectangle<P>>) {
+        let top = (y > self.min_y()).then(|| Rectangle {
+            top_left: self.top_left,
+            bottom_right: P::from_xy(self.max_x(), (y - P::Scalar::one()).min(self.max_y())),
+        });
+        let bottom = (y <= self.max_y()).then(|| Rectangle {
+            top_left: P::from_xy(self.min_x(), y.max(self.min_y())),
+            bottom_right: self.bottom_right,
+        });
+        return (top, bottom);
+    }
+
+    /// The cells of self not in other, as at most 4 disjoint rectangles.
+    pub fn subtract(&self, other: &Rectangle<P>) -> Vec<Rectangle<P>> {
+        let hole = match self.intersection(other) {
+            Some(hole) => hole,
+            None => return vec![*self],
+        };
+        let one = P::Scalar::one();
+        let mut result = vec![];
+        // Full width bands above and below, then the parts left and right of the hole.
+        if hole.min_y() > self.min_y() {
+            result.push(Rectangle {
+                top_left: self.top_left,
+                bottom_right: P::from_xy(self.max_x(), hole.min_y() - one),
+            });
+        }
+        if hole.max_y() < self.max_y() {
+            result.push(Rectangle {
+                top_left: P::from_xy(self.min_x(), hole.max_y() + one),
+                bottom_right: self.bottom_right,
+            });
+        }
+        if hole.min_x() > self.min_x() {
+            result.push(Rectangle {
+                top_left: P::from_xy(self.min_x(), hole.min_y()),
+                bottom_right: P::from_xy(hole.min_x() - one, hole.max_y()),
+            });
+        }
+        if hole.max_x() < self.max_x() {
+            result.push(Rectangle {
+                top_left: P::from_xy(hole.max_x() + one, hole.min_y()),
+                bottom_right: P::from_xy(self.max_x(), hole.max_y()),
+            });
+        }
+        return result;
+    }
+
+    /// Every cell, in reading order.
+    pub fn cells(&self) -> impl Iterator<Item = P> {
+        let (min_x, max_x) = (self.min_x(), self.max_x());
+        let one = P::Scalar::one();
+        let mut y = self.min_y();
+        let max_y = self.max_y();
+        let mut x = min_x;
+        let mut done = false;
+        return std::iter::from_fn(move || {
+            if done {
+                return None;
+            }
+            let cur = P::from_xy(x, y);
+            if x < max_x {
+                x = x + one;
+            } else if y < max_y {
+                x = min_x;
+                y = y + one;
+            } else {
+                done = true;
+            }
+            Some(cur)
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gridcoord::GridCoordinateInf64;
+
+    fn rect(x1: i64, y1: i64, x2: i64, y2: i64) -> Rectangle<GridCoordinateInf64> {
+        return Rectangle::from_corners(
+            GridCoordinateInf64::new(x1, y1),
+            GridCoordinateInf64::new(x2, y2),
+        );
+    }
+
+    #[test]
+    fn test_area_conventions() {
+        let r = rect(11, 1, 2, 5);
+        assert_eq!(r.top_left, GridCoordinateInf64::new(2, 1));
+        assert_eq!(r.bottom_right, GridCoordinateInf64::new(11, 5));
+        assert_eq!(r.get_width(), 10);
+        assert_eq!(r.get_height(), 5);
+        assert_eq!(r.area(), 50);
+        assert_eq!(r.area_exclusive(), 36);
+    }
+
+    #[test]
+    fn test_with_size_and_cells() {
+        let r = Rectangle::with_size(GridCoordinate::new(1, 2), 2, 3);
+        assert_eq!(r.bottom_right, GridCoordinate::new(2, 4));
+        let cells: Vec<GridCoordinate> = r.cells().collect();
+        assert_eq!(cells.len(), 6);
+        assert_eq!(cells[0], GridCoordinate::new(1, 2));
+        assert_eq!(cells[1], GridCoordinate::new(2, 2));
+        assert_eq!(cells[2], GridCoordinate::new(1, 3));
+        assert_eq!(cells[5], GridCoordinate::new(2, 4));
+        // Starting at 0 must not underflow
+        let corner = Rectangle::with_size(GridCoordinate::new(0, 0), 1, 1);
+        assert_eq!(corner.cells().count(), 1);
+    }
+
+    #[test]
+    fn test_containment_and_intersection() {
+        let a = rect(0, 0, 4, 4);
+        let b = rect(3, 2, 6, 8);
+        assert!(a.contains(&rect(1, 1, 4, 4)));
+        assert!(!a.contains(&b));
+        assert!(a.contains_point(GridCoordinateInf64::new(4, 0)));
+        assert!(!a.contains_point(GridCoordinateInf64::new(5, 0)));
+        assert_eq!(a.intersection(&b), Some(rect(3, 2, 4, 4)));
+        assert_eq!(a.intersection(&rect(5, 0, 6, 1)), None);
+        assert!(!a.intersects(&rect(5, 0, 6, 1)));
+        assert_eq!(a.union(&b), rect(0, 0, 6, 8));
+    }
+
+    #[test]
+    fn test_split() {
+        let r = rect(0, 0, 4, 2);
+        assert_eq!(
+            r.split_at_x(2),
+            (Some(rect(0, 0, 1, 2)), Some(rect(2, 0, 4, 2)))
+        );
+        assert_eq!(r.split_at_x(0), (None, Some(r)));
+        assert_eq!(r.split_at_x(9), (Some(r), None));
+        assert_eq!(
+            r.split_at_y(1),
+            (Some(rect(0, 0, 4, 0)), Some(rect(0, 1, 4, 2)))
+        );
+    }
+
+    #[test]
+    fn test_subtract() {
+        let a = rect(0, 0, 4, 4);
+        let hole = rect(1, 1, 2, 3);
+        let pieces = a.subtract(&hole);
+        assert_eq!(pieces.len(), 4);
+        let total: i64 = pieces.iter().map(|p| p.area()).sum();
+        assert_eq!(total, a.area() - hole.area());
+        for (i, p) in pieces.iter().enumerate() {
+            assert!(a.contains(p));
+            assert!(!p.intersects(&hole));
+            for q in pieces[i + 1..].iter() {
+                assert!(!p.intersects(q));
+            }
+        }
+        assert_eq!(a.subtract(&rect(5, 5, 6, 6)), vec![a]);
+        assert_eq!(a.subtract(&rect(-1, -1, 9, 9)), vec![]);
+        // Corner bite on an unsigned grid
+        let u = Rectangle::from_corners(GridCoordinate::new(0, 0), GridCoordinate::new(2, 2));
+        let bite = Rectangle::from_corners(GridCoordinate::new(0, 0), GridCoordinate::new(0, 0));
+        assert_eq!(u.subtract(&bite).iter().map(|p| p.area()).sum::<usize>(), 8);
+    }
+}
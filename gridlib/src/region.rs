@@ -0,0 +1,301 @@
+use crate::direction::Direction;
+use crate::grid::{Grid, GridTraversable};
+use crate::gridcoord::GridCoordinate;
+use crate::search::{bfs_by, grid_neighbors, Adjacency};
+
+pub type RegionId = usize;
+
+/// A connected group of cells, and some statistics about its shape.
+///
+/// Perimeter and sides are always measured along cell edges, even when the
+/// region was joined up with diagonal adjacency.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Region<T: Copy> {
+    pub id: RegionId,
+    // Value of the first cell found, in reading order.
+    pub value: T,
+    pub cells: Vec<GridCoordinate>,
+    pub perimeter: usize,
+    // A closed outline has as many straight sides as it has corners.
+    pub sides: usize,
+    pub top_left: GridCoordinate,
+    pub bottom_right: GridCoordinate,
+}
+
+impl<T: Copy> Region<T> {
+    pub fn area(&self) -> usize {
+        return self.cells.len();
+    }
+
+    pub fn get_width(&self) -> usize {
+        return self.bottom_right.x - self.top_left.x + 1;
+    }
+
+    pub fn get_height(&self) -> usize {
+        return self.bottom_right.y - self.top_left.y + 1;
+    }
+}
+
+/// Every cell reachable from `start`, stepping only between neighbours where
+/// `connected(current_value, next_value)` holds. Sorted in reading order.
+pub fn flood_fill<G, F>(
+    grid: &G,
+    start: GridCoordinate,
+    adjacency: Adjacency,
+    mut connected: F,
+) -> Vec<GridCoordinate>
+where
+    G: GridTraversable,
+    F: FnMut(G::Item, G::Item) -> bool,
+{
+    if grid.get_value(start).is_none() {
+        return vec![];
+    }
+    let found = bfs_by(vec![start], |cur| {
+        let mut result = vec![];
+        for (next, value) in grid_neighbors(grid, cur, adjacency) {
+            if connected(grid.get_value(cur).unwrap(), value) {
+                result.push(next);
+            }
+        }
+        return result;
+    });
+    let mut cells: Vec<GridCoordinate> = found.reached().map(|(pos, _)| pos).collect();
+    cells.sort();
+    return cells;
+}
+
+/// Split the grid into regions of equal neighbouring cells.
+pub fn label_regions<T: Copy + PartialEq>(
+    grid: &Grid<T>,
+    adjacency: Adjacency,
+) -> (Grid<RegionId>, Vec<Region<T>>) {
+    return label_regions_by(grid, adjacency, |a, b| a == b);
+}
+
+/// Split the grid into regions, two neighbours share a region when
+/// `connected` holds for their values. `connected` should be symmetric.
+pub fn label_regions_by<T, F>(
+    grid: &Grid<T>,
+    adjacency: Adjacency,
+    mut connected: F,
+) -> (Grid<RegionId>, Vec<Region<T>>)
+where
+    T: Copy,
+    F: FnMut(T, T) -> bool,
+{
+    let width = grid.get_width();
+    let height = grid.get_height();
+    if width == 0 || height == 0 {
+        // coord_iter still gives (0, 0) for an empty grid, so there's nothing to label
+        return (Grid::new(width, height, vec![]), vec![]);
+    }
+    let mut labels: Vec<Option<RegionId>> = vec![None; width * height];
+    let mut region_cells: Vec<Vec<GridCoordinate>> = vec![];
+
+    for coord in grid.coord_iter() {
+        if labels[coord.x + coord.y * width].is_some() {
+            continue;
+        }
+        let id = region_cells.len();
+        let cells = flood_fill(grid, coord, adjacency, &mut connected);
+        for cell in cells.iter() {
+            labels[cell.x + cell.y * width] = Some(id);
+        }
+        region_cells.push(cells);
+    }
+
+    let label_grid = Grid::new(
+        width,
+        height,
+        labels.into_iter().map(|x| x.unwrap()).collect(),
+    );
+    let regions = region_cells
+        .into_iter()
+        .enumerate()
+        .map(|(id, cells)| measure_region(grid, &label_grid, id, cells))
+        .collect();
+    return (label_grid, regions);
+}
+
+fn in_region(
+    labels: &Grid<RegionId>,
+    pos: GridCoordinate,
+    direction: Direction,
+    id: RegionId,
+) -> bool {
+    return match labels.get_coordinate_by_direction(pos, direction) {
+        Some(next) => labels.get_value(next) == Some(id),
+        None => false,
+    };
+}
+
+fn measure_region<T: Copy>(
+    grid: &Grid<T>,
+    labels: &Grid<RegionId>,
+    id: RegionId,
+    cells: Vec<GridCoordinate>,
+) -> Region<T> {
+    // Each pair of edges meeting at a corner of a cell, plus the diagonal between them.
+    static CORNERS: [(Direction, Direction, Direction); 4] = [
+        (Direction::NORTH, Direction::EAST, Direction::NORTHEAST),
+        (Direction::EAST, Direction::SOUTH, Direction::SOUTHEAST),
+        (Direction::SOUTH, Direction::WEST, Direction::SOUTHWEST),
+        (Direction::WEST, Direction::NORTH, Direction::NORTHWEST),
+    ];
+
+    let mut perimeter = 0;
+    let mut sides = 0;
+    let mut top_left = cells[0];
+    let mut bottom_right = cells[0];
+    for &cell in cells.iter() {
+        top_left = GridCoordinate::new(top_left.x.min(cell.x), top_left.y.min(cell.y));
+        bottom_right = GridCoordinate::new(bottom_right.x.max(cell.x), bottom_right.y.max(cell.y));
+
+        for &direction in Direction::cardinal_iterator() {
+            if !in_region(labels, cell, direction, id) {
+                perimeter += 1;
+            }
+        }
+
+        for &(a, b, diagonal) in CORNERS.iter() {
+            let in_a = in_region(labels, cell, a, id);
+            let in_b = in_region(labels, cell, b, id);
+            let in_diagonal = in_region(labels, cell, diagonal, id);
+            // Outside corner, or inside corner of an L shape
+            if (!in_a && !in_b) || (in_a && in_b && !in_diagonal) {
+                sides += 1;
+            }
+        }
+    }
+
+    return Region {
+        id: id,
+        value: grid.get_value(cells[0]).unwrap(),
+        cells: cells,
+        perimeter: perimeter,
+        sides: sides,
+        top_left: top_left,
+        bottom_right: bottom_right,
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn produce_grid(lines: &[&str]) -> Grid<char> {
+        let values: Vec<char> = lines.iter().flat_map(|l| l.chars()).collect();
+        return Grid::new(lines[0].len(), lines.len(), values);
+    }
+
+    #[test]
+    fn test_label_regions() {
+        let grid = produce_grid(&["AAAA", "BBCD", "BBCC", "EEEC"]);
+        let (labels, regions) = label_regions(&grid, Adjacency::Cardinal);
+        assert_eq!(regions.len(), 5);
+        assert_eq!(
+            labels.data_copy(),
+            vec![0, 0, 0, 0, 1, 1, 2, 3, 1, 1, 2, 2, 4, 4, 4, 2]
+        );
+
+        let stats: Vec<(char, usize, usize, usize)> = regions
+            .iter()
+            .map(|r| (r.value, r.area(), r.perimeter, r.sides))
+            .collect();
+        assert_eq!(
+            stats,
+            vec![
+                ('A', 4, 10, 4),
+                ('B', 4, 8, 4),
+                ('C', 4, 10, 8),
+                ('D', 1, 4, 4),
+                ('E', 3, 8, 4)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_region_bounding_box() {
+        let grid = produce_grid(&["AAAA", "BBCD", "BBCC", "EEEC"]);
+        let (_, regions) = label_regions(&grid, Adjacency::Cardinal);
+        let c = &regions[2];
+        assert_eq!(c.top_left, GridCoordinate::new(2, 1));
+        assert_eq!(c.bottom_right, GridCoordinate::new(3, 3));
+        assert_eq!(c.get_width(), 2);
+        assert_eq!(c.get_height(), 3);
+    }
+
+    #[test]
+    fn test_regions_with_holes() {
+        let grid = produce_grid(&["OOOOO", "OXOXO", "OOOOO", "OXOXO", "OOOOO"]);
+        let (_, regions) = label_regions(&grid, Adjacency::Cardinal);
+        assert_eq!(regions.len(), 5);
+        assert_eq!(regions[0].area(), 21);
+        assert_eq!(regions[0].perimeter, 36);
+        // 4 outside, 4 for each hole
+        assert_eq!(regions[0].sides, 20);
+    }
+
+    #[test]
+    fn test_diagonal_connectivity() {
+        let grid = produce_grid(&["#..", ".#.", "..#"]);
+        let (_, cardinal) = label_regions(&grid, Adjacency::Cardinal);
+        let (labels, all) = label_regions(&grid, Adjacency::All);
+        assert_eq!(cardinal.len(), 5);
+        assert_eq!(all.len(), 2);
+        assert_eq!(labels.data_copy(), vec![0, 1, 1, 1, 0, 1, 1, 1, 0]);
+        assert_eq!(all[0].area(), 3);
+        assert_eq!(all[0].perimeter, 12);
+    }
+
+    #[test]
+    fn test_label_regions_by() {
+        let grid = Grid::new(4, 2, vec![1, 2, 8, 9, 3, 7, 9, 1]);
+        let (_, regions) = label_regions_by(&grid, Adjacency::Cardinal, |a, b| (a > 5) == (b > 5));
+        assert_eq!(regions.len(), 3);
+        assert_eq!(
+            regions[1].cells,
+            vec![
+                GridCoordinate::new(2, 0),
+                GridCoordinate::new(3, 0),
+                GridCoordinate::new(1, 1),
+                GridCoordinate::new(2, 1)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_label_empty() {
+        let grid: Grid<char> = Grid::new(0, 0, vec![]);
+        let (labels, regions) = label_regions(&grid, Adjacency::Cardinal);
+        assert_eq!(labels.get_width(), 0);
+        assert!(regions.is_empty());
+        let wide: Grid<char> = Grid::new(3, 0, vec![]);
+        assert!(label_regions(&wide, Adjacency::Cardinal).1.is_empty());
+    }
+
+    #[test]
+    fn test_flood_fill() {
+        let grid = produce_grid(&["..#", ".##", "#.."]);
+        let cells = flood_fill(
+            &grid,
+            GridCoordinate::new(0, 0),
+            Adjacency::Cardinal,
+            |a, b| a == b,
+        );
+        assert_eq!(
+            cells,
+            vec![
+                GridCoordinate::new(0, 0),
+                GridCoordinate::new(1, 0),
+                GridCoordinate::new(0, 1)
+            ]
+        );
+        assert_eq!(
+            flood_fill(&grid, GridCoordinate::new(9, 9), Adjacency::All, |a, b| a
+                == b),
+            vec![]
+        );
+    }
+}
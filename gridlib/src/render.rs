@@ -0,0 +1,368 @@
+extern crate gif;
+extern crate png;
+
+use crate::grid::{Grid, GridTraversable};
+
+use std::fs::File;
+use std::io::{BufWriter, Error, ErrorKind, Write};
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Color {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+impl Color {
+    pub const BLACK: Color = Color::new(0, 0, 0);
+    pub const WHITE: Color = Color::new(255, 255, 255);
+
+    pub const fn new(r: u8, g: u8, b: u8) -> Color {
+        return Color { r: r, g: g, b: b };
+    }
+}
+
+/// Image counterpart of `GridPrintable`, a cell picks its own colour.
+pub trait GridColorable {
+    fn get_color(&self) -> Color;
+}
+
+impl GridColorable for bool {
+    fn get_color(&self) -> Color {
+        return if *self { Color::WHITE } else { Color::BLACK };
+    }
+}
+
+impl GridColorable for Color {
+    fn get_color(&self) -> Color {
+        return *self;
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum ImageFormat {
+    Ppm,
+    Png,
+}
+
+impl ImageFormat {
+    pub fn extension(&self) -> &'static str {
+        return match self {
+            ImageFormat::Ppm => "ppm",
+            ImageFormat::Png => "png",
+        };
+    }
+}
+
+/// RGB image of a grid, every cell drawn as a cell_size * cell_size square.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct GridImage {
+    width: usize,
+    height: usize,
+    pixels: Vec<u8>,
+}
+
+impl GridImage {
+    pub fn get_width(&self) -> usize {
+        return self.width;
+    }
+
+    pub fn get_height(&self) -> usize {
+        return self.height;
+    }
+
+    pub fn get_pixel(&self, x: usize, y: usize) -> Option<Color> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        let index = (x + y * self.width) * 3;
+        return Some(Color::new(
+            self.pixels[index],
+            self.pixels[index + 1],
+            self.pixels[index + 2],
+        ));
+    }
+
+    /// Raw r, g, b bytes, row by row.
+    pub fn as_rgb(&self) -> &[u8] {
+        return &self.pixels;
+    }
+
+    /// Binary (P6) PPM.
+    pub fn encode_ppm<W: Write>(&self, mut w: W) -> std::io::Result<()> {
+        write!(w, "P6\n{} {}\n255\n", self.width, self.height)?;
+        w.write_all(&self.pixels)?;
+        return w.flush();
+    }
+
+    pub fn encode_png<W: Write>(&self, w: W) -> std::io::Result<()> {
+        let to_u32 = |v: usize| u32::try_from(v).map_err(|_| ErrorKind::InvalidInput);
+        let mut encoder = png::Encoder::new(w, to_u32(self.width)?, to_u32(self.height)?);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header().map_err(Error::other)?;
+        writer
+            .write_image_data(&self.pixels)
+            .map_err(Error::other)?;
+        return writer.finish().map_err(Error::other);
+    }
+
+    pub fn encode<W: Write>(&self, w: W, format: ImageFormat) -> std::io::Result<()> {
+        return match format {
+            ImageFormat::Ppm => self.encode_ppm(w),
+            ImageFormat::Png => self.encode_png(w),
+        };
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P, format: ImageFormat) -> std::io::Result<()> {
+        let file = File::create(path)?;
+        return self.encode(BufWriter::new(file), format);
+    }
+}
+
+impl<T: Copy> Grid<T> {
+    /// Draw the grid, colouring each cell with color_of.
+    pub fn to_image_with<F: Fn(T) -> Color>(&self, cell_size: usize, color_of: F) -> GridImage {
+        let width = self.get_width() * cell_size;
+        let height = self.get_height() * cell_size;
+        let mut pixels = vec![0; width * height * 3];
+        for coord in self.coord_iter() {
+            let color = color_of(self.get_value(coord).unwrap());
+            for py in coord.y * cell_size..(coord.y + 1) * cell_size {
+                for px in coord.x * cell_size..(coord.x + 1) * cell_size {
+                    let index = (px + py * width) * 3;
+                    pixels[index] = color.r;
+                    pixels[index + 1] = color.g;
+                    pixels[index + 2] = color.b;
+                }
+            }
+        }
+        return GridImage {
+            width: width,
+            height: height,
+            pixels: pixels,
+        };
+    }
+}
+
+impl<T: Copy + GridColorable> Grid<T> {
+    pub fn to_image(&self, cell_size: usize) -> GridImage {
+        return self.to_image_with(cell_size, |v| v.get_color());
+    }
+}
+
+/// Collects frames of a simulation, then writes them out all at once.
+#[derive(Debug, Clone, Default)]
+pub struct FrameRecorder {
+    frames: Vec<GridImage>,
+}
+
+impl FrameRecorder {
+    pub fn new() -> FrameRecorder {
+        return FrameRecorder { frames: vec![] };
+    }
+
+    pub fn record(&mut self, frame: GridImage) {
+        self.frames.push(frame);
+    }
+
+    pub fn len(&self) -> usize {
+        return self.frames.len();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.frames.is_empty();
+    }
+
+    /// Write every frame as dir/prefix_0000.ext, dir/prefix_0001.ext, ...
+    pub fn write_sequence<P: AsRef<Path>>(
+        &self,
+        dir: P,
+        prefix: &str,
+        format: ImageFormat,
+    ) -> std::io::Result<Vec<PathBuf>> {
+        std::fs::create_dir_all(dir.as_ref())?;
+        let mut paths = vec![];
+        for (i, frame) in self.frames.iter().enumerate() {
+            let path = dir
+                .as_ref()
+                .join(format!("{}_{:04}.{}", prefix, i, format.extension()));
+            frame.save(&path, format)?;
+            paths.push(path);
+        }
+        return Ok(paths);
+    }
+
+    /// Animated GIF looping forever, delay is in hundredths of a second.
+    ///
+    /// All frames need the same size, and GIFs can't be larger than 65535 pixels a side.
+    pub fn encode_gif<W: Write>(&self, w: W, delay: u16) -> std::io::Result<()> {
+        let first = match self.frames.first() {
+            Some(frame) => frame,
+            None => return Err(Error::new(ErrorKind::InvalidInput, "No frames recorded")),
+        };
+        let to_u16 = |v: usize| u16::try_from(v).map_err(|_| ErrorKind::InvalidInput);
+        let width = to_u16(first.width)?;
+        let height = to_u16(first.height)?;
+        // Check everything up front, so a bad frame doesn't leave half a GIF written
+        if self
+            .frames
+            .iter()
+            .any(|frame| frame.width != first.width || frame.height != first.height)
+        {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "Frames must all be the same size",
+            ));
+        }
+
+        let mut encoder = gif::Encoder::new(w, width, height, &[]).map_err(Error::other)?;
+        encoder
+            .set_repeat(gif::Repeat::Infinite)
+            .map_err(Error::other)?;
+        for frame in self.frames.iter() {
+            // Speed 10 is the quantizer's recommended default, 1 is very slow
+            let mut gif_frame = gif::Frame::from_rgb_speed(width, height, &frame.pixels, 10);
+            gif_frame.delay = delay;
+            encoder.write_frame(&gif_frame).map_err(Error::other)?;
+        }
+        return Ok(());
+    }
+
+    pub fn write_gif<P: AsRef<Path>>(&self, path: P, delay: u16) -> std::io::Result<()> {
+        let file = File::create(path)?;
+        return self.encode_gif(BufWriter::new(file), delay);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::GridRotation;
+
+    fn produce_grid() -> Grid<bool> {
+        return Grid::new(3, 2, vec![true, false, true, false, true, false]);
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("gridlib_render_{}_{}", name, std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        return dir;
+    }
+
+    #[test]
+    fn test_to_image_cell_size() {
+        let image = produce_grid().to_image(2);
+        assert_eq!(image.get_width(), 6);
+        assert_eq!(image.get_height(), 4);
+        assert_eq!(image.get_pixel(0, 0), Some(Color::WHITE));
+        assert_eq!(image.get_pixel(1, 1), Some(Color::WHITE));
+        assert_eq!(image.get_pixel(2, 0), Some(Color::BLACK));
+        assert_eq!(image.get_pixel(3, 3), Some(Color::WHITE));
+        assert_eq!(image.get_pixel(6, 0), None);
+    }
+
+    #[test]
+    fn test_to_image_with() {
+        let grid = Grid::new(2, 1, vec!['#', '.']);
+        let red = Color::new(255, 0, 0);
+        let image = grid.to_image_with(1, |c| if c == '#' { red } else { Color::BLACK });
+        assert_eq!(image.as_rgb(), &[255, 0, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn test_encode_ppm() {
+        let image = produce_grid().to_image(1);
+        let mut bytes = vec![];
+        image.encode_ppm(&mut bytes).unwrap();
+        let header = b"P6\n3 2\n255\n";
+        assert_eq!(&bytes[..header.len()], header);
+        assert_eq!(bytes.len(), header.len() + 3 * 2 * 3);
+        assert_eq!(&bytes[header.len()..header.len() + 3], &[255, 255, 255]);
+    }
+
+    #[test]
+    fn test_encode_png() {
+        let image = produce_grid().to_image(4);
+        let mut bytes = vec![];
+        image.encode_png(&mut bytes).unwrap();
+        assert_eq!(
+            &bytes[..8],
+            &[0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A]
+        );
+
+        let decoder = png::Decoder::new(std::io::Cursor::new(bytes));
+        let mut reader = decoder.read_info().unwrap();
+        let mut buffer = vec![0; reader.output_buffer_size().unwrap()];
+        let info = reader.next_frame(&mut buffer).unwrap();
+        assert_eq!((info.width, info.height), (12, 8));
+        assert_eq!(&buffer[..info.buffer_size()], image.as_rgb());
+    }
+
+    #[test]
+    fn test_write_sequence() {
+        let dir = temp_dir("sequence");
+        let mut recorder = FrameRecorder::new();
+        let mut grid = produce_grid();
+        for _ in 0..3 {
+            recorder.record(grid.to_image(1));
+            grid.rotate_clockwise();
+            grid.rotate_clockwise();
+        }
+        let paths = recorder
+            .write_sequence(&dir, "frame", ImageFormat::Ppm)
+            .unwrap();
+        assert_eq!(paths.len(), 3);
+        assert_eq!(paths[2], dir.join("frame_0002.ppm"));
+        assert!(paths.iter().all(|p| p.exists()));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_encode_gif() {
+        let mut recorder = FrameRecorder::new();
+        let grid = produce_grid();
+        recorder.record(grid.to_image(2));
+        recorder.record(grid.to_image_with(2, |v| (!v).get_color()));
+        let mut bytes = vec![];
+        recorder.encode_gif(&mut bytes, 5).unwrap();
+        assert_eq!(&bytes[..6], b"GIF89a");
+
+        let mut decoder = gif::DecodeOptions::new();
+        decoder.set_color_output(gif::ColorOutput::RGBA);
+        let mut reader = decoder.read_info(std::io::Cursor::new(bytes)).unwrap();
+        let mut count = 0;
+        while let Some(frame) = reader.read_next_frame().unwrap() {
+            assert_eq!((frame.width, frame.height), (6, 4));
+            assert_eq!(frame.delay, 5);
+            count += 1;
+        }
+        assert_eq!(count, 2);
+    }
+
+    #[test]
+    fn test_gif_errors() {
+        let mut recorder = FrameRecorder::new();
+        assert!(recorder.encode_gif(vec![], 1).is_err());
+        recorder.record(produce_grid().to_image(1));
+        recorder.record(produce_grid().to_image(2));
+        let mut out: Vec<u8> = vec![];
+        let err = recorder.encode_gif(&mut out, 1).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+        // Rejected before the header went out
+        assert!(out.is_empty());
+    }
+
+    #[test]
+    fn test_png_too_wide() {
+        let image = GridImage {
+            width: u32::MAX as usize + 1,
+            height: 0,
+            pixels: vec![],
+        };
+        let err = image.encode_png(vec![]).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+    }
+}
@@ -0,0 +1,454 @@
+use crate::direction::Direction;
+use crate::grid::{Grid, GridTraversable, SimpleGridOverlay};
+use crate::gridcoord::GridCoordinate;
+
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, VecDeque};
+use std::hash::Hash;
+use std::ops::Add;
+use std::slice::Iter;
+
+/// Which neighbours a search is allowed to step to.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Adjacency {
+    Cardinal,
+    Diagonal,
+    All,
+}
+
+impl Adjacency {
+    pub fn directions(&self) -> Iter<'static, Direction> {
+        return match self {
+            Adjacency::Cardinal => Direction::cardinal_iterator(),
+            Adjacency::Diagonal => Direction::diagonal_iterator(),
+            Adjacency::All => Direction::iterator(),
+        };
+    }
+}
+
+/// Distances and back-pointers produced by a search.
+///
+/// Every reached node knows which node it was reached from, so a path to any
+/// of them can be rebuilt without searching again.
+#[derive(Debug, Clone)]
+pub struct SearchResult<N: Copy + Eq + Hash, C: Copy> {
+    distances: HashMap<N, C>,
+    previous: HashMap<N, N>,
+}
+
+impl<N: Copy + Eq + Hash, C: Copy> SearchResult<N, C> {
+    fn new() -> SearchResult<N, C> {
+        return SearchResult {
+            distances: HashMap::new(),
+            previous: HashMap::new(),
+        };
+    }
+
+    pub fn distance(&self, node: N) -> Option<C> {
+        return self.distances.get(&node).copied();
+    }
+
+    pub fn contains(&self, node: N) -> bool {
+        return self.distances.contains_key(&node);
+    }
+
+    pub fn len(&self) -> usize {
+        return self.distances.len();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.distances.is_empty();
+    }
+
+    pub fn reached(&self) -> impl Iterator<Item = (N, C)> + '_ {
+        return self.distances.iter().map(|(&n, &c)| (n, c));
+    }
+
+    /// The path from whichever start reached `node` up to and including `node`.
+    pub fn path_to(&self, node: N) -> Option<Vec<N>> {
+        if !self.distances.contains_key(&node) {
+            return None;
+        }
+        let mut path = vec![node];
+        let mut cur = node;
+        while let Some(&prev) = self.previous.get(&cur) {
+            path.push(prev);
+            cur = prev;
+        }
+        path.reverse();
+        return Some(path);
+    }
+}
+
+impl<C: Copy> SearchResult<GridCoordinate, C> {
+    /// Lay the distances out as a grid, unreached cells are None.
+    pub fn distance_grid(&self, width: usize, height: usize) -> Grid<Option<C>> {
+        let mut grid = Grid::new(width, height, vec![None; width * height]);
+        for (&coord, &distance) in self.distances.iter() {
+            grid.set_value(coord, Some(distance));
+        }
+        return grid;
+    }
+}
+
+/// Breadth first search over any node type, every step costs 1.
+/// ```
+/// // Walk a number line from 0 to 3.
+/// let result = gridlib::bfs_by(vec![0], |n: i32| if n < 3 { vec![n + 1] } else { vec![] });
+/// assert_eq!(result.distance(3), Some(3));
+/// assert_eq!(result.path_to(2), Some(vec![0, 1, 2]));
+/// ```
+pub fn bfs_by<N, I, F>(starts: I, mut neighbors: F) -> SearchResult<N, usize>
+where
+    N: Copy + Eq + Hash,
+    I: IntoIterator<Item = N>,
+    F: FnMut(N) -> Vec<N>,
+{
+    let mut result = SearchResult::new();
+    let mut queue = VecDeque::new();
+    for start in starts {
+        if result.distances.contains_key(&start) {
+            continue;
+        }
+        result.distances.insert(start, 0);
+        queue.push_back(start);
+    }
+
+    while let Some(cur) = queue.pop_front() {
+        let next_distance = result.distances[&cur] + 1;
+        for next in neighbors(cur) {
+            if result.distances.contains_key(&next) {
+                continue;
+            }
+            result.distances.insert(next, next_distance);
+            result.previous.insert(next, cur);
+            queue.push_back(next);
+        }
+    }
+    return result;
+}
+
+/// Dijkstra over any node type, neighbours come with the cost of the step.
+///
+/// Costs use `Default` as zero, so any of the integer types work.
+pub fn dijkstra_by<N, C, I, F>(starts: I, mut neighbors: F) -> SearchResult<N, C>
+where
+    N: Copy + Eq + Hash + Ord,
+    C: Copy + Ord + Add<Output = C> + Default,
+    I: IntoIterator<Item = N>,
+    F: FnMut(N) -> Vec<(N, C)>,
+{
+    let mut result = SearchResult::new();
+    let mut heap = BinaryHeap::new();
+    for start in starts {
+        result.distances.insert(start, C::default());
+        heap.push(Reverse((C::default(), start)));
+    }
+
+    while let Some(Reverse((cost, cur))) = heap.pop() {
+        if cost > result.distances[&cur] {
+            // Already found a cheaper way here
+            continue;
+        }
+        for (next, step) in neighbors(cur) {
+            let next_cost = cost + step;
+            let better = match result.distances.get(&next) {
+                Some(&existing) => next_cost < existing,
+                None => true,
+            };
+            if better {
+                result.distances.insert(next, next_cost);
+                result.previous.insert(next, cur);
+                heap.push(Reverse((next_cost, next)));
+            }
+        }
+    }
+    return result;
+}
+
+/// A* over any node type. Heuristic must never overestimate the remaining cost.
+///
+/// Returns the total cost and the path including both ends.
+pub fn astar_by<N, C, F, H>(
+    start: N,
+    goal: N,
+    mut neighbors: F,
+    mut heuristic: H,
+) -> Option<(C, Vec<N>)>
+where
+    N: Copy + Eq + Hash + Ord,
+    C: Copy + Ord + Add<Output = C> + Default,
+    F: FnMut(N) -> Vec<(N, C)>,
+    H: FnMut(N) -> C,
+{
+    let mut result: SearchResult<N, C> = SearchResult::new();
+    let mut heap = BinaryHeap::new();
+    result.distances.insert(start, C::default());
+    heap.push(Reverse((heuristic(start), C::default(), start)));
+
+    while let Some(Reverse((_, cost, cur))) = heap.pop() {
+        if cur == goal {
+            return Some((cost, result.path_to(goal)?));
+        }
+        if cost > result.distances[&cur] {
+            continue;
+        }
+        for (next, step) in neighbors(cur) {
+            let next_cost = cost + step;
+            let better = match result.distances.get(&next) {
+                Some(&existing) => next_cost < existing,
+                None => true,
+            };
+            if better {
+                result.distances.insert(next, next_cost);
+                result.previous.insert(next, cur);
+                heap.push(Reverse((next_cost + heuristic(next), next_cost, next)));
+            }
+        }
+    }
+    return None;
+}
+
+pub(crate) fn grid_neighbors<G: GridTraversable>(
+    grid: &G,
+    pos: GridCoordinate,
+    adjacency: Adjacency,
+) -> Vec<(GridCoordinate, G::Item)> {
+    let mut result = vec![];
+    for &direction in adjacency.directions() {
+        if let Some(next) = grid.get_coordinate_by_direction(pos, direction) {
+            if let Some(value) = grid.get_value(next) {
+                result.push((next, value));
+            }
+        }
+    }
+    return result;
+}
+
+/// BFS distance map from `start`, only stepping onto cells where `passable` is true.
+pub fn bfs<G, P>(
+    grid: &G,
+    start: GridCoordinate,
+    adjacency: Adjacency,
+    mut passable: P,
+) -> SearchResult<GridCoordinate, usize>
+where
+    G: GridTraversable,
+    P: FnMut(GridCoordinate, G::Item) -> bool,
+{
+    return bfs_by(vec![start], |cur| {
+        grid_neighbors(grid, cur, adjacency)
+            .into_iter()
+            .filter_map(|(next, value)| passable(next, value).then_some(next))
+            .collect()
+    });
+}
+
+/// Dijkstra from `start`. `cost(from, to, value_at_to)` gives the cost of a step,
+/// or None if the step isn't allowed.
+pub fn dijkstra<G, C, F>(
+    grid: &G,
+    start: GridCoordinate,
+    adjacency: Adjacency,
+    mut cost: F,
+) -> SearchResult<GridCoordinate, C>
+where
+    G: GridTraversable,
+    C: Copy + Ord + Add<Output = C> + Default,
+    F: FnMut(GridCoordinate, GridCoordinate, G::Item) -> Option<C>,
+{
+    return dijkstra_by(vec![start], |cur| {
+        grid_neighbors(grid, cur, adjacency)
+            .into_iter()
+            .filter_map(|(next, value)| Some((next, cost(cur, next, value)?)))
+            .collect()
+    });
+}
+
+/// A* from `start` to `goal`, `cost` works the same as for `dijkstra`.
+pub fn astar<G, C, F, H>(
+    grid: &G,
+    start: GridCoordinate,
+    goal: GridCoordinate,
+    adjacency: Adjacency,
+    mut cost: F,
+    heuristic: H,
+) -> Option<(C, Vec<GridCoordinate>)>
+where
+    G: GridTraversable,
+    C: Copy + Ord + Add<Output = C> + Default,
+    F: FnMut(GridCoordinate, GridCoordinate, G::Item) -> Option<C>,
+    H: FnMut(GridCoordinate) -> C,
+{
+    return astar_by(
+        start,
+        goal,
+        |cur| {
+            grid_neighbors(grid, cur, adjacency)
+                .into_iter()
+                .filter_map(|(next, value)| Some((next, cost(cur, next, value)?)))
+                .collect()
+        },
+        heuristic,
+    );
+}
+
+/// Turn a path into an overlay for `grid_strings_with_overlay`.
+pub fn path_overlay(path: &[GridCoordinate], c: char) -> Vec<SimpleGridOverlay> {
+    return path
+        .iter()
+        .map(|&pos| SimpleGridOverlay::new(c, pos))
+        .collect();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn produce_maze() -> Grid<char> {
+        let lines = ["S..#....", ".#.#.##.", ".#...#..", ".####.#.", "......#E"];
+        let values: Vec<char> = lines.iter().flat_map(|l| l.chars()).collect();
+        return Grid::new(8, 5, values);
+    }
+
+    fn manhattan(a: GridCoordinate, b: GridCoordinate) -> usize {
+        return a.x.abs_diff(b.x) + a.y.abs_diff(b.y);
+    }
+
+    #[test]
+    fn test_adjacency_directions() {
+        assert_eq!(Adjacency::Cardinal.directions().len(), 4);
+        assert_eq!(Adjacency::Diagonal.directions().len(), 4);
+        assert_eq!(Adjacency::All.directions().len(), 8);
+    }
+
+    #[test]
+    fn test_bfs_distance() {
+        let grid = produce_maze();
+        let result = bfs(
+            &grid,
+            GridCoordinate::new(0, 0),
+            Adjacency::Cardinal,
+            |_, c| c != '#',
+        );
+        assert_eq!(result.distance(GridCoordinate::new(0, 0)), Some(0));
+        assert_eq!(result.distance(GridCoordinate::new(2, 2)), Some(4));
+        assert_eq!(result.distance(GridCoordinate::new(7, 4)), Some(15));
+        assert_eq!(result.distance(GridCoordinate::new(3, 0)), None);
+        let path = result.path_to(GridCoordinate::new(7, 4)).unwrap();
+        assert_eq!(path.len(), 16);
+        assert_eq!(path[0], GridCoordinate::new(0, 0));
+        assert_eq!(path[15], GridCoordinate::new(7, 4));
+    }
+
+    #[test]
+    fn test_bfs_diagonal_is_shorter() {
+        let grid = Grid::new(5, 5, vec!['.'; 25]);
+        let cardinal = bfs(
+            &grid,
+            GridCoordinate::new(0, 0),
+            Adjacency::Cardinal,
+            |_, _| true,
+        );
+        let all = bfs(&grid, GridCoordinate::new(0, 0), Adjacency::All, |_, _| {
+            true
+        });
+        let diagonal = bfs(
+            &grid,
+            GridCoordinate::new(0, 0),
+            Adjacency::Diagonal,
+            |_, _| true,
+        );
+        assert_eq!(cardinal.distance(GridCoordinate::new(4, 4)), Some(8));
+        assert_eq!(all.distance(GridCoordinate::new(4, 4)), Some(4));
+        // Diagonal only moves can never change colour on a checkerboard
+        assert_eq!(diagonal.distance(GridCoordinate::new(1, 0)), None);
+        assert_eq!(diagonal.len(), 13);
+    }
+
+    #[test]
+    fn test_distance_grid() {
+        let grid = Grid::new(3, 1, vec!['.', '.', '#']);
+        let result = bfs(
+            &grid,
+            GridCoordinate::new(0, 0),
+            Adjacency::Cardinal,
+            |_, c| c != '#',
+        );
+        let distances = result.distance_grid(3, 1);
+        assert_eq!(distances.data_copy(), vec![Some(0), Some(1), None]);
+    }
+
+    #[test]
+    fn test_dijkstra_weighted() {
+        let values = vec![1, 9, 1, 1, 9, 1, 1, 1, 1];
+        let grid = Grid::new(3, 3, values);
+        let result = dijkstra(
+            &grid,
+            GridCoordinate::new(0, 0),
+            Adjacency::Cardinal,
+            |_, _, v| Some(v),
+        );
+        // Going around the 9s is cheaper than going through them
+        assert_eq!(result.distance(GridCoordinate::new(2, 0)), Some(6));
+        assert_eq!(
+            result.path_to(GridCoordinate::new(2, 0)).unwrap(),
+            vec![
+                GridCoordinate::new(0, 0),
+                GridCoordinate::new(0, 1),
+                GridCoordinate::new(0, 2),
+                GridCoordinate::new(1, 2),
+                GridCoordinate::new(2, 2),
+                GridCoordinate::new(2, 1),
+                GridCoordinate::new(2, 0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_astar_matches_bfs() {
+        let grid = produce_maze();
+        let start = GridCoordinate::new(0, 0);
+        let goal = GridCoordinate::new(7, 4);
+        let (cost, path) = astar(
+            &grid,
+            start,
+            goal,
+            Adjacency::Cardinal,
+            |_, _, c| if c == '#' { None } else { Some(1usize) },
+            |pos| manhattan(pos, goal),
+        )
+        .unwrap();
+        assert_eq!(cost, 15);
+        assert_eq!(path.len(), 16);
+        assert_eq!(*path.last().unwrap(), goal);
+    }
+
+    #[test]
+    fn test_astar_no_path() {
+        let grid = Grid::new(3, 1, vec!['.', '#', '.']);
+        let result = astar(
+            &grid,
+            GridCoordinate::new(0, 0),
+            GridCoordinate::new(2, 0),
+            Adjacency::All,
+            |_, _, c| if c == '#' { None } else { Some(1usize) },
+            |_| 0,
+        );
+        assert_eq!(result, None);
+    }
+
+    #[test]
+    fn test_path_overlay() {
+        let grid = Grid::new(3, 2, vec!['.'; 6]);
+        let result = bfs(
+            &grid,
+            GridCoordinate::new(0, 0),
+            Adjacency::Cardinal,
+            |_, _| true,
+        );
+        let path = result.path_to(GridCoordinate::new(2, 0)).unwrap();
+        let strings = grid.grid_strings_with_overlay(path_overlay(&path, 'O'));
+        assert_eq!(strings, vec!["OOO", "..."]);
+    }
+}
@@ -0,0 +1,281 @@
+use crate::grid::{Grid, GridOverlay, GridPrintable, GridTraversable};
+use crate::gridcoord::GridCoordinate;
+use crate::render::Color;
+
+const RESET: &str = "\x1b[0m";
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+struct Cell {
+    character: char,
+    foreground: Option<Color>,
+    background: Option<Color>,
+}
+
+#[derive(Debug, Clone)]
+struct Layer {
+    z: i32,
+    cells: Vec<(GridCoordinate, Cell)>,
+}
+
+/// Lines of a rendered grid, plus any overlay positions that fell off the grid.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct RenderedGrid {
+    pub lines: Vec<String>,
+    pub out_of_bounds: Vec<GridCoordinate>,
+}
+
+/// Draws a grid with ANSI colours, and any number of overlays stacked on top.
+///
+/// Layers with a higher z are drawn over lower ones, equal z in the order added.
+/// An overlay with no colour of its own keeps the colour of the cell below it.
+#[derive(Debug, Clone)]
+pub struct TerminalRenderer {
+    layers: Vec<Layer>,
+    // Center, width and height
+    viewport: Option<(GridCoordinate, usize, usize)>,
+    color: bool,
+}
+
+impl Default for TerminalRenderer {
+    fn default() -> Self {
+        return TerminalRenderer::new();
+    }
+}
+
+impl TerminalRenderer {
+    pub fn new() -> TerminalRenderer {
+        return TerminalRenderer {
+            layers: vec![],
+            viewport: None,
+            color: true,
+        };
+    }
+
+    /// Turn escape codes off, for logs and tests.
+    pub fn set_color(&mut self, color: bool) {
+        self.color = color;
+    }
+
+    pub fn add_layer<J: GridOverlay, I: IntoIterator<Item = J>>(&mut self, z: i32, overlay: I) {
+        let cells = overlay
+            .into_iter()
+            .map(|v| {
+                let cell = Cell {
+                    character: v.get_character(),
+                    foreground: v.get_foreground(),
+                    background: v.get_background(),
+                };
+                (v.get_position(), cell)
+            })
+            .collect();
+        self.layers.push(Layer { z: z, cells: cells });
+    }
+
+    pub fn clear_layers(&mut self) {
+        self.layers.clear();
+    }
+
+    /// Only draw a width * height window around center, moved inwards at the grid edges.
+    pub fn set_viewport(&mut self, center: GridCoordinate, width: usize, height: usize) {
+        self.viewport = Some((center, width, height));
+    }
+
+    pub fn clear_viewport(&mut self) {
+        self.viewport = None;
+    }
+
+    // Start and length of the window along one axis.
+    fn window(center: usize, wanted: usize, size: usize) -> (usize, usize) {
+        if wanted >= size {
+            return (0, size);
+        }
+        let start = center.saturating_sub(wanted / 2).min(size - wanted);
+        return (start, wanted);
+    }
+
+    pub fn render<T: Copy + GridPrintable>(&self, grid: &Grid<T>) -> RenderedGrid {
+        let (x_start, width, y_start, height) = match self.viewport {
+            Some((center, w, h)) => {
+                let (x, w) = TerminalRenderer::window(center.x, w, grid.get_width());
+                let (y, h) = TerminalRenderer::window(center.y, h, grid.get_height());
+                (x, w, y, h)
+            }
+            None => (0, grid.get_width(), 0, grid.get_height()),
+        };
+
+        let mut cells = Vec::with_capacity(width * height);
+        for y in y_start..y_start + height {
+            for x in x_start..x_start + width {
+                let v = grid.get_value(GridCoordinate::new(x, y)).unwrap();
+                cells.push(Cell {
+                    character: v.get_character(),
+                    foreground: v.get_foreground(),
+                    background: v.get_background(),
+                });
+            }
+        }
+
+        let mut layers: Vec<&Layer> = self.layers.iter().collect();
+        layers.sort_by_key(|l| l.z);
+        let mut out_of_bounds = vec![];
+        for layer in layers {
+            for &(pos, cell) in layer.cells.iter() {
+                if grid.get_value(pos).is_none() {
+                    out_of_bounds.push(pos);
+                    continue;
+                }
+                if pos.x < x_start
+                    || pos.x >= x_start + width
+                    || pos.y < y_start
+                    || pos.y >= y_start + height
+                {
+                    continue;
+                }
+                let below = &mut cells[(pos.x - x_start) + (pos.y - y_start) * width];
+                *below = Cell {
+                    character: cell.character,
+                    foreground: cell.foreground.or(below.foreground),
+                    background: cell.background.or(below.background),
+                };
+            }
+        }
+
+        let lines = cells
+            .chunks(width.max(1))
+            .take(height)
+            .map(|row| self.render_line(row))
+            .collect();
+        return RenderedGrid {
+            lines: lines,
+            out_of_bounds: out_of_bounds,
+        };
+    }
+
+    fn render_line(&self, row: &[Cell]) -> String {
+        let mut line = String::new();
+        let mut current = (None, None);
+        for cell in row {
+            let style = (cell.foreground, cell.background);
+            if self.color && style != current {
+                if current != (None, None) {
+                    line.push_str(RESET);
+                }
+                if let Some(c) = cell.foreground {
+                    line.push_str(&format!("\x1b[38;2;{};{};{}m", c.r, c.g, c.b));
+                }
+                if let Some(c) = cell.background {
+                    line.push_str(&format!("\x1b[48;2;{};{};{}m", c.r, c.g, c.b));
+                }
+                current = style;
+            }
+            line.push(cell.character);
+        }
+        if self.color && current != (None, None) {
+            line.push_str(RESET);
+        }
+        return line;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::SimpleGridOverlay;
+
+    const RED: Color = Color::new(255, 0, 0);
+
+    fn produce_grid() -> Grid<char> {
+        let values: Vec<char> = ["abcde", "fghij", "klmno", "pqrst"]
+            .iter()
+            .flat_map(|l| l.chars())
+            .collect();
+        return Grid::new(5, 4, values);
+    }
+
+    #[test]
+    fn test_render_plain() {
+        let grid = produce_grid();
+        let renderer = TerminalRenderer::new();
+        let rendered = renderer.render(&grid);
+        assert_eq!(rendered.lines, grid.grid_strings());
+        assert!(rendered.out_of_bounds.is_empty());
+    }
+
+    #[test]
+    fn test_layers_z_order() {
+        let grid = produce_grid();
+        let mut renderer = TerminalRenderer::new();
+        let pos = GridCoordinate::new(1, 1);
+        renderer.add_layer(5, vec![SimpleGridOverlay::new('#', pos)]);
+        renderer.add_layer(
+            0,
+            vec![
+                SimpleGridOverlay::new('*', pos),
+                SimpleGridOverlay::new('*', GridCoordinate::new(2, 1)),
+            ],
+        );
+        renderer.add_layer(5, vec![SimpleGridOverlay::new('@', pos)]);
+        let rendered = renderer.render(&grid);
+        assert_eq!(rendered.lines[1], "f@*ij");
+    }
+
+    #[test]
+    fn test_out_of_bounds_reported() {
+        let grid = produce_grid();
+        let mut renderer = TerminalRenderer::new();
+        renderer.add_layer(
+            0,
+            vec![
+                SimpleGridOverlay::new('#', GridCoordinate::new(5, 0)),
+                SimpleGridOverlay::new('#', GridCoordinate::new(0, 0)),
+                SimpleGridOverlay::new('#', GridCoordinate::new(1, 4)),
+            ],
+        );
+        let rendered = renderer.render(&grid);
+        assert_eq!(rendered.lines[0], "#bcde");
+        assert_eq!(
+            rendered.out_of_bounds,
+            vec![GridCoordinate::new(5, 0), GridCoordinate::new(1, 4)]
+        );
+    }
+
+    #[test]
+    fn test_viewport() {
+        let grid = produce_grid();
+        let mut renderer = TerminalRenderer::new();
+        renderer.set_viewport(GridCoordinate::new(2, 1), 3, 3);
+        assert_eq!(renderer.render(&grid).lines, vec!["bcd", "ghi", "lmn"]);
+        // Pushed back inside at the corner
+        renderer.set_viewport(GridCoordinate::new(4, 3), 2, 2);
+        assert_eq!(renderer.render(&grid).lines, vec!["no", "st"]);
+        // Larger than the grid shows all of it
+        renderer.set_viewport(GridCoordinate::new(0, 0), 10, 10);
+        assert_eq!(renderer.render(&grid).lines, grid.grid_strings());
+    }
+
+    #[test]
+    fn test_colors() {
+        let grid = Grid::new(3, 1, vec!['.', '.', '.']);
+        let mut renderer = TerminalRenderer::new();
+        renderer.add_layer(
+            0,
+            vec![
+                SimpleGridOverlay::new('#', GridCoordinate::new(0, 0)).with_colors(Some(RED), None),
+                SimpleGridOverlay::new('#', GridCoordinate::new(1, 0)).with_colors(Some(RED), None),
+            ],
+        );
+        renderer.add_layer(
+            1,
+            vec![SimpleGridOverlay::new('x', GridCoordinate::new(1, 0))
+                .with_colors(None, Some(Color::WHITE))],
+        );
+        let rendered = renderer.render(&grid);
+        assert_eq!(
+            rendered.lines[0],
+            "\x1b[38;2;255;0;0m#\x1b[0m\x1b[38;2;255;0;0m\x1b[48;2;255;255;255mx\x1b[0m."
+        );
+
+        renderer.set_color(false);
+        assert_eq!(renderer.render(&grid).lines[0], "#x.");
+    }
+}
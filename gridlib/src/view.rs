@@ -0,0 +1,457 @@
+use crate::direction::Direction;
+use crate::grid::{bounded_coordinate_by_direction, Grid, GridTraversable};
+use crate::gridcoord::GridCoordinate;
+use crate::rectangle::Rectangle;
+
+/// Read only window onto a grid, coordinates are relative to the window's top left.
+///
+/// It has no `set_value`, so it isn't `GridTraversable`. Copy it out with `to_grid`, or use a
+/// `GridViewMut`, to run searches on it.
+#[derive(Debug, Clone, Copy)]
+pub struct GridView<'a, T: Copy> {
+    grid: &'a Grid<T>,
+    origin: GridCoordinate,
+    width: usize,
+    height: usize,
+}
+
+/// Same as `GridView`, but writes go through to the grid underneath.
+#[derive(Debug)]
+pub struct GridViewMut<'a, T: Copy> {
+    grid: &'a mut Grid<T>,
+    origin: GridCoordinate,
+    width: usize,
+    height: usize,
+}
+
+// Window must lie entirely inside a width * height grid.
+fn window_fits(
+    origin: GridCoordinate,
+    width: usize,
+    height: usize,
+    grid_width: usize,
+    grid_height: usize,
+) -> bool {
+    let fits = |start: usize, size: usize, limit: usize| {
+        return start.checked_add(size).is_some_and(|end| end <= limit);
+    };
+    return fits(origin.x, width, grid_width) && fits(origin.y, height, grid_height);
+}
+
+impl<'a, T: Copy> GridView<'a, T> {
+    pub fn get_width(&self) -> usize {
+        return self.width;
+    }
+
+    pub fn get_height(&self) -> usize {
+        return self.height;
+    }
+
+    /// Where the window's (0, 0) is on the grid underneath.
+    pub fn get_origin(&self) -> GridCoordinate {
+        return self.origin;
+    }
+
+    /// Convert a position in the window to one on the grid underneath.
+    pub fn to_parent(&self, pos: GridCoordinate) -> GridCoordinate {
+        return self.origin + pos;
+    }
+
+    pub fn coord_iter(&self) -> impl Iterator<Item = GridCoordinate> {
+        let width = self.width;
+        return (0..self.width * self.height)
+            .map(move |i| GridCoordinate::new(i % width, i / width));
+    }
+
+    /// A window of this window, None if it doesn't fit.
+    pub fn view(
+        &self,
+        origin: GridCoordinate,
+        width: usize,
+        height: usize,
+    ) -> Option<GridView<'a, T>> {
+        if !window_fits(origin, width, height, self.width, self.height) {
+            return None;
+        }
+        return Some(GridView {
+            grid: self.grid,
+            origin: self.origin + origin,
+            width: width,
+            height: height,
+        });
+    }
+
+    /// Copy the window out into a grid of its own.
+    pub fn to_grid(&self) -> Grid<T> {
+        let values = self
+            .coord_iter()
+            .map(|pos| self.get_value(pos).unwrap())
+            .collect();
+        return Grid::new(self.width, self.height, values);
+    }
+}
+
+// Views are read only, use `GridViewMut` to write.
+impl<T: Copy> GridView<'_, T> {
+    pub fn get_value(&self, pos: GridCoordinate) -> Option<T> {
+        if pos.x >= self.width || pos.y >= self.height {
+            return None;
+        }
+        return self.grid.get_value(self.origin + pos);
+    }
+
+    /// Step from pos in direction, None if that leaves the window.
+    pub fn get_coordinate_by_direction(
+        &self,
+        pos: GridCoordinate,
+        direction: Direction,
+    ) -> Option<GridCoordinate> {
+        return bounded_coordinate_by_direction(self.width, self.height, pos, direction);
+    }
+}
+
+impl<T: Copy> GridViewMut<'_, T> {
+    pub fn get_width(&self) -> usize {
+        return self.width;
+    }
+
+    pub fn get_height(&self) -> usize {
+        return self.height;
+    }
+
+    pub fn get_origin(&self) -> GridCoordinate {
+        return self.origin;
+    }
+
+    pub fn to_parent(&self, pos: GridCoordinate) -> GridCoordinate {
+        return self.origin + pos;
+    }
+
+    /// Set every cell in the window.
+    pub fn fill(&mut self, value: T) {
+        for y in 0..self.height {
+            for x in 0..self.width {
+                self.set_value(GridCoordinate::new(x, y), value);
+            }
+        }
+    }
+
+    pub fn as_view(&self) -> GridView<'_, T> {
+        return GridView {
+            grid: self.grid,
+            origin: self.origin,
+            width: self.width,
+            height: self.height,
+        };
+    }
+}
+
+impl<T: Copy> GridTraversable for GridViewMut<'_, T> {
+    type Item = T;
+
+    fn get_value(&self, pos: GridCoordinate) -> Option<T> {
+        if pos.x >= self.width || pos.y >= self.height {
+            return None;
+        }
+        return self.grid.get_value(self.origin + pos);
+    }
+
+    fn set_value(&mut self, pos: GridCoordinate, value: T) {
+        if pos.x >= self.width || pos.y >= self.height {
+            return;
+        }
+        self.grid.set_value(self.origin + pos, value);
+    }
+
+    fn get_coordinate_by_direction(
+        &self,
+        pos: GridCoordinate,
+        direction: Direction,
+    ) -> Option<GridCoordinate> {
+        return bounded_coordinate_by_direction(self.width, self.height, pos, direction);
+    }
+}
+
+impl<T: Copy> Grid<T> {
+    /// A width * height window with origin as its top left, None if it doesn't fit.
+    pub fn view(
+        &self,
+        origin: GridCoordinate,
+        width: usize,
+        height: usize,
+    ) -> Option<GridView<'_, T>> {
+        if !window_fits(origin, width, height, self.get_width(), self.get_height()) {
+            return None;
+        }
+        return Some(GridView {
+            grid: self,
+            origin: origin,
+            width: width,
+            height: height,
+        });
+    }
+
+    pub fn view_mut(
+        &mut self,
+        origin: GridCoordinate,
+        width: usize,
+        height: usize,
+    ) -> Option<GridViewMut<'_, T>> {
+        if !window_fits(origin, width, height, self.get_width(), self.get_height()) {
+            return None;
+        }
+        return Some(GridViewMut {
+            grid: self,
+            origin: origin,
+            width: width,
+            height: height,
+        });
+    }
+
+    /// Smallest rectangle holding every cell that is_content is true for.
+    pub fn content_bounds<F: Fn(T) -> bool>(
+        &self,
+        is_content: F,
+    ) -> Option<Rectangle<GridCoordinate>> {
+        let mut bounds: Option<Rectangle<GridCoordinate>> = None;
+        for pos in self.coord_iter() {
+            if is_content(self.get_value(pos).unwrap()) {
+                let cell = Rectangle::from_corners(pos, pos);
+                bounds = Some(bounds.map_or(cell, |b| b.union(&cell)));
+            }
+        }
+        return bounds;
+    }
+
+    /// View of just the content, None if there isn't any.
+    pub fn crop_to_content<F: Fn(T) -> bool>(&self, is_content: F) -> Option<GridView<'_, T>> {
+        let bounds = self.content_bounds(is_content)?;
+        return self.view(bounds.top_left, bounds.get_width(), bounds.get_height());
+    }
+
+    /// New grid with a border of value, border cells wide, all the way round.
+    pub fn pad(&self, border: usize, value: T) -> Grid<T> {
+        let width = self.get_width() + 2 * border;
+        let height = self.get_height() + 2 * border;
+        let mut result = Grid::new(width, height, vec![value; width * height]);
+        result.blit(self, GridCoordinate::new(border, border));
+        return result;
+    }
+
+    /// Whether all of other lands on this grid when its top left is at.
+    pub fn fits_at<U: Copy>(&self, other: &Grid<U>, at: GridCoordinate) -> bool {
+        return window_fits(
+            at,
+            other.get_width(),
+            other.get_height(),
+            self.get_width(),
+            self.get_height(),
+        );
+    }
+
+    // Columns and rows of other that land on this grid with its top left at at, (0, 0) if none do.
+    fn overlap_size<U: Copy>(&self, other: &Grid<U>, at: GridCoordinate) -> (usize, usize) {
+        let columns = other.get_width().min(self.get_width().saturating_sub(at.x));
+        let rows = other
+            .get_height()
+            .min(self.get_height().saturating_sub(at.y));
+        if columns == 0 || rows == 0 {
+            // Wholly off an edge, so at may be past the end of every row
+            return (0, 0);
+        }
+        return (columns, rows);
+    }
+
+    /// Whether any cell of other at at lands on a cell it collides with.
+    ///
+    /// Parts of other off the grid are ignored, check `fits_at` as well when stamping.
+    pub fn collides_at<U: Copy, F: Fn(T, U) -> bool>(
+        &self,
+        other: &Grid<U>,
+        at: GridCoordinate,
+        collides: F,
+    ) -> bool {
+        let (columns, rows) = self.overlap_size(other, at);
+        for y in 0..rows {
+            let mine = &self.row(at.y + y)[at.x..at.x + columns];
+            let theirs = &other.row(y)[..columns];
+            for x in 0..columns {
+                if collides(mine[x], theirs[x]) {
+                    return true;
+                }
+            }
+        }
+        return false;
+    }
+
+    /// Copy other over this grid with its top left at at, clipping anything off the edge.
+    pub fn blit(&mut self, other: &Grid<T>, at: GridCoordinate) {
+        let (columns, rows) = self.overlap_size(other, at);
+        for y in 0..rows {
+            self.row_mut(at.y + y)[at.x..at.x + columns].copy_from_slice(&other.row(y)[..columns]);
+        }
+    }
+
+    /// Same as `blit`, but only copies the cells of other that mask is true for.
+    pub fn blit_masked<F: Fn(T) -> bool>(&mut self, other: &Grid<T>, at: GridCoordinate, mask: F) {
+        let (columns, rows) = self.overlap_size(other, at);
+        for y in 0..rows {
+            let theirs = &other.row(y)[..columns];
+            let mine = &mut self.row_mut(at.y + y)[at.x..at.x + columns];
+            for x in 0..columns {
+                if mask(theirs[x]) {
+                    mine[x] = theirs[x];
+                }
+            }
+        }
+    }
+
+    /// Set value wherever mask is true for the cell of other, for stamping and unstamping shapes.
+    pub fn fill_masked<U: Copy, F: Fn(U) -> bool>(
+        &mut self,
+        other: &Grid<U>,
+        at: GridCoordinate,
+        mask: F,
+        value: T,
+    ) {
+        let (columns, rows) = self.overlap_size(other, at);
+        for y in 0..rows {
+            let theirs = &other.row(y)[..columns];
+            let mine = &mut self.row_mut(at.y + y)[at.x..at.x + columns];
+            for x in 0..columns {
+                if mask(theirs[x]) {
+                    mine[x] = value;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search::bfs;
+    use crate::search::Adjacency;
+
+    fn produce_grid() -> Grid<char> {
+        return Grid::parse(".....\n.#...\n.##..\n...#.\n.....").unwrap();
+    }
+
+    #[test]
+    fn test_view() {
+        let grid = produce_grid();
+        assert!(grid.view(GridCoordinate::new(3, 3), 3, 1).is_none());
+        // origin + size would wrap around to something small
+        assert!(grid
+            .view(GridCoordinate::new(1, 1), usize::MAX, 1)
+            .is_none());
+        assert!(grid
+            .view(GridCoordinate::new(1, 1), 1, usize::MAX)
+            .is_none());
+        let view = grid.view(GridCoordinate::new(1, 1), 3, 3).unwrap();
+        assert_eq!(view.get_value(GridCoordinate::new(0, 0)), Some('#'));
+        assert_eq!(view.get_value(GridCoordinate::new(2, 2)), Some('#'));
+        assert_eq!(view.get_value(GridCoordinate::new(3, 0)), None);
+        assert_eq!(
+            view.to_parent(GridCoordinate::new(2, 2)),
+            GridCoordinate::new(3, 3)
+        );
+        assert_eq!(
+            view.get_coordinate_by_direction(GridCoordinate::new(2, 0), Direction::EAST),
+            None
+        );
+        assert_eq!(view.to_grid().grid_strings(), vec!["#..", "##.", "..#"]);
+
+        let inner = view.view(GridCoordinate::new(1, 1), 2, 2).unwrap();
+        assert_eq!(inner.get_origin(), GridCoordinate::new(2, 2));
+        assert_eq!(inner.to_grid().grid_strings(), vec!["#.", ".#"]);
+    }
+
+    #[test]
+    fn test_search_on_view() {
+        let grid = produce_grid();
+        let view = grid.view(GridCoordinate::new(1, 1), 3, 3).unwrap();
+        assert_eq!(
+            view.get_coordinate_by_direction(GridCoordinate::new(2, 0), Direction::EAST),
+            None
+        );
+        // Inside the window the diagonal wall cuts the top right off from the bottom left
+        let result = bfs(
+            &view.to_grid(),
+            GridCoordinate::new(1, 0),
+            Adjacency::Cardinal,
+            |_, to| to == '.',
+        );
+        assert!(result.contains(GridCoordinate::new(2, 1)));
+        assert!(!result.contains(GridCoordinate::new(0, 2)));
+    }
+
+    #[test]
+    fn test_view_mut() {
+        let mut grid = produce_grid();
+        {
+            let mut view = grid.view_mut(GridCoordinate::new(3, 0), 2, 2).unwrap();
+            view.set_value(GridCoordinate::new(0, 0), 'a');
+            view.set_value(GridCoordinate::new(5, 5), 'z');
+            assert_eq!(
+                view.as_view().get_value(GridCoordinate::new(0, 0)),
+                Some('a')
+            );
+            view.fill('b');
+        }
+        assert_eq!(grid.grid_strings()[0], "...bb");
+        assert_eq!(grid.grid_strings()[1], ".#.bb");
+    }
+
+    #[test]
+    fn test_crop_and_pad() {
+        let grid = produce_grid();
+        let cropped = grid.crop_to_content(|c| c == '#').unwrap();
+        assert_eq!(cropped.get_origin(), GridCoordinate::new(1, 1));
+        assert_eq!(cropped.to_grid().grid_strings(), vec!["#..", "##.", "..#"]);
+        assert!(grid.crop_to_content(|c| c == 'x').is_none());
+
+        let padded = cropped.to_grid().pad(1, ' ');
+        assert_eq!(padded.get_width(), 5);
+        assert_eq!(padded.grid_strings()[0], "     ");
+        assert_eq!(padded.grid_strings()[2], " ##. ");
+    }
+
+    #[test]
+    fn test_blit_and_collisions() {
+        let mut board = Grid::new(4, 3, vec![false; 12]);
+        let shape = Grid::new(2, 2, vec![true, true, false, true]);
+        let overlaps = |a: bool, b: bool| a && b;
+
+        assert!(board.fits_at(&shape, GridCoordinate::new(2, 1)));
+        assert!(!board.fits_at(&shape, GridCoordinate::new(3, 1)));
+        assert!(!board.collides_at(&shape, GridCoordinate::new(0, 0), overlaps));
+
+        board.fill_masked(&shape, GridCoordinate::new(0, 0), |v| v, true);
+        assert!(board.collides_at(&shape, GridCoordinate::new(1, 0), overlaps));
+        assert!(!board.collides_at(&shape, GridCoordinate::new(2, 0), overlaps));
+        board.fill_masked(&shape, GridCoordinate::new(0, 0), |v| v, false);
+        assert_eq!(board, Grid::new(4, 3, vec![false; 12]));
+
+        let mut canvas = Grid::new(3, 2, vec!['.'; 6]);
+        let stamp = Grid::new(2, 2, vec!['a', ' ', 'b', 'c']);
+        canvas.blit_masked(&stamp, GridCoordinate::new(0, 0), |c| c != ' ');
+        canvas.blit(&stamp, GridCoordinate::new(2, 1));
+        assert_eq!(canvas.grid_strings(), vec!["a..", "bca"]);
+    }
+
+    #[test]
+    fn test_stamp_off_edge() {
+        // Wholly past the right or bottom edge is ignored, not a panic
+        let mut board = Grid::new(4, 3, vec![false; 12]);
+        let shape = Grid::new(2, 2, vec![true; 4]);
+        for at in [GridCoordinate::new(6, 0), GridCoordinate::new(0, 5)] {
+            assert!(!board.fits_at(&shape, at));
+            assert!(!board.collides_at(&shape, at, |_, _| true));
+            board.blit(&shape, at);
+            board.blit_masked(&shape, at, |v| v);
+            board.fill_masked(&shape, at, |v| v, true);
+        }
+        assert_eq!(board, Grid::new(4, 3, vec![false; 12]));
+    }
+}
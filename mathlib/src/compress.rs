@@ -0,0 +1,225 @@
+extern crate num_traits;
+
+use crate::number_theory::to_i128;
+
+use num_traits::PrimInt;
+
+// high - low for low <= high, which can be wider than T or i128.
+fn distance<T: PrimInt>(low: T, high: T) -> u128 {
+    if low >= T::zero() {
+        return high.to_u128().unwrap() - low.to_u128().unwrap();
+    }
+    if high < T::zero() {
+        return (to_i128(high) - to_i128(low)) as u128;
+    }
+    return high.to_u128().unwrap() + to_i128(low).unsigned_abs();
+}
+
+/// Maps the distinct values used to dense indices and back, for coordinate compression.
+///
+/// With gaps, every stretch strictly between two neighbouring values gets a cell of its own,
+/// so value i is at index 2 * i and the gap after it at 2 * i + 1.
+/// ```
+/// let xs = mathlib::CoordinateCompressor::with_gaps(vec![100, 7, 40, 7]);
+/// assert_eq!(xs.values(), &[7, 40, 100]);
+/// assert_eq!(xs.len(), 5);
+/// assert_eq!(xs.index_of(40), Some(2));
+/// // 41 through 99 are squashed into one cell
+/// assert_eq!(xs.index_containing(60), Some(3));
+/// assert_eq!(xs.weight(3), 59);
+/// assert_eq!(xs.cell_range(3), Some((41, 99)));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CoordinateCompressor<T: PrimInt> {
+    values: Vec<T>,
+    gaps: bool,
+}
+
+impl<T: PrimInt> CoordinateCompressor<T> {
+    /// Only the values themselves get indices.
+    pub fn new<I: IntoIterator<Item = T>>(values: I) -> CoordinateCompressor<T> {
+        return CoordinateCompressor::build(values, false);
+    }
+
+    /// Values and the gaps between them get indices.
+    pub fn with_gaps<I: IntoIterator<Item = T>>(values: I) -> CoordinateCompressor<T> {
+        return CoordinateCompressor::build(values, true);
+    }
+
+    fn build<I: IntoIterator<Item = T>>(values: I, gaps: bool) -> CoordinateCompressor<T> {
+        let mut values: Vec<T> = values.into_iter().collect();
+        values.sort();
+        values.dedup();
+        return CoordinateCompressor {
+            values: values,
+            gaps: gaps,
+        };
+    }
+
+    /// The distinct values, in order.
+    pub fn values(&self) -> &[T] {
+        return &self.values;
+    }
+
+    pub fn has_gaps(&self) -> bool {
+        return self.gaps;
+    }
+
+    /// How many cells there are, counting gaps if there are any.
+    pub fn len(&self) -> usize {
+        if self.gaps && !self.values.is_empty() {
+            return 2 * self.values.len() - 1;
+        }
+        return self.values.len();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.values.is_empty();
+    }
+
+    /// The index of a value that was compressed, None for anything else.
+    /// ```
+    /// let xs = mathlib::CoordinateCompressor::new(vec![5, 1, 9]);
+    /// assert_eq!(xs.index_of(9), Some(2));
+    /// assert_eq!(xs.index_of(6), None);
+    /// ```
+    pub fn index_of(&self, v: T) -> Option<usize> {
+        let i = self.values.binary_search(&v).ok()?;
+        return Some(if self.gaps { 2 * i } else { i });
+    }
+
+    /// The cell v lands in, its gap if it falls between values. None outside the values,
+    /// or between them when there are no gap cells.
+    pub fn index_containing(&self, v: T) -> Option<usize> {
+        return match self.values.binary_search(&v) {
+            Ok(i) => Some(if self.gaps { 2 * i } else { i }),
+            Err(i) if self.gaps && i > 0 && i < self.values.len() => Some(2 * i - 1),
+            Err(_) => None,
+        };
+    }
+
+    /// The value at a value cell, None for a gap or past the end.
+    /// ```
+    /// let xs = mathlib::CoordinateCompressor::with_gaps(vec![5, 1, 9]);
+    /// assert_eq!(xs.value_at(2), Some(5));
+    /// assert_eq!(xs.value_at(1), None);
+    /// ```
+    pub fn value_at(&self, index: usize) -> Option<T> {
+        if !self.gaps {
+            return self.values.get(index).copied();
+        }
+        if index % 2 == 1 {
+            return None;
+        }
+        return self.values.get(index / 2).copied();
+    }
+
+    /// The real values a cell stands for, both ends included. None for a gap with nothing
+    /// in it, like the one between 3 and 4.
+    pub fn cell_range(&self, index: usize) -> Option<(T, T)> {
+        if index >= self.len() {
+            return None;
+        }
+        if let Some(v) = self.value_at(index) {
+            return Some((v, v));
+        }
+        let (before, after) = (self.values[index / 2], self.values[index / 2 + 1]);
+        // before is below after, so this can't overflow
+        if before + T::one() == after {
+            return None;
+        }
+        return Some((before + T::one(), after - T::one()));
+    }
+
+    /// How many real values a cell stands for, 0 for an empty gap or past the end.
+    pub fn weight(&self, index: usize) -> u128 {
+        return match self.cell_range(index) {
+            // A gap is at least two short of the whole type, so the + 1 fits
+            Some((low, high)) => distance(low, high) + 1,
+            None => 0,
+        };
+    }
+
+    /// The real width of a run of cells, both ends included.
+    ///
+    /// Panics if that doesn't fit in a u128, which only 128 bit values can reach.
+    /// ```
+    /// let xs = mathlib::CoordinateCompressor::with_gaps(vec![0, 10, 12]);
+    /// // 0, 1..=9, 10, 11 and 12
+    /// assert_eq!(xs.span_weight(0, 4), 13);
+    /// assert_eq!(xs.span_weight(1, 2), 10);
+    /// ```
+    pub fn span_weight(&self, first: usize, last: usize) -> u128 {
+        return (first..=last)
+            .try_fold(0u128, |acc, i| acc.checked_add(self.weight(i)))
+            .expect("Width should fit in a u128");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn without_gaps() {
+        let xs = CoordinateCompressor::new(vec![30i64, -10, 20, -10]);
+        assert_eq!(xs.len(), 3);
+        assert_eq!(xs.index_containing(0), None);
+        assert_eq!(xs.value_at(0), Some(-10));
+        assert_eq!(xs.cell_range(2), Some((30, 30)));
+        assert_eq!(xs.span_weight(0, 2), 3);
+    }
+
+    #[test]
+    fn gaps_round_trip() {
+        let xs = CoordinateCompressor::with_gaps(vec![3u32, 4, 10, 0]);
+        assert_eq!(xs.len(), 7);
+        // Every real value from the first to the last lands in a cell that covers it
+        for v in 0..=10 {
+            let i = xs.index_containing(v).unwrap();
+            let (low, high) = xs.cell_range(i).unwrap();
+            assert!(low <= v && v <= high, "{}", v);
+        }
+        assert_eq!(xs.index_containing(11), None);
+        // Nothing between 3 and 4
+        assert_eq!(xs.cell_range(3), None);
+        assert_eq!(xs.weight(3), 0);
+        assert_eq!(xs.span_weight(0, xs.len() - 1), 11);
+    }
+
+    #[test]
+    fn widest_gap() {
+        let xs = CoordinateCompressor::with_gaps(vec![i32::MIN, i32::MAX]);
+        assert_eq!(xs.cell_range(1), Some((i32::MIN + 1, i32::MAX - 1)));
+        assert_eq!(xs.weight(1), (1 << 32) - 2);
+        assert_eq!(xs.span_weight(0, 2), 1 << 32);
+
+        // Gaps as wide as 128 bit types allow
+        let xs = CoordinateCompressor::with_gaps(vec![i128::MIN, i128::MAX]);
+        assert_eq!(xs.cell_range(1), Some((i128::MIN + 1, i128::MAX - 1)));
+        assert_eq!(xs.weight(1), u128::MAX - 1);
+        let xs = CoordinateCompressor::with_gaps(vec![0, u128::MAX]);
+        assert_eq!(xs.weight(1), u128::MAX - 1);
+        assert_eq!(xs.span_weight(0, 1), u128::MAX);
+        let xs = CoordinateCompressor::with_gaps(vec![-1i128, 0]);
+        assert_eq!(xs.cell_range(1), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Width should fit in a u128")]
+    fn span_past_u128() {
+        let xs = CoordinateCompressor::with_gaps(vec![0, u128::MAX]);
+        xs.span_weight(0, 2);
+    }
+
+    #[test]
+    fn empty_and_single() {
+        let empty: CoordinateCompressor<i32> = CoordinateCompressor::with_gaps(vec![]);
+        assert!(empty.is_empty());
+        assert_eq!(empty.len(), 0);
+        assert_eq!(empty.cell_range(0), None);
+        let single = CoordinateCompressor::with_gaps(vec![5]);
+        assert_eq!(single.len(), 1);
+        assert_eq!(single.index_containing(5), Some(0));
+    }
+}
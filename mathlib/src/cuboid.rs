@@ -0,0 +1,296 @@
+extern crate num_traits;
+
+use crate::distance::Point;
+use crate::number_theory::to_i128;
+use crate::range_set::RangeSet;
+
+use num_traits::PrimInt;
+
+/// An axis aligned box in N dimensions, both corners included, like the ranges in a `RangeSet`.
+/// ```
+/// use mathlib::Cuboid;
+/// let a = Cuboid::new([0, 0, 0], [9, 9, 9]);
+/// let b = Cuboid::from_corners(&(5, 12, 5), &(14, 5, 14));
+/// assert_eq!(a.volume(), 1000);
+/// assert_eq!(a.intersection(&b), Some(Cuboid::new([5, 5, 5], [9, 9, 9])));
+/// assert_eq!(b.volume(), 800);
+/// assert_eq!(mathlib::union_volume(&[a, b]), 1000 + 800 - 125);
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Cuboid<T: PrimInt, const N: usize> {
+    pub min: [T; N],
+    pub max: [T; N],
+}
+
+impl<T: PrimInt, const N: usize> Cuboid<T, N> {
+    pub fn new(min: [T; N], max: [T; N]) -> Cuboid<T, N> {
+        for axis in 0..N {
+            assert!(min[axis] <= max[axis], "Min corner must not be past max");
+        }
+        return Cuboid { min: min, max: max };
+    }
+
+    /// The box with any two opposite corners, in any order.
+    /// ```
+    /// let rect = mathlib::Cuboid::from_corners(&(7, 1), &(2, 3));
+    /// assert_eq!(rect, mathlib::Cuboid::new([2, 1], [7, 3]));
+    /// ```
+    pub fn from_corners<P: Point<T, N>>(a: &P, b: &P) -> Cuboid<T, N> {
+        let (a, b) = (a.coordinates(), b.coordinates());
+        let mut min = a;
+        let mut max = b;
+        for axis in 0..N {
+            min[axis] = a[axis].min(b[axis]);
+            max[axis] = a[axis].max(b[axis]);
+        }
+        return Cuboid { min: min, max: max };
+    }
+
+    /// The min and max on one axis, as a `RangeSet` range.
+    pub fn axis_range(&self, axis: usize) -> (T, T) {
+        return (self.min[axis], self.max[axis]);
+    }
+
+    /// How many cells across on one axis.
+    /// ```
+    /// let rect = mathlib::Cuboid::new([2, 1], [7, 3]);
+    /// assert_eq!(rect.side_length(0), 6);
+    /// ```
+    pub fn side_length(&self, axis: usize) -> u128 {
+        return (to_i128(self.max[axis]) - to_i128(self.min[axis])) as u128 + 1;
+    }
+
+    /// How many cells are inside, edges included.
+    ///
+    /// Panics if that doesn't fit in a u128, like a full range `Cuboid<u64, 2>`. Use
+    /// `checked_volume` when the sides can be that long.
+    pub fn volume(&self) -> u128 {
+        return self.checked_volume().expect("Volume should fit in a u128");
+    }
+
+    /// Same as `volume`, None if it doesn't fit in a u128.
+    /// ```
+    /// let square = mathlib::Cuboid::new([0u64, 0], [u64::MAX, u64::MAX]);
+    /// assert_eq!(square.checked_volume(), None);
+    /// let strip = mathlib::Cuboid::new([0u64, 0], [u64::MAX, 0]);
+    /// assert_eq!(strip.checked_volume(), Some(1 << 64));
+    /// ```
+    pub fn checked_volume(&self) -> Option<u128> {
+        return (0..N).try_fold(1u128, |acc, axis| acc.checked_mul(self.side_length(axis)));
+    }
+
+    /// If the point is inside or on the edge.
+    /// ```
+    /// let rect = mathlib::Cuboid::new([2, 1], [7, 3]);
+    /// assert!(rect.contains_point(&(7, 3)));
+    /// assert!(!rect.contains_point(&(8, 3)));
+    /// ```
+    pub fn contains_point<P: Point<T, N>>(&self, p: &P) -> bool {
+        let p = p.coordinates();
+        return (0..N).all(|axis| self.min[axis] <= p[axis] && p[axis] <= self.max[axis]);
+    }
+
+    /// If all of other is inside this box.
+    /// ```
+    /// let outer = mathlib::Cuboid::new([0, 0], [10, 10]);
+    /// assert!(outer.contains(&mathlib::Cuboid::new([0, 3], [10, 4])));
+    /// assert!(!outer.contains(&mathlib::Cuboid::new([-1, 3], [10, 4])));
+    /// ```
+    pub fn contains(&self, other: &Cuboid<T, N>) -> bool {
+        return (0..N)
+            .all(|axis| self.min[axis] <= other.min[axis] && other.max[axis] <= self.max[axis]);
+    }
+
+    /// If the boxes share at least one cell, touching edges count.
+    pub fn intersects(&self, other: &Cuboid<T, N>) -> bool {
+        return (0..N)
+            .all(|axis| self.min[axis] <= other.max[axis] && other.min[axis] <= self.max[axis]);
+    }
+
+    /// The cells in both boxes.
+    pub fn intersection(&self, other: &Cuboid<T, N>) -> Option<Cuboid<T, N>> {
+        if !self.intersects(other) {
+            return None;
+        }
+        let mut min = self.min;
+        let mut max = self.max;
+        for axis in 0..N {
+            min[axis] = self.min[axis].max(other.min[axis]);
+            max[axis] = self.max[axis].min(other.max[axis]);
+        }
+        return Some(Cuboid { min: min, max: max });
+    }
+
+    /// This box with other cut out of it, as at most 2 * N boxes that don't overlap.
+    /// ```
+    /// let square = mathlib::Cuboid::new([0, 0], [2, 2]);
+    /// let pieces = square.subtract(&mathlib::Cuboid::new([1, 1], [1, 1]));
+    /// assert_eq!(pieces.len(), 4);
+    /// assert_eq!(pieces.iter().map(|p| p.volume()).sum::<u128>(), 8);
+    /// ```
+    pub fn subtract(&self, other: &Cuboid<T, N>) -> Vec<Cuboid<T, N>> {
+        let cut = match self.intersection(other) {
+            Some(cut) => cut,
+            None => return vec![*self],
+        };
+        let mut pieces = vec![];
+        // Slice off whatever is outside the cut one axis at a time, shrinking what's left
+        let mut rest = *self;
+        for axis in 0..N {
+            if rest.min[axis] < cut.min[axis] {
+                let mut piece = rest;
+                piece.max[axis] = cut.min[axis] - T::one();
+                pieces.push(piece);
+                rest.min[axis] = cut.min[axis];
+            }
+            if rest.max[axis] > cut.max[axis] {
+                let mut piece = rest;
+                piece.min[axis] = cut.max[axis] + T::one();
+                pieces.push(piece);
+                rest.max[axis] = cut.max[axis];
+            }
+        }
+        return pieces;
+    }
+}
+
+// Boxes as half open i128 spans, so widths are just differences. None if the volume overflows.
+fn sweep_volume(boxes: &[Vec<(i128, i128)>], axis: usize) -> Option<u128> {
+    if boxes.is_empty() {
+        return Some(0);
+    }
+    if axis == boxes[0].len() - 1 {
+        let covered: RangeSet<i128> = boxes.iter().map(|b| (b[axis].0, b[axis].1 - 1)).collect();
+        return Some(covered.covered_length());
+    }
+    let mut edges: Vec<i128> = boxes.iter().flat_map(|b| [b[axis].0, b[axis].1]).collect();
+    edges.sort();
+    edges.dedup();
+
+    let mut total = 0;
+    for slab in edges.windows(2) {
+        // Everything covering this whole slab looks the same across it
+        let active: Vec<Vec<(i128, i128)>> = boxes
+            .iter()
+            .filter(|b| b[axis].0 <= slab[0] && slab[1] <= b[axis].1)
+            .cloned()
+            .collect();
+        let slab_volume =
+            ((slab[1] - slab[0]) as u128).checked_mul(sweep_volume(&active, axis + 1)?)?;
+        total = slab_volume.checked_add(total)?;
+    }
+    return Some(total);
+}
+
+/// How many cells are in at least one of the boxes, sweeping across one axis at a time.
+///
+/// Panics if that doesn't fit in a u128, the same as `Cuboid::volume`.
+/// ```
+/// use mathlib::Cuboid;
+/// let boxes = [
+///     Cuboid::new([0, 0], [3, 3]),
+///     Cuboid::new([2, 2], [5, 5]),
+///     Cuboid::new([10, 10], [10, 10]),
+/// ];
+/// assert_eq!(mathlib::union_volume(&boxes), 16 + 16 - 4 + 1);
+/// ```
+pub fn union_volume<T: PrimInt, const N: usize>(boxes: &[Cuboid<T, N>]) -> u128 {
+    if N == 0 {
+        return if boxes.is_empty() { 0 } else { 1 };
+    }
+    let spans: Vec<Vec<(i128, i128)>> = boxes
+        .iter()
+        .map(|b| {
+            (0..N)
+                .map(|axis| (to_i128(b.min[axis]), to_i128(b.max[axis]) + 1))
+                .collect()
+        })
+        .collect();
+    return sweep_volume(&spans, 0).expect("Volume should fit in a u128");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Small boxes from a simple linear congruential generator, so the test is repeatable.
+    fn some_boxes(count: usize, seed: u64) -> Vec<Cuboid<i32, 3>> {
+        let mut state = seed;
+        let mut next = move |limit: i32| {
+            state = state
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            return ((state >> 33) % limit as u64) as i32;
+        };
+        let mut result = vec![];
+        for _ in 0..count {
+            let a = [next(12) - 4, next(12) - 4, next(12) - 4];
+            let b = [next(12) - 4, next(12) - 4, next(12) - 4];
+            result.push(Cuboid::from_corners(&a, &b));
+        }
+        return result;
+    }
+
+    #[test]
+    fn union_volume_brute_force() {
+        for seed in 0..5 {
+            let boxes = some_boxes(6, seed);
+            let mut count = 0;
+            for x in -4..8 {
+                for y in -4..8 {
+                    for z in -4..8 {
+                        if boxes.iter().any(|b| b.contains_point(&[x, y, z])) {
+                            count += 1;
+                        }
+                    }
+                }
+            }
+            assert_eq!(union_volume(&boxes), count, "seed {}", seed);
+        }
+    }
+
+    #[test]
+    fn subtract_is_disjoint() {
+        let boxes = some_boxes(10, 42);
+        for a in boxes.iter() {
+            for b in boxes.iter() {
+                let pieces = a.subtract(b);
+                let overlap = a.intersection(b).map_or(0, |c| c.volume());
+                assert_eq!(
+                    pieces.iter().map(|p| p.volume()).sum::<u128>(),
+                    a.volume() - overlap
+                );
+                assert_eq!(union_volume(&pieces), a.volume() - overlap);
+                assert!(pieces.iter().all(|p| a.contains(p) && !p.intersects(b)));
+            }
+        }
+    }
+
+    #[test]
+    fn one_dimension_matches_range_set() {
+        let boxes = [
+            Cuboid::new([1u64], [5]),
+            Cuboid::new([6], [8]),
+            Cuboid::new([20], [u64::MAX]),
+        ];
+        let set: RangeSet<u64> = boxes.iter().map(|b| b.axis_range(0)).collect();
+        assert_eq!(union_volume(&boxes), set.covered_length());
+        assert_eq!(boxes[2].volume(), (u64::MAX - 19) as u128);
+    }
+
+    #[test]
+    #[should_panic(expected = "Volume should fit in a u128")]
+    fn union_volume_too_big() {
+        let full = Cuboid::new([0u64, 0], [u64::MAX, u64::MAX]);
+        union_volume(&[full]);
+    }
+
+    #[test]
+    fn subtract_whole_and_none() {
+        let a = Cuboid::new([0, 0], [4, 4]);
+        assert!(a.subtract(&Cuboid::new([-1, -1], [5, 5])).is_empty());
+        assert_eq!(a.subtract(&Cuboid::new([5, 5], [6, 6])), vec![a]);
+        assert!(!a.intersects(&Cuboid::new([5, 0], [6, 4])));
+        assert_eq!(union_volume::<i32, 2>(&[]), 0);
+    }
+}
@@ -0,0 +1,316 @@
+// Everything here stays in integers, so values near powers of the base are exact.
+
+fn checked_base(base: u64) -> u64 {
+    assert!(base >= 2, "Base must be at least 2");
+    return base;
+}
+
+/// How many digits n has in base, 0 has one digit.
+/// ```
+/// assert_eq!(mathlib::digit_count(999_999_999_999_999_999, 10), 18);
+/// assert_eq!(mathlib::digit_count(1_000_000_000_000_000_000, 10), 19);
+/// assert_eq!(mathlib::digit_count(255, 2), 8);
+/// assert_eq!(mathlib::digit_count(0, 10), 1);
+/// ```
+pub fn digit_count(n: u64, base: u64) -> u32 {
+    let base = checked_base(base);
+    let mut count = 1;
+    let mut rest = n / base;
+    while rest > 0 {
+        rest /= base;
+        count += 1;
+    }
+    return count;
+}
+
+/// The digits of a number, most significant first.
+/// ```
+/// let digits: Vec<u64> = mathlib::Digits::new(1204, 10).collect();
+/// assert_eq!(digits, vec![1, 2, 0, 4]);
+/// let digits: Vec<u64> = mathlib::Digits::new(1204, 10).rev().collect();
+/// assert_eq!(digits, vec![4, 0, 2, 1]);
+/// let hex: Vec<u64> = mathlib::Digits::new(0xbeef, 16).collect();
+/// assert_eq!(hex, vec![11, 14, 14, 15]);
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Digits {
+    /* The digits not yet returned, from either end */
+    value: u64,
+    base: u64,
+    /* Place value of the leading digit left in value */
+    place: u64,
+    remaining: u32,
+}
+
+impl Digits {
+    pub fn new(n: u64, base: u64) -> Digits {
+        let remaining = digit_count(n, base);
+        return Digits {
+            value: n,
+            base: base,
+            place: base.pow(remaining - 1),
+            remaining: remaining,
+        };
+    }
+}
+
+impl Iterator for Digits {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<u64> {
+        if self.remaining == 0 {
+            return None;
+        }
+        let digit = self.value / self.place;
+        self.value %= self.place;
+        self.place /= self.base;
+        self.remaining -= 1;
+        return Some(digit);
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.remaining as usize;
+        return (remaining, Some(remaining));
+    }
+}
+
+impl DoubleEndedIterator for Digits {
+    fn next_back(&mut self) -> Option<u64> {
+        if self.remaining == 0 {
+            return None;
+        }
+        let digit = self.value % self.base;
+        self.value /= self.base;
+        // Dropping the last digit moves the leading one down a place
+        self.place /= self.base;
+        self.remaining -= 1;
+        return Some(digit);
+    }
+}
+
+impl ExactSizeIterator for Digits {}
+
+/// Build a number from its digits, most significant first. None if it doesn't fit in a u64.
+/// ```
+/// assert_eq!(mathlib::from_digits(&[1, 2, 0, 4], 10), Some(1204));
+/// assert_eq!(mathlib::from_digits(&[1, 0, 1], 2), Some(5));
+/// assert_eq!(mathlib::from_digits(&[], 10), Some(0));
+/// assert_eq!(mathlib::from_digits(&[9; 20], 10), None);
+/// ```
+pub fn from_digits(digits: &[u64], base: u64) -> Option<u64> {
+    let base = checked_base(base);
+    let mut result: u64 = 0;
+    for &d in digits {
+        assert!(d < base, "Digit {} is too big for base {}", d, base);
+        result = result.checked_mul(base)?.checked_add(d)?;
+    }
+    return Some(result);
+}
+
+/// Split off the last count digits, giving (front, back).
+/// ```
+/// assert_eq!(mathlib::split_digits(123456, 10, 2), (1234, 56));
+/// // Zeros at the start of the back are kept as its value
+/// assert_eq!(mathlib::split_digits(1005, 10, 2), (10, 5));
+/// ```
+pub fn split_digits(n: u64, base: u64, count: u32) -> (u64, u64) {
+    let base = checked_base(base);
+    return match base.checked_pow(count) {
+        Some(divisor) => (n / divisor, n % divisor),
+        // More digits than a u64 can have, so all of n is at the back
+        None => (0, n),
+    };
+}
+
+/// The two halves of a number with an even digit count.
+/// ```
+/// assert_eq!(mathlib::digit_halves(123123, 10), Some((123, 123)));
+/// assert_eq!(mathlib::digit_halves(12312, 10), None);
+/// ```
+pub fn digit_halves(n: u64, base: u64) -> Option<(u64, u64)> {
+    let count = digit_count(n, base);
+    if !count.is_multiple_of(2) {
+        return None;
+    }
+    return Some(split_digits(n, base, count / 2));
+}
+
+/// Cut a number into blocks of block_len digits, first block first, if the digit count allows it.
+/// ```
+/// assert_eq!(mathlib::digit_blocks(123456, 10, 2), Some(vec![12, 34, 56]));
+/// assert_eq!(mathlib::digit_blocks(101010, 10, 2), Some(vec![10, 10, 10]));
+/// assert_eq!(mathlib::digit_blocks(12345, 10, 2), None);
+/// ```
+pub fn digit_blocks(n: u64, base: u64, block_len: u32) -> Option<Vec<u64>> {
+    assert!(block_len > 0, "Blocks need at least one digit");
+    let count = digit_count(n, base);
+    if !count.is_multiple_of(block_len) {
+        return None;
+    }
+    let mut blocks = Vec::with_capacity((count / block_len) as usize);
+    let mut rest = n;
+    for _ in 0..count / block_len {
+        let (front, back) = split_digits(rest, base, block_len);
+        blocks.push(back);
+        rest = front;
+    }
+    blocks.reverse();
+    return Some(blocks);
+}
+
+/// base^(block_len * 0) + ... + base^(block_len * (blocks - 1)), so seed times this repeats seed.
+///
+/// None if it doesn't fit in a u64.
+/// ```
+/// assert_eq!(mathlib::repeat_multiplier(3, 3, 10), Some(1001001));
+/// assert_eq!(mathlib::repeat_multiplier(3, 3, 10).unwrap() * 123, 123123123);
+/// assert_eq!(mathlib::repeat_multiplier(1, 21, 10), None);
+/// ```
+pub fn repeat_multiplier(block_len: u32, blocks: u32, base: u64) -> Option<u64> {
+    let base = checked_base(base);
+    let shift = base.checked_pow(block_len)?;
+    let mut result: u64 = 0;
+    for _ in 0..blocks {
+        result = result.checked_mul(shift)?.checked_add(1)?;
+    }
+    return Some(result);
+}
+
+/// The number made of len ones, None if it doesn't fit in a u64.
+/// ```
+/// assert_eq!(mathlib::repunit(4, 10), Some(1111));
+/// assert_eq!(mathlib::repunit(4, 2), Some(15));
+/// assert_eq!(mathlib::repunit(20, 10), Some(11_111_111_111_111_111_111));
+/// assert_eq!(mathlib::repunit(21, 10), None);
+/// ```
+pub fn repunit(len: u32, base: u64) -> Option<u64> {
+    return repeat_multiplier(1, len, base);
+}
+
+/// The shortest block length that repeated makes up all of n, the digit count if nothing shorter does.
+/// ```
+/// assert_eq!(mathlib::digit_period(123123123, 10), 3);
+/// assert_eq!(mathlib::digit_period(1111, 10), 1);
+/// assert_eq!(mathlib::digit_period(1212121, 10), 7);
+/// ```
+pub fn digit_period(n: u64, base: u64) -> u32 {
+    let count = digit_count(n, base);
+    for block_len in 1..count {
+        if !count.is_multiple_of(block_len) {
+            continue;
+        }
+        // n is periodic exactly when it is its first block times the multiplier
+        // and one too big for a u64 is bigger than n.
+        let Some(multiplier) = repeat_multiplier(block_len, count / block_len, base) else {
+            continue;
+        };
+        if n.is_multiple_of(multiplier) {
+            return block_len;
+        }
+    }
+    return count;
+}
+
+/// If n is some block of digits repeated at least twice.
+/// ```
+/// assert!(mathlib::is_digit_periodic(565656, 10));
+/// assert!(!mathlib::is_digit_periodic(5656565, 10));
+/// assert!(!mathlib::is_digit_periodic(7, 10));
+/// ```
+pub fn is_digit_periodic(n: u64, base: u64) -> bool {
+    return digit_period(n, base) < digit_count(n, base);
+}
+
+/// The digits of n in the other order, zeros that end up in front are dropped.
+///
+/// None if the reversed number doesn't fit in a u64.
+/// ```
+/// assert_eq!(mathlib::reverse_digits(1230, 10), Some(321));
+/// assert_eq!(mathlib::reverse_digits(0b1101, 2), Some(0b1011));
+/// assert_eq!(mathlib::reverse_digits(10_000_000_000_000_000_009, 10), None);
+/// ```
+pub fn reverse_digits(n: u64, base: u64) -> Option<u64> {
+    let base = checked_base(base);
+    let mut rest = n;
+    // u128 as a small last digit can reverse into a leading digit that overflows
+    let mut result: u128 = 0;
+    while rest > 0 {
+        result = result * base as u128 + (rest % base) as u128;
+        rest /= base;
+    }
+    return u64::try_from(result).ok();
+}
+
+/// If n reads the same both ways in base.
+/// ```
+/// assert!(mathlib::is_palindrome(12321, 10));
+/// assert!(!mathlib::is_palindrome(1232, 10));
+/// assert!(mathlib::is_palindrome(0b10101, 2));
+/// ```
+pub fn is_palindrome(n: u64, base: u64) -> bool {
+    let mut digits = Digits::new(n, base);
+    while let (Some(front), Some(back)) = (digits.next(), digits.next_back()) {
+        if front != back {
+            return false;
+        }
+    }
+    return true;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn digit_count_powers_of_ten() {
+        // Floating point log10 gets some of these wrong
+        for power in 1..20 {
+            let n = 10u64.pow(power);
+            assert_eq!(digit_count(n - 1, 10), power);
+            assert_eq!(digit_count(n, 10), power + 1);
+        }
+        assert_eq!(digit_count(u64::MAX, 10), 20);
+        assert_eq!(digit_count(u64::MAX, 2), 64);
+    }
+
+    #[test]
+    fn digits_both_ends() {
+        let mut digits = Digits::new(12345, 10);
+        assert_eq!(digits.len(), 5);
+        assert_eq!(digits.next(), Some(1));
+        assert_eq!(digits.next_back(), Some(5));
+        assert_eq!(digits.next(), Some(2));
+        assert_eq!(digits.next_back(), Some(4));
+        assert_eq!(digits.next(), Some(3));
+        assert_eq!(digits.next_back(), None);
+        let zero: Vec<u64> = Digits::new(0, 10).collect();
+        assert_eq!(zero, vec![0]);
+        let max: Vec<u64> = Digits::new(u64::MAX, 10).collect();
+        assert_eq!(from_digits(&max, 10), Some(u64::MAX));
+    }
+
+    #[test]
+    fn period_matches_rotation() {
+        // A string is periodic exactly when it shows up inside itself doubled, less the ends
+        for n in 1..20000u64 {
+            let s = n.to_string();
+            let doubled = format!("{}{}", s, s);
+            let rotation = doubled[1..doubled.len() - 1].contains(&s);
+            assert_eq!(is_digit_periodic(n, 10), rotation, "{}", n);
+        }
+    }
+
+    #[test]
+    fn split_digits_large() {
+        assert_eq!(split_digits(u64::MAX, 10, 30), (0, u64::MAX));
+        assert_eq!(split_digits(42, 10, 0), (42, 0));
+        assert_eq!(digit_halves(0, 10), None);
+    }
+
+    #[test]
+    fn palindrome_single_digit() {
+        assert!(is_palindrome(0, 10));
+        assert!(is_palindrome(7, 10));
+        assert_eq!(reverse_digits(0, 10), Some(0));
+    }
+}
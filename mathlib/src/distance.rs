@@ -28,3 +28,69 @@ pub fn euclidean_distance<T: Signed + Copy + ToPrimitive>(x1: T, y1: T, x2: T, y
     let squared = euclidean_distance_squared(x1, y1, x2, y2);
     return f64::sqrt(squared.to_f64().unwrap());
 }
+
+/// Anything with N coordinates, so the `_nd` distances work in any number of dimensions.
+pub trait Point<T, const N: usize> {
+    fn coordinates(&self) -> [T; N];
+}
+
+impl<T: Copy, const N: usize> Point<T, N> for [T; N] {
+    fn coordinates(&self) -> [T; N] {
+        return *self;
+    }
+}
+
+impl<T: Copy> Point<T, 2> for (T, T) {
+    fn coordinates(&self) -> [T; 2] {
+        return [self.0, self.1];
+    }
+}
+
+impl<T: Copy> Point<T, 3> for (T, T, T) {
+    fn coordinates(&self) -> [T; 3] {
+        return [self.0, self.1, self.2];
+    }
+}
+
+/// Manhattan distance between two points of any dimension
+/// ```
+/// assert_eq!(mathlib::manhattan_distance_nd(&(1, 2, 3), &(4, 0, 3)), 5);
+/// ```
+pub fn manhattan_distance_nd<T: Signed + Copy, P: Point<T, N>, const N: usize>(a: &P, b: &P) -> T {
+    let a = a.coordinates();
+    let b = b.coordinates();
+    return (0..N).fold(T::zero(), |acc, i| acc + (a[i] - b[i]).abs());
+}
+
+/// Euclidean distance squared between two points of any dimension
+/// ```
+/// assert_eq!(mathlib::euclidean_distance_squared_nd(&[1, 2, 3], &[4, 0, 3]), 13);
+/// ```
+pub fn euclidean_distance_squared_nd<T: Signed + Copy, P: Point<T, N>, const N: usize>(
+    a: &P,
+    b: &P,
+) -> T {
+    let a = a.coordinates();
+    let b = b.coordinates();
+    return (0..N).fold(T::zero(), |acc, i| acc + (a[i] - b[i]) * (a[i] - b[i]));
+}
+
+/// Euclidean distance between two points of any dimension, stored in an f64.
+///
+/// Each difference is made an f64 before squaring, so big integer coordinates can't overflow.
+/// ```
+/// assert_eq!(mathlib::euclidean_distance_nd(&(0, 3, 0), &(4, 0, 0)), 5.0);
+/// assert_eq!(mathlib::euclidean_distance_nd(&(0i32, 0), &(300_000, 400_000)), 500_000.0);
+/// ```
+pub fn euclidean_distance_nd<T: Signed + Copy + ToPrimitive, P: Point<T, N>, const N: usize>(
+    a: &P,
+    b: &P,
+) -> f64 {
+    let a = a.coordinates();
+    let b = b.coordinates();
+    let squared = (0..N).fold(0.0, |acc, i| {
+        let d = (a[i] - b[i]).to_f64().unwrap();
+        return acc + d * d;
+    });
+    return f64::sqrt(squared);
+}
@@ -5,13 +5,47 @@ mod interpolation;
 pub use crate::interpolation::{lerp, remap};
 
 mod multiples;
-pub use crate::multiples::{gcd, lcm};
+pub use crate::multiples::{gcd, lcm, lcm_iter};
 
 mod polygon;
-pub use crate::polygon::{picks_theorem_i, shoelace_area, shoepick, shoepick_intlengths};
+pub use crate::polygon::{
+    is_simple_polygon, picks_theorem_i, point_in_polygon, polygon_orientation,
+    rect_in_rectilinear_polygon, segment_crosses_rect_interior, shoelace_area, shoepick,
+    shoepick_intlengths, Orientation, PointLocation,
+};
 
 mod distance;
-pub use crate::distance::{euclidean_distance, euclidean_distance_squared, manhattan_distance};
+pub use crate::distance::{
+    euclidean_distance, euclidean_distance_nd, euclidean_distance_squared,
+    euclidean_distance_squared_nd, manhattan_distance, manhattan_distance_nd, Point,
+};
 
 mod lines;
 pub use crate::lines::{determinant, line_intersect};
+
+mod number_theory;
+pub use crate::number_theory::{crt, extended_gcd, mod_inverse, mod_pow, CrtError};
+
+mod modint;
+pub use crate::modint::{zero_crossings, DynModInt, ModInt};
+
+mod primes;
+pub use crate::primes::{
+    count_multiples, count_multiples_of_any, divisors, factorize, is_prime, mobius, pollard_rho,
+    primes_up_to, segmented_sieve, sum_multiples, sum_multiples_of_any, totient,
+};
+
+mod digits;
+pub use crate::digits::{
+    digit_blocks, digit_count, digit_halves, digit_period, from_digits, is_digit_periodic,
+    is_palindrome, repeat_multiplier, repunit, reverse_digits, split_digits, Digits,
+};
+
+mod range_set;
+pub use crate::range_set::RangeSet;
+
+mod cuboid;
+pub use crate::cuboid::{union_volume, Cuboid};
+
+mod compress;
+pub use crate::compress::CoordinateCompressor;
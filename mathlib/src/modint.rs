@@ -0,0 +1,413 @@
+extern crate num_traits;
+
+use crate::modulus::modulus;
+use crate::number_theory::{mod_inverse, mod_pow, to_i128};
+
+use num_traits::{PrimInt, Signed};
+use std::fmt;
+use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+
+// Any integer into 0..m.
+fn reduce(v: i128, m: u64) -> u64 {
+    assert!(m > 0, "Modulus must be positive");
+    return modulus(v, m as i128) as u64;
+}
+
+fn floor_div<T: PrimInt + Signed>(a: T, b: T) -> T {
+    return (a - modulus(a, b)) / b;
+}
+
+/// How many multiples of m a walk from start passes, counting where it ends but not where it starts.
+/// ```
+/// // A 100 position dial, turning left 68 from 50 passes 0 once
+/// assert_eq!(mathlib::zero_crossings(50, -68, 100), 1);
+/// // Landing exactly on 0 counts, leaving it doesn't
+/// assert_eq!(mathlib::zero_crossings(95, 5, 100), 1);
+/// assert_eq!(mathlib::zero_crossings(0, -5, 100), 0);
+/// assert_eq!(mathlib::zero_crossings(5, -5000, 100), 50);
+/// ```
+pub fn zero_crossings<T: PrimInt + Signed>(start: T, step: T, m: T) -> T {
+    let end = start + step;
+    if step.is_positive() {
+        return floor_div(end, m) - floor_div(start, m);
+    }
+    // Going down, the multiples in end..start, so shift both by one
+    return floor_div(start - T::one(), m) - floor_div(end - T::one(), m);
+}
+
+/// An integer mod M, always kept in 0..M.
+/// ```
+/// use mathlib::ModInt;
+/// type Dial = ModInt<100>;
+/// let dial = Dial::new(50) + Dial::new(-68);
+/// assert_eq!(dial.value(), 82);
+/// assert_eq!((ModInt::<7>::new(3) / ModInt::new(5)).value(), 2);
+/// assert_eq!(ModInt::<1_000_000_007>::new(2).pow(30).value(), 73741817);
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct ModInt<const M: u64> {
+    value: u64,
+}
+
+impl<const M: u64> ModInt<M> {
+    pub const ZERO: ModInt<M> = ModInt { value: 0 };
+
+    pub fn new<T: PrimInt>(v: T) -> ModInt<M> {
+        return ModInt {
+            value: reduce(to_i128(v), M),
+        };
+    }
+
+    pub fn value(&self) -> u64 {
+        return self.value;
+    }
+
+    pub fn modulus() -> u64 {
+        return M;
+    }
+
+    pub fn pow(&self, exponent: u64) -> ModInt<M> {
+        return ModInt {
+            value: mod_pow(self.value, exponent, M),
+        };
+    }
+
+    /// None if the value shares a factor with M, always Some for prime M apart from 0.
+    pub fn inverse(&self) -> Option<ModInt<M>> {
+        return mod_inverse(self.value as i128, M as i128).map(|v| ModInt { value: v as u64 });
+    }
+
+    /// Move by step, also giving how many times that passed 0.
+    /// ```
+    /// let (dial, zeros) = mathlib::ModInt::<100>::new(98).step_counting_zeros(-651);
+    /// assert_eq!((dial.value(), zeros), (47, 6));
+    /// ```
+    pub fn step_counting_zeros(&self, step: i64) -> (ModInt<M>, u64) {
+        let start = self.value as i128;
+        let zeros = zero_crossings(start, step as i128, M as i128);
+        return (ModInt::new(start + step as i128), zeros as u64);
+    }
+}
+
+impl<const M: u64> fmt::Display for ModInt<M> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return write!(f, "{}", self.value);
+    }
+}
+
+impl<const M: u64> From<ModInt<M>> for u64 {
+    fn from(v: ModInt<M>) -> u64 {
+        return v.value;
+    }
+}
+
+impl<const M: u64> From<i64> for ModInt<M> {
+    fn from(v: i64) -> ModInt<M> {
+        return ModInt::new(v);
+    }
+}
+
+impl<const M: u64> From<u64> for ModInt<M> {
+    fn from(v: u64) -> ModInt<M> {
+        return ModInt::new(v);
+    }
+}
+
+impl<const M: u64> Add for ModInt<M> {
+    type Output = ModInt<M>;
+
+    fn add(self, rhs: ModInt<M>) -> ModInt<M> {
+        // u128 so moduli near u64::MAX can't overflow
+        let sum = (self.value as u128 + rhs.value as u128) % M as u128;
+        return ModInt { value: sum as u64 };
+    }
+}
+
+impl<const M: u64> Sub for ModInt<M> {
+    type Output = ModInt<M>;
+
+    fn sub(self, rhs: ModInt<M>) -> ModInt<M> {
+        let difference = (self.value as u128 + M as u128 - rhs.value as u128) % M as u128;
+        return ModInt {
+            value: difference as u64,
+        };
+    }
+}
+
+impl<const M: u64> Mul for ModInt<M> {
+    type Output = ModInt<M>;
+
+    fn mul(self, rhs: ModInt<M>) -> ModInt<M> {
+        let product = (self.value as u128 * rhs.value as u128) % M as u128;
+        return ModInt {
+            value: product as u64,
+        };
+    }
+}
+
+impl<const M: u64> Div for ModInt<M> {
+    type Output = ModInt<M>;
+
+    /// Multiply by the inverse, panics if rhs has none.
+    #[allow(clippy::suspicious_arithmetic_impl)]
+    fn div(self, rhs: ModInt<M>) -> ModInt<M> {
+        return self * rhs.inverse().expect("Divisor has no inverse mod M");
+    }
+}
+
+impl<const M: u64> Neg for ModInt<M> {
+    type Output = ModInt<M>;
+
+    fn neg(self) -> ModInt<M> {
+        return ModInt::ZERO - self;
+    }
+}
+
+impl<const M: u64> AddAssign for ModInt<M> {
+    fn add_assign(&mut self, rhs: ModInt<M>) {
+        *self = *self + rhs;
+    }
+}
+
+impl<const M: u64> SubAssign for ModInt<M> {
+    fn sub_assign(&mut self, rhs: ModInt<M>) {
+        *self = *self - rhs;
+    }
+}
+
+impl<const M: u64> MulAssign for ModInt<M> {
+    fn mul_assign(&mut self, rhs: ModInt<M>) {
+        *self = *self * rhs;
+    }
+}
+
+impl<const M: u64> DivAssign for ModInt<M> {
+    fn div_assign(&mut self, rhs: ModInt<M>) {
+        *self = *self / rhs;
+    }
+}
+
+/// Same as `ModInt`, for when the modulus is only known at runtime.
+///
+/// Mixing values with different moduli panics.
+/// ```
+/// use mathlib::DynModInt;
+/// let a = DynModInt::new(10, 13);
+/// assert_eq!((a * a).value(), 9);
+/// assert_eq!((a - DynModInt::new(12, 13)).value(), 11);
+/// assert_eq!((DynModInt::new(1, 13) / a).value(), 4);
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct DynModInt {
+    value: u64,
+    modulus: u64,
+}
+
+impl DynModInt {
+    pub fn new<T: PrimInt>(v: T, modulus: u64) -> DynModInt {
+        return DynModInt {
+            value: reduce(to_i128(v), modulus),
+            modulus: modulus,
+        };
+    }
+
+    pub fn value(&self) -> u64 {
+        return self.value;
+    }
+
+    pub fn modulus(&self) -> u64 {
+        return self.modulus;
+    }
+
+    pub fn pow(&self, exponent: u64) -> DynModInt {
+        return DynModInt {
+            value: mod_pow(self.value, exponent, self.modulus),
+            modulus: self.modulus,
+        };
+    }
+
+    pub fn inverse(&self) -> Option<DynModInt> {
+        return mod_inverse(self.value as i128, self.modulus as i128).map(|v| DynModInt {
+            value: v as u64,
+            modulus: self.modulus,
+        });
+    }
+
+    pub fn step_counting_zeros(&self, step: i64) -> (DynModInt, u64) {
+        let start = self.value as i128;
+        let zeros = zero_crossings(start, step as i128, self.modulus as i128);
+        return (
+            DynModInt::new(start + step as i128, self.modulus),
+            zeros as u64,
+        );
+    }
+
+    fn with_value(&self, rhs: &DynModInt, value: u128) -> DynModInt {
+        assert_eq!(self.modulus, rhs.modulus, "Moduli don't match");
+        return DynModInt {
+            value: (value % self.modulus as u128) as u64,
+            modulus: self.modulus,
+        };
+    }
+}
+
+impl fmt::Display for DynModInt {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return write!(f, "{} (mod {})", self.value, self.modulus);
+    }
+}
+
+impl<const M: u64> From<ModInt<M>> for DynModInt {
+    fn from(v: ModInt<M>) -> DynModInt {
+        return DynModInt {
+            value: v.value,
+            modulus: M,
+        };
+    }
+}
+
+impl From<DynModInt> for u64 {
+    fn from(v: DynModInt) -> u64 {
+        return v.value;
+    }
+}
+
+impl Add for DynModInt {
+    type Output = DynModInt;
+
+    fn add(self, rhs: DynModInt) -> DynModInt {
+        return self.with_value(&rhs, self.value as u128 + rhs.value as u128);
+    }
+}
+
+impl Sub for DynModInt {
+    type Output = DynModInt;
+
+    fn sub(self, rhs: DynModInt) -> DynModInt {
+        return self.with_value(
+            &rhs,
+            self.value as u128 + self.modulus as u128 - rhs.value as u128,
+        );
+    }
+}
+
+impl Mul for DynModInt {
+    type Output = DynModInt;
+
+    fn mul(self, rhs: DynModInt) -> DynModInt {
+        return self.with_value(&rhs, self.value as u128 * rhs.value as u128);
+    }
+}
+
+impl Div for DynModInt {
+    type Output = DynModInt;
+
+    #[allow(clippy::suspicious_arithmetic_impl)]
+    fn div(self, rhs: DynModInt) -> DynModInt {
+        return self
+            * rhs
+                .inverse()
+                .expect("Divisor has no inverse for this modulus");
+    }
+}
+
+impl Neg for DynModInt {
+    type Output = DynModInt;
+
+    fn neg(self) -> DynModInt {
+        return DynModInt::new(0, self.modulus) - self;
+    }
+}
+
+impl AddAssign for DynModInt {
+    fn add_assign(&mut self, rhs: DynModInt) {
+        *self = *self + rhs;
+    }
+}
+
+impl SubAssign for DynModInt {
+    fn sub_assign(&mut self, rhs: DynModInt) {
+        *self = *self - rhs;
+    }
+}
+
+impl MulAssign for DynModInt {
+    fn mul_assign(&mut self, rhs: DynModInt) {
+        *self = *self * rhs;
+    }
+}
+
+impl DivAssign for DynModInt {
+    fn div_assign(&mut self, rhs: DynModInt) {
+        *self = *self / rhs;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn modint_wraps() {
+        let mut v = ModInt::<10>::new(7);
+        v += ModInt::new(5);
+        assert_eq!(v.value(), 2);
+        v -= ModInt::new(3);
+        assert_eq!(v.value(), 9);
+        v *= ModInt::new(3);
+        assert_eq!(v.value(), 7);
+        assert_eq!((-v).value(), 3);
+        assert_eq!(ModInt::<10>::new(-1i64), ModInt::new(9u8));
+        assert_eq!(u64::from(ModInt::<10>::from(-21i64)), 9);
+    }
+
+    #[test]
+    fn modint_huge_modulus() {
+        const BIG: u64 = u64::MAX - 58;
+        let a = ModInt::<BIG>::new(BIG - 1);
+        assert_eq!((a + a).value(), BIG - 2);
+        assert_eq!((a * a).value(), 1);
+    }
+
+    #[test]
+    fn modint_no_inverse() {
+        assert_eq!(ModInt::<12>::new(4).inverse(), None);
+        assert_eq!(ModInt::<12>::new(5).inverse(), Some(ModInt::new(5)));
+    }
+
+    #[test]
+    #[should_panic]
+    fn dynmodint_mismatch() {
+        let _ = DynModInt::new(1, 5) + DynModInt::new(1, 7);
+    }
+
+    #[test]
+    fn dynmodint_matches_modint() {
+        let a = ModInt::<101>::new(37);
+        let b = DynModInt::from(a);
+        assert_eq!(a.pow(77).value(), b.pow(77).value());
+        assert_eq!(a.step_counting_zeros(-300), (ModInt::new(37 - 300), 3));
+        let (moved, zeros) = b.step_counting_zeros(-300);
+        assert_eq!(
+            (moved.value(), zeros),
+            (a.step_counting_zeros(-300).0.value(), 3)
+        );
+    }
+
+    #[test]
+    fn zero_crossings_matches_walk() {
+        for start in 0..10i64 {
+            for step in -25..25i64 {
+                let mut pos = start;
+                let mut zeros = 0;
+                for _ in 0..step.abs() {
+                    pos += step.signum();
+                    if pos % 10 == 0 {
+                        zeros += 1;
+                    }
+                }
+                assert_eq!(zero_crossings(start, step, 10), zeros);
+            }
+        }
+    }
+}
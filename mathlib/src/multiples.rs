@@ -29,6 +29,15 @@ pub fn lcm<T: Num + Copy + Signed>(a: T, b: T) -> T {
     return (a.abs() / gcd(a, b)) * b.abs();
 }
 
+/// Least Common Multiple of any number of values, 1 if there are none.
+/// ```
+/// assert_eq!(mathlib::lcm_iter(vec![2, 3, 4, 5]), 60);
+/// assert_eq!(mathlib::lcm_iter(1..=20i64), 232792560);
+/// ```
+pub fn lcm_iter<T: Num + Copy + Signed, I: IntoIterator<Item = T>>(values: I) -> T {
+    return values.into_iter().fold(T::one(), |acc, v| lcm(acc, v));
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -51,6 +60,12 @@ mod tests {
         assert_eq!(result, 0);
     }
 
+    #[test]
+    fn lcm_iter_empty() {
+        let result: i32 = lcm_iter(vec![]);
+        assert_eq!(result, 1);
+    }
+
     #[test]
     fn gcd_zero() {
         let result = gcd(1, 0);
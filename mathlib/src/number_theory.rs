@@ -0,0 +1,207 @@
+extern crate num_traits;
+
+use crate::modulus::modulus;
+use crate::multiples::gcd;
+
+use num_traits::{NumCast, PrimInt, Signed};
+
+/// Find g = gcd(a, b) along with x and y where a * x + b * y = g.
+/// ```
+/// let (g, x, y) = mathlib::extended_gcd(240, 46);
+/// assert_eq!(g, 2);
+/// assert_eq!(240 * x + 46 * y, 2);
+/// ```
+pub fn extended_gcd<T: Signed + Copy>(a: T, b: T) -> (T, T, T) {
+    let (mut old_r, mut r) = (a, b);
+    let (mut old_x, mut x) = (T::one(), T::zero());
+    let (mut old_y, mut y) = (T::zero(), T::one());
+    while r != T::zero() {
+        let q = old_r / r;
+        (old_r, r) = (r, old_r - q * r);
+        (old_x, x) = (x, old_x - q * x);
+        (old_y, y) = (y, old_y - q * y);
+    }
+    // Keep the divisor positive
+    if old_r.is_negative() {
+        return (-old_r, -old_x, -old_y);
+    }
+    return (old_r, old_x, old_y);
+}
+
+/// The x in 0..m with a * x = 1 mod m, if there is one.
+/// ```
+/// assert_eq!(mathlib::mod_inverse(3, 11), Some(4));
+/// assert_eq!(mathlib::mod_inverse(-3, 11), Some(7));
+/// assert_eq!(mathlib::mod_inverse(4, 8), None);
+/// ```
+pub fn mod_inverse<T: Signed + Copy>(a: T, m: T) -> Option<T> {
+    let (g, x, _) = extended_gcd(modulus(a, m), m);
+    if g != T::one() {
+        return None;
+    }
+    return Some(modulus(x, m));
+}
+
+pub(crate) fn to_i128<T: PrimInt>(v: T) -> i128 {
+    return v.to_i128().expect("Value fits in i128");
+}
+
+pub(crate) fn from_i128<T: PrimInt>(v: i128) -> T {
+    return NumCast::from(v).expect("Result fits in the input type");
+}
+
+// a * b mod m for a and b in 0..m, without overflowing u128.
+fn mul_mod(a: u128, b: u128, m: u128) -> u128 {
+    if m <= 1 << 64 {
+        // Both are below 2^64, so the product fits
+        return a * b % m;
+    }
+    // Double and add, each step stays below m
+    let add_mod = |x: u128, y: u128| if x >= m - y { x - (m - y) } else { x + y };
+    let (mut a, mut b) = (a, b);
+    let mut result = 0;
+    while b > 0 {
+        if b & 1 == 1 {
+            result = add_mod(result, a);
+        }
+        a = add_mod(a, a);
+        b >>= 1;
+    }
+    return result;
+}
+
+/// base ^ exponent mod m, in 0..m. Works in u128, so any integer type is fine.
+/// ```
+/// assert_eq!(mathlib::mod_pow(4, 13, 497), 445);
+/// assert_eq!(mathlib::mod_pow(-2i64, 3, 5), 2);
+/// assert_eq!(mathlib::mod_pow(u64::MAX - 1, 2, u64::MAX), 1);
+/// assert_eq!(mathlib::mod_pow(2u128, 128, u128::MAX), 1);
+/// ```
+pub fn mod_pow<T: PrimInt>(base: T, exponent: T, m: T) -> T {
+    assert!(m > T::zero(), "Modulus must be positive");
+    assert!(exponent >= T::zero(), "Exponent can't be negative");
+    let m = m.to_u128().expect("Positive values fit in u128");
+    // Anything negative fits in i128, reduce its size then flip it into 0..m
+    let mut b = match base.to_u128() {
+        Some(b) => b % m,
+        None => (m - to_i128(base).unsigned_abs() % m) % m,
+    };
+    let mut e = exponent.to_u128().expect("Exponent can't be negative");
+    let mut result = 1 % m;
+    while e > 0 {
+        if e & 1 == 1 {
+            result = mul_mod(result, b, m);
+        }
+        b = mul_mod(b, b, m);
+        e >>= 1;
+    }
+    return NumCast::from(result).expect("Below m, so fits in its type");
+}
+
+/// The congruence at index can't be satisfied along with the ones before it.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct CrtError {
+    pub index: usize,
+}
+
+impl std::fmt::Display for CrtError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        return write!(f, "Congruence {} contradicts the earlier ones", self.index);
+    }
+}
+
+impl std::error::Error for CrtError {}
+
+/// Solve x = a mod m for every (a, m), giving (x, lcm of the moduli) with x in 0..lcm.
+///
+/// Moduli don't need to be coprime, but then the remainders have to agree.
+/// ```
+/// // 3 mod 5, 1 mod 7, 6 mod 8
+/// assert_eq!(mathlib::crt(&[(3, 5), (1, 7), (6, 8)]), Ok((78, 280)));
+/// // 6 and 4 share a factor of 2, and both remainders are odd
+/// assert_eq!(mathlib::crt(&[(3, 6), (1, 4)]), Ok((9, 12)));
+/// // 3 is odd but 2 mod 4 is even
+/// assert_eq!(mathlib::crt(&[(3, 6), (2, 4)]), Err(mathlib::CrtError { index: 1 }));
+/// ```
+pub fn crt<T: PrimInt + Signed>(congruences: &[(T, T)]) -> Result<(T, T), CrtError> {
+    let mut x: i128 = 0;
+    let mut m: i128 = 1;
+    for (index, &(a, n)) in congruences.iter().enumerate() {
+        let (a, n) = (to_i128(a), to_i128(n));
+        assert!(n > 0, "Moduli must be positive");
+        let g = gcd(m, n);
+        let diff = modulus(a - x, n);
+        if diff % g != 0 {
+            return Err(CrtError { index: index });
+        }
+        // x + m * k = a mod n, so k = diff / g * (m / g)^-1 mod n / g
+        let reduced = n / g;
+        let inverse = mod_inverse(m / g % reduced, reduced).expect("Coprime after dividing by g");
+        let k = modulus(diff / g % reduced * inverse, reduced);
+        x += m * k;
+        m *= reduced;
+        x = modulus(x, m);
+    }
+    return Ok((from_i128(x), from_i128(m)));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extended_gcd_negative() {
+        let (g, x, y) = extended_gcd(-12, 18);
+        assert_eq!(g, 6);
+        assert_eq!(-12 * x + 18 * y, 6);
+        assert_eq!(extended_gcd(0, -5).0, 5);
+    }
+
+    #[test]
+    fn mod_inverse_one() {
+        // Everything is 0 mod 1
+        assert_eq!(mod_inverse(5, 1), Some(0));
+    }
+
+    #[test]
+    fn mod_pow_large() {
+        // Products of values near 2^62 overflow i64 but not i128
+        let m = (1i64 << 62) + 135;
+        assert_eq!(mod_pow(m - 1, 2, m), 1);
+        assert_eq!(mod_pow(m - 1, 3, m), m - 1);
+        assert_eq!(mod_pow(7, 0, 1), 0);
+    }
+
+    #[test]
+    fn mod_pow_wide() {
+        // Squares near 2^100 need more than 128 bits
+        let m = (1i128 << 100) - 1;
+        assert_eq!(mod_pow(1i128 << 99, 2, m), 1 << 98);
+        assert_eq!(mod_pow(2, 100, m), 1);
+        assert_eq!(mod_pow(m - 1, 3, m), m - 1);
+        assert_eq!(mod_pow(-1, 3, m), m - 1);
+        assert_eq!(mod_pow(2, 101, m + 2), m);
+        // Above i128::MAX
+        let m = u128::MAX;
+        assert_eq!(mod_pow(m - 1, 2, m), 1);
+        assert_eq!(mod_pow(m - 1, 3, m), m - 1);
+        assert_eq!(mod_pow(i128::MIN, 1, i128::MAX), i128::MAX - 1);
+    }
+
+    #[test]
+    fn crt_cycles() {
+        // Buses leaving every 7, 13 and 59 minutes, offset by 0, 1 and 4 minutes
+        let (t, period) = crt(&[(0i64, 7), (-1, 13), (-4, 59)]).unwrap();
+        assert_eq!(period, 7 * 13 * 59);
+        assert_eq!(t % 7, 0);
+        assert_eq!((t + 1) % 13, 0);
+        assert_eq!((t + 4) % 59, 0);
+        assert_eq!(crt::<i32>(&[]), Ok((0, 1)));
+    }
+
+    #[test]
+    fn crt_duplicate_moduli() {
+        assert_eq!(crt(&[(2, 6), (8, 6)]), Ok((2, 6)));
+        assert_eq!(crt(&[(2, 6), (3, 6)]), Err(CrtError { index: 1 }));
+    }
+}
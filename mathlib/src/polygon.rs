@@ -1,7 +1,9 @@
 extern crate num_traits;
 
+use crate::cuboid::Cuboid;
 use crate::distance::euclidean_distance;
-use num_traits::{FromPrimitive, Num, Signed, ToPrimitive};
+use crate::number_theory::to_i128;
+use num_traits::{FromPrimitive, Num, PrimInt, Signed, ToPrimitive};
 
 // Came up in Advent of code 2023 day10
 /// Provide the area enclosed by a polygon.
@@ -90,3 +92,414 @@ fn inner_shoepick<T: Num + Copy + Signed + std::ops::AddAssign + ToPrimitive + F
     }
     return (shoelace_sum.abs() - boundary_len) / (T::one() + T::one()) + T::one();
 }
+
+/// Where a point is compared to a polygon.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum PointLocation {
+    Inside,
+    OnBoundary,
+    Outside,
+}
+
+/// Which way a polygon's vertices go round, taking y as pointing up.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Orientation {
+    Clockwise,
+    CounterClockwise,
+    // No area, everything is on one line.
+    Degenerate,
+}
+
+type Exact = (i128, i128);
+
+// Below this differences stay under 2^63, so a cross product of two is under 2^127.
+const EXACT_LIMIT: i128 = 1 << 62;
+
+fn exact<T: PrimInt>(p: (T, T)) -> Exact {
+    let p = (to_i128(p.0), to_i128(p.1));
+    assert!(
+        p.0.abs() < EXACT_LIMIT && p.1.abs() < EXACT_LIMIT,
+        "Polygon coordinates must be within 2^62 to be exact"
+    );
+    return p;
+}
+
+// Positive when c is left of the line a to b, zero when on it. Exact for coordinates under EXACT_LIMIT.
+fn cross(a: Exact, b: Exact, c: Exact) -> i128 {
+    return (b.0 - a.0) * (c.1 - a.1) - (b.1 - a.1) * (c.0 - a.0);
+}
+
+// p is on the segment a to b, ends included.
+fn on_segment(a: Exact, b: Exact, p: Exact) -> bool {
+    return cross(a, b, p) == 0
+        && a.0.min(b.0) <= p.0
+        && p.0 <= a.0.max(b.0)
+        && a.1.min(b.1) <= p.1
+        && p.1 <= a.1.max(b.1);
+}
+
+fn edges(vertices: &[Exact]) -> impl Iterator<Item = (Exact, Exact)> + '_ {
+    return vertices
+        .iter()
+        .zip(vertices.iter().cycle().skip(1))
+        .map(|(&a, &b)| (a, b));
+}
+
+fn locate(p: Exact, vertices: &[Exact]) -> PointLocation {
+    let mut inside = false;
+    for (a, b) in edges(vertices) {
+        if on_segment(a, b, p) {
+            return PointLocation::OnBoundary;
+        }
+        // Ray to the right, counting edges that cross its y. Going up the ray crosses
+        // when p is left of the edge, going down when it is right.
+        if (a.1 > p.1) != (b.1 > p.1) && (cross(a, b, p) > 0) == (b.1 > a.1) {
+            inside = !inside;
+        }
+    }
+    return if inside {
+        PointLocation::Inside
+    } else {
+        PointLocation::Outside
+    };
+}
+
+/// Where p is compared to the polygon, exactly, with no floating point.
+///
+/// Panics if a coordinate is 2^62 or more away from 0, past that an i128 can't hold the products.
+/// ```
+/// use mathlib::PointLocation;
+/// let square = [(0, 0), (10, 0), (10, 10), (0, 10)];
+/// assert_eq!(mathlib::point_in_polygon((5, 5), &square), PointLocation::Inside);
+/// assert_eq!(mathlib::point_in_polygon((10, 3), &square), PointLocation::OnBoundary);
+/// assert_eq!(mathlib::point_in_polygon((11, 3), &square), PointLocation::Outside);
+/// ```
+pub fn point_in_polygon<T: PrimInt + Signed>(p: (T, T), vertices: &[(T, T)]) -> PointLocation {
+    let vertices: Vec<Exact> = vertices.iter().map(|&v| exact(v)).collect();
+    return locate(exact(p), &vertices);
+}
+
+/// Which way the vertices go round, by the sign of the shoelace sum.
+///
+/// Coordinates have the same limit as `point_in_polygon`.
+/// ```
+/// use mathlib::Orientation;
+/// let square = [(0, 0), (10, 0), (10, 10), (0, 10)];
+/// assert_eq!(mathlib::polygon_orientation(&square), Orientation::CounterClockwise);
+/// let mut backwards = square;
+/// backwards.reverse();
+/// assert_eq!(mathlib::polygon_orientation(&backwards), Orientation::Clockwise);
+/// ```
+pub fn polygon_orientation<T: PrimInt + Signed>(vertices: &[(T, T)]) -> Orientation {
+    let vertices: Vec<Exact> = vertices.iter().map(|&v| exact(v)).collect();
+    // Terms can overflow part way, but wrapping still ends on twice the area, which fits
+    let sum = edges(&vertices).fold(0i128, |acc, (a, b)| {
+        acc.wrapping_add(a.0.wrapping_mul(b.1).wrapping_sub(b.0.wrapping_mul(a.1)))
+    });
+    return match sum.signum() {
+        1 => Orientation::CounterClockwise,
+        -1 => Orientation::Clockwise,
+        _ => Orientation::Degenerate,
+    };
+}
+
+// The closed segments share at least one point.
+fn segments_touch(a: Exact, b: Exact, c: Exact, d: Exact) -> bool {
+    let (d1, d2) = (cross(a, b, c), cross(a, b, d));
+    let (d3, d4) = (cross(c, d, a), cross(c, d, b));
+    if d1.signum() * d2.signum() < 0 && d3.signum() * d4.signum() < 0 {
+        return true;
+    }
+    return on_segment(a, b, c)
+        || on_segment(a, b, d)
+        || on_segment(c, d, a)
+        || on_segment(c, d, b);
+}
+
+/// If the polygon's edges only meet where neighbouring edges share a vertex.
+///
+/// Coordinates have the same limit as `point_in_polygon`.
+/// ```
+/// let square = [(0, 0), (10, 0), (10, 10), (0, 10)];
+/// assert!(mathlib::is_simple_polygon(&square));
+/// let bowtie = [(0, 0), (10, 10), (10, 0), (0, 10)];
+/// assert!(!mathlib::is_simple_polygon(&bowtie));
+/// ```
+pub fn is_simple_polygon<T: PrimInt + Signed>(vertices: &[(T, T)]) -> bool {
+    let vertices: Vec<Exact> = vertices.iter().map(|&v| exact(v)).collect();
+    let n = vertices.len();
+    if n < 3 || polygon_orientation(&vertices) == Orientation::Degenerate {
+        return false;
+    }
+    let all_edges: Vec<(Exact, Exact)> = edges(&vertices).collect();
+    for i in 0..n {
+        let (a, b) = all_edges[i];
+        if a == b {
+            return false;
+        }
+        for (j, &(c, d)) in all_edges.iter().enumerate().skip(i + 1) {
+            if j == i + 1 || (i == 0 && j == n - 1) {
+                // Neighbours share one vertex, they mustn't fold back along each other
+                let (shared, mine, theirs) = if j == i + 1 { (b, a, d) } else { (a, b, c) };
+                if cross(shared, mine, theirs) == 0
+                    && (mine.0 - shared.0) * (theirs.0 - shared.0)
+                        + (mine.1 - shared.1) * (theirs.1 - shared.1)
+                        > 0
+                {
+                    return false;
+                }
+                continue;
+            }
+            if segments_touch(a, b, c, d) {
+                return false;
+            }
+        }
+    }
+    return true;
+}
+
+/// If the segment a to b passes through the inside of the rectangle, not just along or up to its edge.
+///
+/// Coordinates have the same limit as `point_in_polygon`.
+/// ```
+/// let rect = mathlib::Cuboid::new([0, 0], [10, 10]);
+/// assert!(mathlib::segment_crosses_rect_interior((5, -5), (5, 15), &rect));
+/// // Along the edge isn't inside
+/// assert!(!mathlib::segment_crosses_rect_interior((0, -5), (0, 15), &rect));
+/// // Nor is stopping at it
+/// assert!(!mathlib::segment_crosses_rect_interior((5, -5), (5, 0), &rect));
+/// assert!(mathlib::segment_crosses_rect_interior((-1, 5), (5, 12), &rect));
+/// ```
+pub fn segment_crosses_rect_interior<T: PrimInt + Signed>(
+    a: (T, T),
+    b: (T, T),
+    rect: &Cuboid<T, 2>,
+) -> bool {
+    let (a, b) = (exact(a), exact(b));
+    let (min, max) = (
+        exact((rect.min[0], rect.min[1])),
+        exact((rect.max[0], rect.max[1])),
+    );
+    // Separating axes, with strict tests as the inside is open. First the rectangle's own
+    if a.0.max(b.0) <= min.0 || a.0.min(b.0) >= max.0 {
+        return false;
+    }
+    if a.1.max(b.1) <= min.1 || a.1.min(b.1) >= max.1 {
+        return false;
+    }
+    // Then across the segment, the corners have to be on both sides of its line
+    let corners = [min, (max.0, min.1), max, (min.0, max.1)];
+    let sides: Vec<i128> = corners.iter().map(|&c| cross(a, b, c)).collect();
+    return sides.iter().any(|&s| s > 0) && sides.iter().any(|&s| s < 0);
+}
+
+/// If the whole rectangle, edges included, is inside or on the edge of a rectilinear polygon.
+///
+/// Coordinates are doubled, so here they must be within 2^61.
+/// ```
+/// // An L shape
+/// let l = [(0, 0), (4, 0), (4, 2), (2, 2), (2, 6), (0, 6)];
+/// assert!(mathlib::rect_in_rectilinear_polygon(&mathlib::Cuboid::new([0, 0], [4, 2]), &l));
+/// assert!(mathlib::rect_in_rectilinear_polygon(&mathlib::Cuboid::new([0, 0], [2, 6]), &l));
+/// assert!(!mathlib::rect_in_rectilinear_polygon(&mathlib::Cuboid::new([0, 0], [4, 6]), &l));
+/// // A line along the inside corner
+/// assert!(mathlib::rect_in_rectilinear_polygon(&mathlib::Cuboid::new([2, 2], [4, 2]), &l));
+/// ```
+pub fn rect_in_rectilinear_polygon<T: PrimInt + Signed>(
+    rect: &Cuboid<T, 2>,
+    vertices: &[(T, T)],
+) -> bool {
+    // Doubled, so the middle of anything is still a whole number
+    let double = |p: (T, T)| {
+        let (x, y) = exact(p);
+        assert!(
+            x.abs() < EXACT_LIMIT / 2 && y.abs() < EXACT_LIMIT / 2,
+            "Polygon coordinates must be within 2^61 to be exact once doubled"
+        );
+        return (2 * x, 2 * y);
+    };
+    let (min, max) = (
+        double((rect.min[0], rect.min[1])),
+        double((rect.max[0], rect.max[1])),
+    );
+    let doubled: Vec<Exact> = vertices.iter().map(|&v| double(v)).collect();
+    let inside = |p: Exact| locate(p, &doubled) != PointLocation::Outside;
+
+    if min.0 < max.0 && min.1 < max.1 {
+        // With no edge through it, the inside of the rectangle is all on one side of the
+        // boundary, so its centre decides. The rectangle's edges then come with it.
+        let crosses = edges(&doubled).any(|(a, b)| {
+            segment_crosses_rect_interior(a, b, &Cuboid::new([min.0, min.1], [max.0, max.1]))
+        });
+        return !crosses && inside(((min.0 + max.0) / 2, (min.1 + max.1) / 2));
+    }
+
+    // A line or a point. The boundary can only come or go at vertex coordinates, so check
+    // those along it and a point between each pair.
+    let axis = if min.0 < max.0 { 0 } else { 1 };
+    let (low, high) = if axis == 0 {
+        (min.0, max.0)
+    } else {
+        (min.1, max.1)
+    };
+    let mut stops: Vec<i128> = doubled
+        .iter()
+        .map(|v| if axis == 0 { v.0 } else { v.1 })
+        .filter(|&c| low < c && c < high)
+        .collect();
+    stops.push(low);
+    stops.push(high);
+    stops.sort();
+    stops.dedup();
+    let at = |c: i128| if axis == 0 { (c, min.1) } else { (min.0, c) };
+    if !stops.iter().all(|&c| inside(at(c))) {
+        return false;
+    }
+    return stops.windows(2).all(|w| inside(at((w[0] + w[1]) / 2)));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The worked example from 2025 day 9.
+    fn example() -> Vec<(i64, i64)> {
+        return vec![
+            (7, 1),
+            (11, 1),
+            (11, 7),
+            (9, 7),
+            (9, 5),
+            (2, 5),
+            (2, 3),
+            (7, 3),
+        ];
+    }
+
+    #[test]
+    fn point_in_polygon_grid() {
+        let vertices = example();
+        let rows: Vec<String> = (0..9)
+            .map(|y| {
+                (0..13)
+                    .map(|x| match point_in_polygon((x, y), &vertices) {
+                        PointLocation::Inside => 'I',
+                        PointLocation::OnBoundary => '#',
+                        PointLocation::Outside => '.',
+                    })
+                    .collect()
+            })
+            .collect();
+        assert_eq!(
+            rows,
+            vec![
+                ".............",
+                ".......#####.",
+                ".......#III#.",
+                "..######III#.",
+                "..#IIIIIIII#.",
+                "..########I#.",
+                ".........#I#.",
+                ".........###.",
+                ".............",
+            ]
+        );
+    }
+
+    #[test]
+    fn point_in_polygon_large() {
+        // Products here overflow i64, so the cross products need the extra room
+        let big = i64::MAX / 2;
+        let triangle = [(-big, -big), (big, -big), (0, big)];
+        assert_eq!(point_in_polygon((0, 0), &triangle), PointLocation::Inside);
+        assert_eq!(
+            point_in_polygon((0, big), &triangle),
+            PointLocation::OnBoundary
+        );
+        assert_eq!(
+            point_in_polygon((big, big), &triangle),
+            PointLocation::Outside
+        );
+    }
+
+    #[test]
+    fn near_the_limit() {
+        let big = (1i64 << 61) - 1;
+        let square = [(-big, -big), (big, -big), (big, big), (-big, big)];
+        assert_eq!(polygon_orientation(&square), Orientation::CounterClockwise);
+        assert!(is_simple_polygon(&square));
+        let all = Cuboid::new([-big, -big], [big, big]);
+        assert!(rect_in_rectilinear_polygon(&all, &square));
+        let corner = Cuboid::new([-big, -big], [0, 0]);
+        let l = [
+            (-big, -big),
+            (big, -big),
+            (big, 0),
+            (0, 0),
+            (0, big),
+            (-big, big),
+        ];
+        assert!(rect_in_rectilinear_polygon(&corner, &l));
+        assert!(!rect_in_rectilinear_polygon(&all, &l));
+    }
+
+    #[test]
+    #[should_panic(expected = "must be within 2^62")]
+    fn past_the_limit() {
+        let big = i64::MAX;
+        point_in_polygon((0, 0), &[(-big, -big), (big, -big), (0, big)]);
+    }
+
+    #[test]
+    #[should_panic(expected = "must be within 2^61")]
+    fn past_the_doubled_limit() {
+        let big = 1i64 << 61;
+        let square = [(0, 0), (big, 0), (big, big), (0, big)];
+        rect_in_rectilinear_polygon(&Cuboid::new([0, 0], [1, 1]), &square);
+    }
+
+    #[test]
+    fn simple_polygon_cases() {
+        assert!(is_simple_polygon(&example()));
+        // Folds back on itself along a line
+        assert!(!is_simple_polygon(&[(0, 0), (4, 0), (2, 0), (2, 3)]));
+        // Two separate loops meeting at a vertex
+        assert!(!is_simple_polygon(&[
+            (0, 0),
+            (2, 0),
+            (2, 2),
+            (4, 2),
+            (4, 4),
+            (2, 4),
+            (2, 2),
+            (0, 2)
+        ]));
+        assert!(!is_simple_polygon(&[(0, 0), (1, 1), (2, 2)]));
+    }
+
+    #[test]
+    fn rect_in_polygon_matches_points() {
+        // A rectangle is inside exactly when every whole point in it is, for this shape
+        let vertices = example();
+        for x1 in 0..13 {
+            for x2 in x1..13 {
+                for y1 in 0..9 {
+                    for y2 in y1..9 {
+                        let rect = Cuboid::new([x1, y1], [x2, y2]);
+                        let every_point = (x1..=x2).all(|x| {
+                            (y1..=y2).all(|y| {
+                                point_in_polygon((x, y), &vertices) != PointLocation::Outside
+                            })
+                        });
+                        assert_eq!(
+                            rect_in_rectilinear_polygon(&rect, &vertices),
+                            every_point,
+                            "{:?}",
+                            rect
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
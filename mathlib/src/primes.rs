@@ -0,0 +1,453 @@
+use crate::multiples::gcd;
+use crate::number_theory::mod_pow;
+
+fn mul_mod(a: u64, b: u64, m: u64) -> u64 {
+    return ((a as u128 * b as u128) % m as u128) as u64;
+}
+
+// floor(sqrt(n)) without going through floating point.
+fn isqrt(n: u64) -> u64 {
+    if n < 2 {
+        return n;
+    }
+    let mut x = n;
+    let mut y = x / 2 + 1;
+    while y < x {
+        x = y;
+        y = (x + n / x) / 2;
+    }
+    return x;
+}
+
+/// Every prime up to and including n, by the sieve of Eratosthenes.
+/// ```
+/// assert_eq!(mathlib::primes_up_to(20), vec![2, 3, 5, 7, 11, 13, 17, 19]);
+/// ```
+pub fn primes_up_to(n: u64) -> Vec<u64> {
+    if n < 2 {
+        return vec![];
+    }
+    let n = n as usize;
+    let mut composite = vec![false; n + 1];
+    let mut result = vec![];
+    for i in 2..=n {
+        if composite[i] {
+            continue;
+        }
+        result.push(i as u64);
+        let mut j = i * i;
+        while j <= n {
+            composite[j] = true;
+            j += i;
+        }
+    }
+    return result;
+}
+
+// The first multiple of p that a sieve from low crosses off, None if it is past u64::MAX.
+fn first_to_cross(p: u64, low: u64) -> Option<u64> {
+    // Start at p * p so p itself isn't crossed off when it is in the window
+    return Some((p * p).max(low.div_ceil(p).checked_mul(p)?));
+}
+
+/// Every prime in low..=high, only sieving that window so huge bounds are fine.
+/// ```
+/// assert_eq!(mathlib::segmented_sieve(90, 110), vec![97, 101, 103, 107, 109]);
+/// assert_eq!(mathlib::segmented_sieve(1_000_000_000_000, 1_000_000_000_100).len(), 4);
+/// ```
+pub fn segmented_sieve(low: u64, high: u64) -> Vec<u64> {
+    if high < 2 || low > high {
+        return vec![];
+    }
+    let low = low.max(2);
+    let mut composite = vec![false; (high - low + 1) as usize];
+    for p in primes_up_to(isqrt(high)) {
+        let mut next = first_to_cross(p, low);
+        while let Some(j) = next.filter(|&j| j <= high) {
+            composite[(j - low) as usize] = true;
+            next = j.checked_add(p);
+        }
+    }
+    return composite
+        .iter()
+        .enumerate()
+        .filter(|(_, &c)| !c)
+        .map(|(i, _)| low + i as u64)
+        .collect();
+}
+
+/// Deterministic Miller-Rabin, the first twelve primes as witnesses cover all of u64.
+/// ```
+/// assert!(mathlib::is_prime(1_000_000_007));
+/// assert!(!mathlib::is_prime(1));
+/// // 3215031751 = 151 * 751 * 28351 fools witnesses 2, 3, 5 and 7
+/// assert!(!mathlib::is_prime(3_215_031_751));
+/// assert!(mathlib::is_prime(18_446_744_073_709_551_557));
+/// ```
+pub fn is_prime(n: u64) -> bool {
+    const WITNESSES: [u64; 12] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37];
+    if n < 2 {
+        return false;
+    }
+    for &p in WITNESSES.iter() {
+        if n.is_multiple_of(p) {
+            return n == p;
+        }
+    }
+    let mut d = n - 1;
+    let mut s = 0;
+    while d.is_multiple_of(2) {
+        d /= 2;
+        s += 1;
+    }
+    'witness: for &a in WITNESSES.iter() {
+        let mut x = mod_pow(a, d, n);
+        if x == 1 || x == n - 1 {
+            continue;
+        }
+        for _ in 1..s {
+            x = mul_mod(x, x, n);
+            if x == n - 1 {
+                continue 'witness;
+            }
+        }
+        return false;
+    }
+    return true;
+}
+
+/// A factor of composite n other than 1 and n, by Pollard's rho.
+/// ```
+/// let f = mathlib::pollard_rho(600_851_475_143);
+/// assert!(f > 1 && f < 600_851_475_143);
+/// assert_eq!(600_851_475_143 % f, 0);
+/// ```
+pub fn pollard_rho(n: u64) -> u64 {
+    assert!(
+        n > 3 && !is_prime(n),
+        "Only composites have a proper factor"
+    );
+    if n.is_multiple_of(2) {
+        return 2;
+    }
+    // A cycle with no factor in it means a bad constant, try the next one
+    for c in 1.. {
+        let f = |x: u64| (mul_mod(x, x, n) + c) % n;
+        let (mut x, mut y, mut d) = (2, 2, 1);
+        while d == 1 {
+            x = f(x);
+            y = f(f(y));
+            d = gcd(x.abs_diff(y), n);
+        }
+        if d != n {
+            return d;
+        }
+    }
+    unreachable!();
+}
+
+/// The prime factors of n with their powers, smallest prime first.
+/// ```
+/// assert_eq!(mathlib::factorize(360), vec![(2, 3), (3, 2), (5, 1)]);
+/// assert_eq!(mathlib::factorize(1), vec![]);
+/// assert_eq!(mathlib::factorize(1_000_000_016_000_000_063), vec![(1_000_000_007, 1), (1_000_000_009, 1)]);
+/// ```
+pub fn factorize(n: u64) -> Vec<(u64, u32)> {
+    assert!(n > 0, "Zero has no factorization");
+    let mut primes = vec![];
+    let mut remaining = n;
+    // Trial division clears out the small factors quicker than rho
+    for p in [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37] {
+        while remaining.is_multiple_of(p) {
+            primes.push(p);
+            remaining /= p;
+        }
+    }
+    let mut stack = vec![remaining];
+    while let Some(m) = stack.pop() {
+        if m == 1 {
+            continue;
+        }
+        if is_prime(m) {
+            primes.push(m);
+            continue;
+        }
+        let f = pollard_rho(m);
+        stack.push(f);
+        stack.push(m / f);
+    }
+    primes.sort();
+
+    let mut result: Vec<(u64, u32)> = vec![];
+    for p in primes {
+        match result.last_mut() {
+            Some((last, count)) if *last == p => *count += 1,
+            _ => result.push((p, 1)),
+        }
+    }
+    return result;
+}
+
+/// Every divisor of the number with this factorization, in increasing order.
+/// ```
+/// let factors = mathlib::factorize(12);
+/// assert_eq!(mathlib::divisors(&factors), vec![1, 2, 3, 4, 6, 12]);
+/// ```
+pub fn divisors(factors: &[(u64, u32)]) -> Vec<u64> {
+    let mut result = vec![1];
+    for &(p, count) in factors {
+        let mut next = Vec::with_capacity(result.len() * (count as usize + 1));
+        for &d in result.iter() {
+            let mut power = d;
+            next.push(power);
+            for _ in 0..count {
+                power *= p;
+                next.push(power);
+            }
+        }
+        result = next;
+    }
+    result.sort();
+    return result;
+}
+
+/// Euler's totient, how many of 1..=n are coprime to n.
+/// ```
+/// assert_eq!(mathlib::totient(36), 12);
+/// assert_eq!(mathlib::totient(1), 1);
+/// ```
+pub fn totient(n: u64) -> u64 {
+    return factorize(n)
+        .iter()
+        .fold(n, |acc, &(p, _)| acc / p * (p - 1));
+}
+
+/// The Möbius function, 0 if n has a square factor, else -1 to the number of prime factors.
+/// ```
+/// assert_eq!(mathlib::mobius(30), -1);
+/// assert_eq!(mathlib::mobius(6), 1);
+/// assert_eq!(mathlib::mobius(12), 0);
+/// ```
+pub fn mobius(n: u64) -> i32 {
+    let factors = factorize(n);
+    if factors.iter().any(|&(_, count)| count > 1) {
+        return 0;
+    }
+    return if factors.len().is_multiple_of(2) {
+        1
+    } else {
+        -1
+    };
+}
+
+/// How many multiples of d are in low..=high.
+///
+/// Panics for multiples of 1 in 0..=u64::MAX, as 2^64 doesn't fit.
+/// ```
+/// assert_eq!(mathlib::count_multiples(1001, 1000, 9999), 9);
+/// ```
+pub fn count_multiples(d: u64, low: u64, high: u64) -> u64 {
+    assert!(d > 0, "Can't count multiples of zero");
+    if low > high {
+        return 0;
+    }
+    if low == 0 {
+        // 0 is a multiple too
+        return (high / d)
+            .checked_add(1)
+            .expect("Count should fit in a u64");
+    }
+    // Multiples up to high, less the ones below low
+    return high / d - (low - 1) / d;
+}
+
+/// The sum of the multiples of d in low..=high.
+/// ```
+/// // 1010 + 1111 + ... + 9999, every 4 digit number made of a 2 digit block twice
+/// assert_eq!(mathlib::sum_multiples(101, 1000, 9999), 495405);
+/// ```
+pub fn sum_multiples(d: u64, low: u64, high: u64) -> u128 {
+    // 0 adds nothing, and leaving it out keeps the count in a u64
+    let low = low.max(1);
+    let count = count_multiples(d, low, high) as u128;
+    if count == 0 {
+        return 0;
+    }
+    let first = low.div_ceil(d) as u128;
+    // d * (first + ... + first + count - 1), halving whichever factor is even first so the
+    // product stays the size of the answer
+    let ends = 2 * first + count - 1;
+    let series = if count.is_multiple_of(2) {
+        count / 2 * ends
+    } else {
+        count * (ends / 2)
+    };
+    return d as u128 * series;
+}
+
+// Every non-empty subset of divisors with its lcm and size, dropping lcms past the bound.
+fn subset_lcms(divisors: &[u64], bound: u64) -> Vec<(u64, usize)> {
+    let mut result: Vec<(u64, usize)> = vec![];
+    for &d in divisors {
+        let mut added = vec![(d, 1)];
+        for &(l, size) in result.iter() {
+            let joined = (l / gcd(l, d)) as u128 * d as u128;
+            if joined <= bound as u128 {
+                added.push((joined as u64, size + 1));
+            }
+        }
+        result.extend(added);
+    }
+    return result.into_iter().filter(|&(l, _)| l <= bound).collect();
+}
+
+/// How many of low..=high are a multiple of at least one divisor, by inclusion-exclusion.
+/// ```
+/// // 6 digit numbers made of a repeated 2 or 3 digit block
+/// assert_eq!(mathlib::count_multiples_of_any(&[10101, 1001], 100000, 999999), 90 + 900 - 9);
+/// ```
+pub fn count_multiples_of_any(divisors: &[u64], low: u64, high: u64) -> u64 {
+    let mut total: i128 = 0;
+    for (l, size) in subset_lcms(divisors, high) {
+        let sign = if size % 2 == 1 { 1 } else { -1 };
+        total += sign * count_multiples(l, low, high) as i128;
+    }
+    return total as u64;
+}
+
+/// The sum of every value in low..=high that is a multiple of at least one divisor.
+/// ```
+/// assert_eq!(mathlib::sum_multiples_of_any(&[3, 5], 1, 999), 233168);
+/// ```
+pub fn sum_multiples_of_any(divisors: &[u64], low: u64, high: u64) -> u128 {
+    let mut total: i128 = 0;
+    for (l, size) in subset_lcms(divisors, high) {
+        let sign = if size % 2 == 1 { 1 } else { -1 };
+        total += sign * sum_multiples(l, low, high) as i128;
+    }
+    return total as u128;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn segmented_sieve_matches_simple() {
+        let all = primes_up_to(10_000);
+        let window: Vec<u64> = all
+            .iter()
+            .copied()
+            .filter(|&p| (5000..=7000).contains(&p))
+            .collect();
+        assert_eq!(segmented_sieve(5000, 7000), window);
+        // The window can include the small primes doing the sieving
+        assert_eq!(segmented_sieve(0, 10_000), all);
+        assert_eq!(segmented_sieve(24, 28), vec![]);
+    }
+
+    #[test]
+    fn is_prime_matches_sieve() {
+        let primes = primes_up_to(5000);
+        for n in 0..=5000 {
+            assert_eq!(is_prime(n), primes.binary_search(&n).is_ok(), "{}", n);
+        }
+    }
+
+    #[test]
+    fn is_prime_strong_pseudoprimes() {
+        // Strong pseudoprime to every base up to 23
+        assert!(!is_prime(3_825_123_056_546_413_051));
+        assert!(!is_prime(u64::MAX));
+        assert!(!is_prime(4_294_967_297));
+    }
+
+    #[test]
+    fn factorize_large() {
+        assert_eq!(
+            factorize(u64::MAX),
+            vec![
+                (3, 1),
+                (5, 1),
+                (17, 1),
+                (257, 1),
+                (641, 1),
+                (65537, 1),
+                (6700417, 1)
+            ]
+        );
+        assert_eq!(
+            factorize(4_294_967_291 * 4_294_967_291),
+            vec![(4_294_967_291, 2)]
+        );
+        let p = 18_446_744_073_709_551_557;
+        assert_eq!(factorize(p), vec![(p, 1)]);
+    }
+
+    #[test]
+    fn divisors_empty_factorization() {
+        assert_eq!(divisors(&[]), vec![1]);
+        assert_eq!(divisors(&factorize(1001)).len(), 8);
+    }
+
+    #[test]
+    fn totient_mobius_small() {
+        // Sum of totients of the divisors is n
+        let n = 1001 * 64;
+        let sum: u64 = divisors(&factorize(n)).iter().map(|&d| totient(d)).sum();
+        assert_eq!(sum, n);
+        // Sum of the Möbius function over divisors is 0 past 1
+        let sum: i32 = divisors(&factorize(n)).iter().map(|&d| mobius(d)).sum();
+        assert_eq!(sum, 0);
+        assert_eq!(mobius(1), 1);
+    }
+
+    #[test]
+    fn multiples_of_any_brute_force() {
+        let divisors = [4, 6, 9];
+        let count = (50..=500)
+            .filter(|n| divisors.iter().any(|d| n % d == 0))
+            .count();
+        let sum: u64 = (50..=500)
+            .filter(|n| divisors.iter().any(|d| n % d == 0))
+            .sum();
+        assert_eq!(count_multiples_of_any(&divisors, 50, 500), count as u64);
+        assert_eq!(sum_multiples_of_any(&divisors, 50, 500), sum as u128);
+        assert_eq!(count_multiples(7, 10, 13), 0);
+        assert_eq!(sum_multiples(7, 20, 10), 0);
+    }
+
+    #[test]
+    fn multiples_at_u64_limits() {
+        let max = u64::MAX;
+        assert_eq!(count_multiples(1, 1, max), max);
+        assert_eq!(count_multiples(1, max, max), 1);
+        assert_eq!(count_multiples(2, 0, max), 1 << 63);
+        assert_eq!(count_multiples(max, 0, max), 2);
+        // 1 + 2 + ... + max
+        let all = max as u128 * (max as u128 + 1) / 2;
+        assert_eq!(sum_multiples(1, 1, max), all);
+        assert_eq!(sum_multiples(1, 0, max), all);
+        assert_eq!(sum_multiples(1, max - 1, max), 2 * max as u128 - 1);
+        assert_eq!(
+            sum_multiples(3, 0, max),
+            3 * ((max / 3) as u128 * (max / 3 + 1) as u128 / 2)
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Count should fit in a u64")]
+    fn count_every_u64() {
+        count_multiples(1, 0, u64::MAX);
+    }
+
+    #[test]
+    fn sieve_start_at_u64_limits() {
+        assert_eq!(first_to_cross(3, 10), Some(12));
+        assert_eq!(first_to_cross(7, 10), Some(49));
+        // u64::MAX is odd but a multiple of 3
+        assert_eq!(first_to_cross(2, u64::MAX), None);
+        assert_eq!(first_to_cross(3, u64::MAX - 1), Some(u64::MAX));
+        assert_eq!(first_to_cross(4_294_967_291, u64::MAX), None);
+    }
+}
@@ -0,0 +1,339 @@
+extern crate num_traits;
+
+use crate::number_theory::to_i128;
+
+use num_traits::PrimInt;
+use std::collections::BTreeMap;
+
+/// A set of integers kept as sorted, disjoint, inclusive ranges.
+///
+/// Ranges that overlap or touch are merged as they are inserted.
+/// ```
+/// let mut fresh = mathlib::RangeSet::new();
+/// fresh.insert(3, 5);
+/// fresh.insert(10, 14);
+/// fresh.insert(16, 20);
+/// fresh.insert(12, 18);
+/// assert_eq!(fresh.iter().collect::<Vec<_>>(), vec![(3, 5), (10, 20)]);
+/// assert!(fresh.contains(17));
+/// assert!(!fresh.contains(8));
+/// assert_eq!(fresh.covered_length(), 14);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RangeSet<T: PrimInt> {
+    /* Start of each range to its end */
+    ranges: BTreeMap<T, T>,
+}
+
+impl<T: PrimInt> Default for RangeSet<T> {
+    fn default() -> Self {
+        return RangeSet::new();
+    }
+}
+
+// One past v, None at the top of the type.
+fn after<T: PrimInt>(v: T) -> Option<T> {
+    return v.checked_add(&T::one());
+}
+
+// One before v, None at the bottom of the type.
+fn before<T: PrimInt>(v: T) -> Option<T> {
+    return v.checked_sub(&T::one());
+}
+
+impl<T: PrimInt> RangeSet<T> {
+    pub fn new() -> RangeSet<T> {
+        return RangeSet {
+            ranges: BTreeMap::new(),
+        };
+    }
+
+    /// Add start..=end, joining it with any ranges it overlaps or touches.
+    /// ```
+    /// let mut set = mathlib::RangeSet::new();
+    /// set.insert(1, 3);
+    /// set.insert(4, 6);
+    /// assert_eq!(set.iter().collect::<Vec<_>>(), vec![(1, 6)]);
+    /// ```
+    pub fn insert(&mut self, start: T, end: T) {
+        assert!(start <= end, "Range start must not be after its end");
+        let mut new_start = start;
+        let mut new_end = end;
+        let reach = after(end).unwrap_or(end);
+        let mut merged = vec![];
+        // Walk back from the last range that could touch, stopping at the first that doesn't
+        for (&s, &e) in self.ranges.range(..=reach).rev() {
+            if after(e).is_some_and(|a| a < start) {
+                break;
+            }
+            merged.push(s);
+            new_start = new_start.min(s);
+            new_end = new_end.max(e);
+        }
+        for s in merged {
+            self.ranges.remove(&s);
+        }
+        self.ranges.insert(new_start, new_end);
+    }
+
+    /// Take start..=end out of the set, splitting any range it lands in the middle of.
+    /// ```
+    /// let mut set = mathlib::RangeSet::new();
+    /// set.insert(1, 10);
+    /// set.remove(4, 6);
+    /// assert_eq!(set.iter().collect::<Vec<_>>(), vec![(1, 3), (7, 10)]);
+    /// ```
+    pub fn remove(&mut self, start: T, end: T) {
+        assert!(start <= end, "Range start must not be after its end");
+        let overlapping: Vec<(T, T)> = self
+            .ranges
+            .range(..=end)
+            .rev()
+            .take_while(|(_, &e)| e >= start)
+            .map(|(&s, &e)| (s, e))
+            .collect();
+        for (s, e) in overlapping {
+            self.ranges.remove(&s);
+            if s < start {
+                self.ranges
+                    .insert(s, before(start).expect("s is below start"));
+            }
+            if e > end {
+                self.ranges.insert(after(end).expect("e is above end"), e);
+            }
+        }
+    }
+
+    /// If v is in any range, in O(log n).
+    /// ```
+    /// let set: mathlib::RangeSet<u64> = vec![(10, 20)].into_iter().collect();
+    /// assert!(set.contains(10) && set.contains(20));
+    /// assert!(!set.contains(21));
+    /// ```
+    pub fn contains(&self, v: T) -> bool {
+        return self
+            .ranges
+            .range(..=v)
+            .next_back()
+            .is_some_and(|(_, &e)| e >= v);
+    }
+
+    /// The range holding v, if there is one.
+    /// ```
+    /// let set: mathlib::RangeSet<i32> = vec![(-5, -1), (3, 8)].into_iter().collect();
+    /// assert_eq!(set.range_containing(4), Some((3, 8)));
+    /// assert_eq!(set.range_containing(0), None);
+    /// ```
+    pub fn range_containing(&self, v: T) -> Option<(T, T)> {
+        let (&s, &e) = self.ranges.range(..=v).next_back()?;
+        if e < v {
+            return None;
+        }
+        return Some((s, e));
+    }
+
+    /// The ranges, lowest first.
+    pub fn iter(&self) -> impl Iterator<Item = (T, T)> + '_ {
+        return self.ranges.iter().map(|(&s, &e)| (s, e));
+    }
+
+    /// How many separate ranges there are, not how many values.
+    pub fn range_count(&self) -> usize {
+        return self.ranges.len();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.ranges.is_empty();
+    }
+
+    /// The lowest and highest values in the set.
+    /// ```
+    /// let set: mathlib::RangeSet<u8> = vec![(7, 9), (1, 2)].into_iter().collect();
+    /// assert_eq!(set.bounds(), Some((1, 9)));
+    /// ```
+    pub fn bounds(&self) -> Option<(T, T)> {
+        let (&low, _) = self.ranges.first_key_value()?;
+        let (_, &high) = self.ranges.last_key_value()?;
+        return Some((low, high));
+    }
+
+    /// How many values are in the set. Counted in u128 so a full u64 range fits.
+    /// ```
+    /// let set: mathlib::RangeSet<u64> = vec![(0, u64::MAX)].into_iter().collect();
+    /// assert_eq!(set.covered_length(), 1 << 64);
+    /// ```
+    pub fn covered_length(&self) -> u128 {
+        return self
+            .iter()
+            .map(|(s, e)| (to_i128(e) - to_i128(s)) as u128 + 1)
+            .sum();
+    }
+
+    /// The gaps between neighbouring ranges, not counting anything before the first or after the last.
+    /// ```
+    /// let set: mathlib::RangeSet<i32> = vec![(1, 3), (6, 6), (10, 12)].into_iter().collect();
+    /// assert_eq!(set.gaps().collect::<Vec<_>>(), vec![(4, 5), (7, 9)]);
+    /// ```
+    pub fn gaps(&self) -> impl Iterator<Item = (T, T)> + '_ {
+        return self
+            .iter()
+            .zip(self.iter().skip(1))
+            .map(|((_, e), (s, _))| {
+                // Merging means there is always at least one value between
+                (after(e).unwrap(), before(s).unwrap())
+            });
+    }
+
+    /// Everything in either set.
+    /// ```
+    /// let a: mathlib::RangeSet<i32> = vec![(1, 3), (8, 9)].into_iter().collect();
+    /// let b: mathlib::RangeSet<i32> = vec![(4, 5)].into_iter().collect();
+    /// assert_eq!(a.union(&b).iter().collect::<Vec<_>>(), vec![(1, 5), (8, 9)]);
+    /// ```
+    pub fn union(&self, other: &RangeSet<T>) -> RangeSet<T> {
+        let mut result = self.clone();
+        for (s, e) in other.iter() {
+            result.insert(s, e);
+        }
+        return result;
+    }
+
+    /// Everything in both sets.
+    /// ```
+    /// let a: mathlib::RangeSet<i32> = vec![(1, 5), (8, 12)].into_iter().collect();
+    /// let b: mathlib::RangeSet<i32> = vec![(4, 9), (12, 20)].into_iter().collect();
+    /// assert_eq!(a.intersection(&b).iter().collect::<Vec<_>>(), vec![(4, 5), (8, 9), (12, 12)]);
+    /// ```
+    pub fn intersection(&self, other: &RangeSet<T>) -> RangeSet<T> {
+        let mut result = RangeSet::new();
+        let mut mine = self.iter().peekable();
+        let mut theirs = other.iter().peekable();
+        // Both are sorted, so step whichever range ends first
+        while let (Some(&(s1, e1)), Some(&(s2, e2))) = (mine.peek(), theirs.peek()) {
+            let start = s1.max(s2);
+            let end = e1.min(e2);
+            if start <= end {
+                result.ranges.insert(start, end);
+            }
+            if e1 < e2 {
+                mine.next();
+            } else {
+                theirs.next();
+            }
+        }
+        return result;
+    }
+
+    /// Everything in this set but not the other.
+    /// ```
+    /// let a: mathlib::RangeSet<i32> = vec![(1, 10)].into_iter().collect();
+    /// let b: mathlib::RangeSet<i32> = vec![(3, 4), (9, 20)].into_iter().collect();
+    /// assert_eq!(a.difference(&b).iter().collect::<Vec<_>>(), vec![(1, 2), (5, 8)]);
+    /// ```
+    pub fn difference(&self, other: &RangeSet<T>) -> RangeSet<T> {
+        let mut result = self.clone();
+        for (s, e) in other.iter() {
+            result.remove(s, e);
+        }
+        return result;
+    }
+
+    /// Everything in low..=high that isn't in the set.
+    /// ```
+    /// let set: mathlib::RangeSet<u32> = vec![(0, 2), (5, 6)].into_iter().collect();
+    /// assert_eq!(set.complement(0, 9).iter().collect::<Vec<_>>(), vec![(3, 4), (7, 9)]);
+    /// ```
+    pub fn complement(&self, low: T, high: T) -> RangeSet<T> {
+        let mut bound = RangeSet::new();
+        bound.insert(low, high);
+        return bound.difference(self);
+    }
+}
+
+impl<T: PrimInt> FromIterator<(T, T)> for RangeSet<T> {
+    fn from_iter<I: IntoIterator<Item = (T, T)>>(iter: I) -> Self {
+        let mut result = RangeSet::new();
+        for (s, e) in iter {
+            result.insert(s, e);
+        }
+        return result;
+    }
+}
+
+impl<T: PrimInt> Extend<(T, T)> for RangeSet<T> {
+    fn extend<I: IntoIterator<Item = (T, T)>>(&mut self, iter: I) {
+        for (s, e) in iter {
+            self.insert(s, e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn insert_swallows_many() {
+        let mut set: RangeSet<i32> = vec![(1, 2), (4, 5), (7, 8), (20, 30)].into_iter().collect();
+        set.insert(0, 10);
+        assert_eq!(set.iter().collect::<Vec<_>>(), vec![(0, 10), (20, 30)]);
+        // Inside an existing range changes nothing
+        set.insert(22, 25);
+        assert_eq!(set.range_count(), 2);
+        // Touching on both sides joins everything
+        set.insert(11, 19);
+        assert_eq!(set.iter().collect::<Vec<_>>(), vec![(0, 30)]);
+    }
+
+    #[test]
+    fn insert_type_limits() {
+        let mut set = RangeSet::new();
+        set.insert(250u8, 255);
+        set.insert(0, 5);
+        set.insert(6, 249);
+        assert_eq!(set.iter().collect::<Vec<_>>(), vec![(0, 255)]);
+        assert_eq!(set.covered_length(), 256);
+        set.remove(0, 0);
+        set.remove(255, 255);
+        assert_eq!(set.bounds(), Some((1, 254)));
+
+        let full: RangeSet<i64> = vec![(i64::MIN, i64::MAX)].into_iter().collect();
+        assert_eq!(full.covered_length(), 1 << 64);
+        assert!(full.complement(i64::MIN, i64::MAX).is_empty());
+    }
+
+    #[test]
+    fn remove_across_ranges() {
+        let mut set: RangeSet<i32> = vec![(1, 5), (8, 12), (15, 20)].into_iter().collect();
+        set.remove(3, 16);
+        assert_eq!(set.iter().collect::<Vec<_>>(), vec![(1, 2), (17, 20)]);
+        set.remove(-10, 100);
+        assert!(set.is_empty());
+        assert_eq!(set.bounds(), None);
+    }
+
+    #[test]
+    fn matches_brute_force() {
+        let a: RangeSet<i32> = vec![(0, 4), (10, 12), (15, 30), (33, 33)]
+            .into_iter()
+            .collect();
+        let b: RangeSet<i32> = vec![(3, 11), (13, 14), (20, 25), (33, 40)]
+            .into_iter()
+            .collect();
+        let union = a.union(&b);
+        let intersection = a.intersection(&b);
+        let difference = a.difference(&b);
+        let complement = a.complement(-5, 45);
+        for v in -5..=45 {
+            let (in_a, in_b) = (a.contains(v), b.contains(v));
+            assert_eq!(union.contains(v), in_a || in_b, "{}", v);
+            assert_eq!(intersection.contains(v), in_a && in_b, "{}", v);
+            assert_eq!(difference.contains(v), in_a && !in_b, "{}", v);
+            assert_eq!(complement.contains(v), !in_a, "{}", v);
+        }
+        // 10..=12 touches 13..=14 touches 15..=30
+        assert_eq!(union.range_count(), 2);
+        let in_gaps: i32 = a.gaps().map(|(s, e)| e - s + 1).sum();
+        assert_eq!(in_gaps as u128 + a.covered_length(), 34);
+    }
+}